@@ -0,0 +1,82 @@
+use plotly::{
+    common::{Orientation, Title},
+    layout::Margin,
+    Bar, Layout, Plot,
+};
+use std::collections::{HashMap, HashSet};
+
+use crate::git::Commit;
+use crate::plots::PLOT_WIDTH;
+
+const UNKNOWN_DOMAIN: &str = "(no email)";
+
+pub struct Affiliation {
+    pub name: String,
+    pub commits: usize,
+    pub authors: usize,
+}
+
+/// The organization a domain is mapped to, also matching subdomains like `eng.example.com`
+pub fn mapped_organization<'a>(
+    domain: &str,
+    mapping: &'a HashMap<String, String>,
+) -> Option<&'a str> {
+    let mut candidate = domain;
+    loop {
+        if let Some(organization) = mapping.get(candidate) {
+            return Some(organization);
+        }
+        match candidate.split_once('.') {
+            Some((_, parent)) if parent.contains('.') => candidate = parent,
+            _ => return None,
+        }
+    }
+}
+
+/// Find the organization for a domain, the domain itself if it isn't mapped
+pub fn organization<'a>(domain: &'a str, mapping: &'a HashMap<String, String>) -> &'a str {
+    match mapped_organization(domain, mapping) {
+        Some(organization) => organization,
+        None if domain.is_empty() => UNKNOWN_DOMAIN,
+        None => domain,
+    }
+}
+
+pub fn get_affiliations(commits: &[Commit], mapping: &HashMap<String, String>) -> Vec<Affiliation> {
+    let mut commit_count: HashMap<&str, usize> = HashMap::new();
+    let mut authors: HashMap<&str, HashSet<&str>> = HashMap::new();
+    for commit in commits {
+        let name = organization(commit.email_domain(), mapping);
+        *commit_count.entry(name).or_insert(0) += 1;
+        authors.entry(name).or_default().insert(&commit.email);
+    }
+
+    let mut affiliations: Vec<Affiliation> = commit_count
+        .into_iter()
+        .map(|(name, commits)| Affiliation {
+            name: name.to_string(),
+            commits,
+            authors: authors[name].len(),
+        })
+        .collect();
+    affiliations.sort_by_key(|affiliation| std::cmp::Reverse(affiliation.commits));
+    affiliations
+}
+
+pub fn plot_affiliations(affiliations: &[Affiliation], n: usize) -> Plot {
+    let mut plot = Plot::new();
+
+    // largest organization at the top
+    let top_n: Vec<&Affiliation> = affiliations.iter().take(n).rev().collect();
+    let y: Vec<String> = top_n.iter().map(|a| a.name.clone()).collect();
+    let x: Vec<usize> = top_n.iter().map(|a| a.commits).collect();
+
+    plot.add_trace(Bar::new(x, y).orientation(Orientation::Horizontal));
+    let layout = Layout::new()
+        .width(PLOT_WIDTH / 2)
+        .title(Title::from("Commits per organization"))
+        .margin(Margin::new().left(200));
+    plot.set_layout(layout);
+
+    plot
+}
@@ -0,0 +1,126 @@
+use chrono::{Datelike, NaiveDate, Timelike, Weekday};
+use plotly::{
+    common::Title,
+    layout::{Axis, BarMode},
+    Bar, Layout, Plot,
+};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::config::WorkingHours;
+use crate::git::Commit;
+use crate::plots::{Granularity, PLOT_WIDTH};
+
+/// Commits made on weekends and on weekdays outside working hours
+#[derive(Default, Clone, Copy)]
+pub struct OffHours {
+    pub commits: usize,
+    pub weekend: usize,
+    pub after_hours: usize,
+}
+
+impl OffHours {
+    fn add(&mut self, commit: &Commit, hours: &WorkingHours) {
+        let time = commit.local_time();
+        self.commits += 1;
+        if matches!(time.weekday(), Weekday::Sat | Weekday::Sun) {
+            self.weekend += 1;
+        } else if time.hour() < hours.start || time.hour() >= hours.end {
+            self.after_hours += 1;
+        }
+    }
+
+    pub fn weekend_share(&self) -> f64 {
+        self.weekend as f64 / self.commits.max(1) as f64
+    }
+
+    pub fn after_hours_share(&self) -> f64 {
+        self.after_hours as f64 / self.commits.max(1) as f64
+    }
+}
+
+pub struct AfterHours {
+    pub total: OffHours,
+    pub months: BTreeMap<NaiveDate, OffHours>,
+    /// Authors with the most commits, most commits first
+    pub authors: Vec<(String, OffHours)>,
+}
+
+/// Weekend and after-hours commits in total, per month and per author, by the author's local time. Merge commits
+/// are left out, as they are often made by tools
+pub fn get_after_hours(
+    commits: &[Commit],
+    hours: &WorkingHours,
+    top_authors: usize,
+) -> Option<AfterHours> {
+    let mut total = OffHours::default();
+    let mut months: BTreeMap<NaiveDate, OffHours> = BTreeMap::new();
+    let mut authors: HashMap<&str, OffHours> = HashMap::new();
+    for commit in commits.iter().filter(|commit| !commit.is_merge()) {
+        total.add(commit, hours);
+        months
+            .entry(Granularity::Month.bucket(commit.date))
+            .or_default()
+            .add(commit, hours);
+        authors
+            .entry(&commit.author)
+            .or_default()
+            .add(commit, hours);
+    }
+    if total.commits == 0 {
+        return None;
+    }
+
+    let mut authors: Vec<(String, OffHours)> = authors
+        .into_iter()
+        .map(|(author, off_hours)| (author.to_string(), off_hours))
+        .collect();
+    authors.sort_by(|a, b| b.1.commits.cmp(&a.1.commits).then(a.0.cmp(&b.0)));
+    authors.truncate(top_authors);
+    Some(AfterHours {
+        total,
+        months,
+        authors,
+    })
+}
+
+/// Share of the commits per month made on weekends and after hours, stacked
+pub fn plot_after_hours(after_hours: &AfterHours) -> Plot {
+    let mut plot = Plot::new();
+
+    let x: Vec<String> = after_hours
+        .months
+        .keys()
+        .map(|month| month.to_string())
+        .collect();
+    plot.add_trace(
+        Bar::new(
+            x.clone(),
+            after_hours
+                .months
+                .values()
+                .map(|month| 100.0 * month.weekend_share())
+                .collect(),
+        )
+        .name("Weekend"),
+    );
+    plot.add_trace(
+        Bar::new(
+            x,
+            after_hours
+                .months
+                .values()
+                .map(|month| 100.0 * month.after_hours_share())
+                .collect(),
+        )
+        .name("Weekday after hours"),
+    );
+
+    let layout = Layout::new()
+        .width(PLOT_WIDTH - 50)
+        .bar_mode(BarMode::Stack)
+        .title(Title::from("Commits outside working hours per month"))
+        .y_axis(Axis::new().title(Title::from("% of commits")));
+    plot.set_layout(layout);
+
+    plot
+}
@@ -0,0 +1,210 @@
+use chrono::NaiveDate;
+use globset::GlobSet;
+use std::collections::HashMap;
+
+use crate::git::Commit;
+use crate::plots::Granularity;
+
+/// Modified z-score above which a value is an outlier, as recommended by Iglewicz and Hoaglin
+const OUTLIER_SCORE: f64 = 3.5;
+/// Fewest commits in a week for it to be a spike, so sparse histories don't flag every busier week
+const MIN_SPIKE_COMMITS: f64 = 10.0;
+/// Commits of an author that count as the ones of a new author
+const NEW_AUTHOR_COMMITS: usize = 10;
+/// Share of the changed lines that are deletions in a mass deletion
+const MASS_DELETION_SHARE: f64 = 0.9;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    ActivitySpike,
+    NewAuthorSensitiveChange,
+    MassDeletion,
+}
+
+impl EventKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            EventKind::ActivitySpike => "Activity spike",
+            EventKind::NewAuthorSensitiveChange => "Large change by a new author",
+            EventKind::MassDeletion => "Mass deletion",
+        }
+    }
+}
+
+pub struct NotableEvent {
+    pub kind: EventKind,
+    pub date: NaiveDate,
+    pub description: String,
+    /// Commit the event is about, `None` for events spanning a period
+    pub commit: Option<String>,
+    /// Modified z-score of the event, how unusual it is
+    pub score: f64,
+}
+
+fn median(values: &mut [f64]) -> f64 {
+    values.sort_by(|a, b| a.total_cmp(b));
+    let middle = values.len() / 2;
+    match values.len() % 2 {
+        0 => (values[middle - 1] + values[middle]) / 2.0,
+        _ => values[middle],
+    }
+}
+
+/// Modified z-scores of `values`, their distance from the median in median absolute deviations. Falls back to the
+/// mean absolute deviation when more than half of the values are the same, and scores 0 when all of them are
+fn robust_scores(values: &[f64]) -> Vec<f64> {
+    if values.is_empty() {
+        return vec![];
+    }
+    let center = median(&mut values.to_vec());
+    let mut deviations: Vec<f64> = values.iter().map(|value| (value - center).abs()).collect();
+    let mad = median(&mut deviations);
+    let scale = match mad > 0.0 {
+        true => 1.4826 * mad,
+        false => 1.2533 * deviations.iter().sum::<f64>() / deviations.len() as f64,
+    };
+    if scale == 0.0 {
+        return vec![0.0; values.len()];
+    }
+    values
+        .iter()
+        .map(|value| (value - center) / scale)
+        .collect()
+}
+
+/// Weeks with far more commits than usual, counting weeks without commits, and at least 10 of them
+fn activity_spikes(commits: &[&Commit]) -> Vec<NotableEvent> {
+    let (Some(first), Some(last)) = (
+        commits.iter().map(|commit| commit.date).min(),
+        commits.iter().map(|commit| commit.date).max(),
+    ) else {
+        return vec![];
+    };
+    let mut weeks: HashMap<NaiveDate, usize> = HashMap::new();
+    for commit in commits {
+        *weeks
+            .entry(Granularity::Week.bucket(commit.date))
+            .or_insert(0) += 1;
+    }
+    let periods = Granularity::Week.periods(first, last);
+    let counts: Vec<f64> = periods
+        .iter()
+        .map(|week| *weeks.get(week).unwrap_or(&0) as f64)
+        .collect();
+    let usual = median(&mut counts.clone());
+
+    periods
+        .into_iter()
+        .zip(robust_scores(&counts))
+        .zip(counts)
+        .filter(|((_, score), count)| *score > OUTLIER_SCORE && *count >= MIN_SPIKE_COMMITS)
+        .map(|((week, score), count)| NotableEvent {
+            kind: EventKind::ActivitySpike,
+            date: week,
+            description: format!(
+                "{} commits in the week of {}, against a median of {} per week",
+                count, week, usual
+            ),
+            commit: None,
+            score,
+        })
+        .collect()
+}
+
+/// Commits among the first ones of their author that change unusually many lines, including sensitive files
+fn new_author_sensitive_changes(commits: &[&Commit], sensitive: &GlobSet) -> Vec<NotableEvent> {
+    let lines: Vec<f64> = commits
+        .iter()
+        .map(|commit| (commit.lines_changed() as f64).ln_1p())
+        .collect();
+    let mut authored: HashMap<&str, usize> = HashMap::new();
+    let mut events = vec![];
+    // oldest first, to count the commits each author made so far
+    let mut order: Vec<usize> = (0..commits.len()).collect();
+    order.sort_by_key(|i| commits[*i].time);
+    let scores = robust_scores(&lines);
+    for i in order {
+        let commit = commits[i];
+        let count = authored.entry(&commit.author).or_insert(0);
+        *count += 1;
+        if *count > NEW_AUTHOR_COMMITS || scores[i] <= OUTLIER_SCORE {
+            continue;
+        }
+        let Some(file) = commit
+            .files
+            .iter()
+            .find(|file| sensitive.is_match(&file.path))
+        else {
+            continue;
+        };
+        events.push(NotableEvent {
+            kind: EventKind::NewAuthorSensitiveChange,
+            date: commit.date,
+            description: format!(
+                "Commit {} of {} changed {} lines, including {}",
+                count,
+                commit.author,
+                commit.lines_changed(),
+                file.path
+            ),
+            commit: Some(commit.hash.clone()),
+            score: scores[i],
+        });
+    }
+    events
+}
+
+/// Commits that delete unusually many lines while adding hardly any
+fn mass_deletions(commits: &[&Commit]) -> Vec<NotableEvent> {
+    let deleted: Vec<u32> = commits
+        .iter()
+        .map(|commit| commit.files.iter().filter_map(|file| file.deleted).sum())
+        .collect();
+    let scores = robust_scores(
+        &deleted
+            .iter()
+            .map(|deleted| (*deleted as f64).ln_1p())
+            .collect::<Vec<_>>(),
+    );
+    commits
+        .iter()
+        .zip(deleted)
+        .zip(scores)
+        .filter(|((commit, deleted), score)| {
+            *score > OUTLIER_SCORE
+                && *deleted as f64 >= MASS_DELETION_SHARE * commit.lines_changed() as f64
+        })
+        .map(|((commit, deleted), score)| NotableEvent {
+            kind: EventKind::MassDeletion,
+            date: commit.date,
+            description: format!(
+                "{} deleted {} lines across {} files",
+                commit.author,
+                deleted,
+                commit.files.len()
+            ),
+            commit: Some(commit.hash.clone()),
+            score,
+        })
+        .collect()
+}
+
+/// Statistically unusual events in the history, newest first. Changes to files are only looked at `with_files`,
+/// `sensitive` matches the files new authors should be careful with
+pub fn get_notable_events(
+    commits: &[Commit],
+    with_files: bool,
+    sensitive: &GlobSet,
+) -> Vec<NotableEvent> {
+    let commits: Vec<&Commit> = commits
+        .iter()
+        .filter(|commit| !commit.is_merge() && commit.submodule.is_none())
+        .collect();
+    let mut events = activity_spikes(&commits);
+    if with_files {
+        events.extend(new_author_sensitive_changes(&commits, sensitive));
+        events.extend(mass_deletions(&commits));
+    }
+    events.sort_by(|a, b| b.date.cmp(&a.date).then(b.score.total_cmp(&a.score)));
+    events
+}
@@ -0,0 +1,287 @@
+use regex::{Captures, Regex};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::affiliations::mapped_organization;
+use crate::git::Commit;
+use crate::output::write_atomic;
+
+/// Stands in for names that aren't in the history, like code owners who never committed
+const OTHER: &str = "Other";
+/// Domain of pseudonymous emails whose domain doesn't belong to an organization, which could identify the author
+const PLACEHOLDER_DOMAIN: &str = "example.com";
+
+/// Names and emails behind a pseudonym
+#[derive(Serialize, Default)]
+pub struct Identity {
+    names: BTreeSet<String>,
+    emails: BTreeSet<String>,
+}
+
+/// Replaces authors with pseudonyms like `Author 1`, numbered by their commits so the most active author is
+/// `Author 1`
+pub struct Anonymizer {
+    /// Pseudonyms by lowercased name, email and login
+    pseudonyms: HashMap<String, String>,
+    identities: BTreeMap<String, Identity>,
+    /// Email domains of organizations, from the affiliations of the config
+    affiliations: HashMap<String, String>,
+}
+
+/// Login of a GitHub noreply address like `12345+login@users.noreply.github.com`. Local parts of other addresses,
+/// like `dev` or `info`, are shared by unrelated people
+fn login(email: &str) -> Option<&str> {
+    let local_part = email.strip_suffix("@users.noreply.github.com")?;
+    local_part.rsplit('+').next()
+}
+
+impl Anonymizer {
+    pub fn new(commits: &[Commit], affiliations: &HashMap<String, String>) -> Self {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for commit in commits {
+            *counts.entry(&commit.author).or_insert(0) += 1;
+        }
+        let mut authors: Vec<(&str, usize)> = counts.into_iter().collect();
+        authors.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+
+        let mut anonymizer = Anonymizer {
+            pseudonyms: HashMap::new(),
+            identities: BTreeMap::new(),
+            affiliations: affiliations.clone(),
+        };
+        for (author, _) in authors {
+            anonymizer.add_name(author);
+        }
+        // co-authors who never committed themselves are numbered after the authors
+        for commit in commits {
+            for co_author in &commit.co_authors {
+                anonymizer.add_name(co_author);
+            }
+        }
+        for commit in commits {
+            let pseudonym = anonymizer.pseudonyms[&commit.author.to_lowercase()].clone();
+            let identity = anonymizer.identities.get_mut(&pseudonym).unwrap();
+            identity.emails.insert(commit.email.clone());
+            let login = login(&commit.email).map(str::to_lowercase);
+            for key in [Some(commit.email.to_lowercase()), login]
+                .into_iter()
+                .flatten()
+            {
+                anonymizer
+                    .pseudonyms
+                    .entry(key)
+                    .or_insert_with(|| pseudonym.clone());
+            }
+        }
+        anonymizer
+    }
+
+    fn add_name(&mut self, name: &str) {
+        if self.pseudonyms.contains_key(&name.to_lowercase()) {
+            return;
+        }
+        let pseudonym = format!("Author {}", self.identities.len() + 1);
+        self.pseudonyms
+            .insert(name.to_lowercase(), pseudonym.clone());
+        self.identities
+            .entry(pseudonym)
+            .or_default()
+            .names
+            .insert(name.to_string());
+    }
+
+    /// Pseudonym of a name, email or login
+    pub fn name(&self, name: &str) -> String {
+        self.pseudonyms
+            .get(&name.to_lowercase())
+            .map_or(OTHER.to_string(), String::clone)
+    }
+
+    /// Pseudonymous email, the domain is kept if it belongs to an organization of the affiliations, so commits can
+    /// still be grouped by organization
+    pub fn email(&self, email: &str) -> String {
+        self.pseudonymous_email(&self.name(email), email)
+    }
+
+    /// The email of a pseudonym like `author-1@example.com`, at the domain of `email` if it belongs to an
+    /// organization
+    fn pseudonymous_email(&self, pseudonym: &str, email: &str) -> String {
+        let domain = email.rsplit_once('@').map_or("", |(_, domain)| domain);
+        let domain = match mapped_organization(domain, &self.affiliations) {
+            Some(_) => domain,
+            None => PLACEHOLDER_DOMAIN,
+        };
+        format!("{}@{}", pseudonym.to_lowercase().replace(' ', "-"), domain)
+    }
+
+    /// Anonymize a code owner like `@login` or an email, teams like `@org/team` are kept
+    pub fn owner(&self, owner: &str) -> String {
+        match owner.strip_prefix('@') {
+            Some(team) if team.contains('/') => owner.to_string(),
+            Some(login) => format!("@{}", self.name(login)),
+            None => self.email(owner),
+        }
+    }
+
+    /// Pseudonym of a `Name <email>` like in trailers, by the name or else by the email
+    fn identity(&self, identity: &str) -> String {
+        let Some((name, email)) = identity
+            .strip_suffix('>')
+            .and_then(|identity| identity.split_once('<'))
+        else {
+            return self.name(identity.trim());
+        };
+        let (name, email) = (name.trim(), email.trim());
+        let pseudonym = match self.pseudonyms.contains_key(&name.to_lowercase()) {
+            true => self.name(name),
+            false => self.name(email),
+        };
+        format!(
+            "{} <{}>",
+            pseudonym,
+            self.pseudonymous_email(&pseudonym, email)
+        )
+    }
+
+    /// Replace the people named in a commit message: trailers like `Co-authored-by:` and `Signed-off-by:`, the
+    /// login in `Merge pull request #1 from login/branch` and any other email address
+    pub fn message(&self, message: &str) -> String {
+        static TRAILER: OnceLock<Regex> = OnceLock::new();
+        static MERGE: OnceLock<Regex> = OnceLock::new();
+        static EMAIL: OnceLock<Regex> = OnceLock::new();
+        let trailer =
+            TRAILER.get_or_init(|| Regex::new(r"(?i)^([a-z-]+-by:\s*)(.*?)\s*$").unwrap());
+        let merge = MERGE
+            .get_or_init(|| Regex::new(r"^(Merge pull request #\d+ from )([\w.-]+)/").unwrap());
+        let email = EMAIL.get_or_init(|| Regex::new(r"[\w.+-]+@[\w-]+(?:\.[\w-]+)+").unwrap());
+
+        message
+            .lines()
+            .map(|line| {
+                if let Some(captures) = trailer.captures(line) {
+                    return format!("{}{}", &captures[1], self.identity(&captures[2]));
+                }
+                let line = merge.replace(line, |captures: &Captures| {
+                    let login = self.name(&captures[2]).to_lowercase().replace(' ', "-");
+                    format!("{}{}/", &captures[1], login)
+                });
+                email
+                    .replace_all(&line, |captures: &Captures| self.email(&captures[0]))
+                    .into_owned()
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    pub fn anonymize_commits(&self, commits: &mut [Commit]) {
+        for commit in commits {
+            commit.author = self.name(&commit.author);
+            commit.email = self.email(&commit.email);
+            for co_author in &mut commit.co_authors {
+                *co_author = self.name(co_author);
+            }
+            commit.message = self.message(&commit.message);
+        }
+    }
+
+    /// Write the names and emails behind every pseudonym as JSON
+    pub fn write_mapping(&self, path: &Path) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(&self.identities).unwrap();
+        write_atomic(path, json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn commit(author: &str, email: &str, message: &str) -> Commit {
+        Commit {
+            hash: String::new(),
+            parents: vec![],
+            date: NaiveDate::default(),
+            time: 0,
+            offset: 0,
+            committer_time: 0,
+            committer_offset: 0,
+            author: author.to_string(),
+            email: email.to_string(),
+            co_authors: vec![],
+            message: message.to_string(),
+            files: vec![],
+            submodule: None,
+        }
+    }
+
+    #[test]
+    fn anonymizes_trailers_and_merge_subjects() {
+        let mut commits = vec![
+            commit(
+                "Alice Smith",
+                "alice@corp.com",
+                "Add parser\n\nCo-authored-by: Bob Jones <bob@corp.com>\nSigned-off-by: Alice Smith <alice@corp.com>",
+            ),
+            commit(
+                "Bob Jones",
+                "12345+bobj@users.noreply.github.com",
+                "Merge pull request #7 from bobj/parser\n\nAsk carol@example.org about it",
+            ),
+        ];
+        let affiliations = HashMap::from([("corp.com".to_string(), "Corp".to_string())]);
+        let anonymizer = Anonymizer::new(&commits, &affiliations);
+        anonymizer.anonymize_commits(&mut commits);
+
+        assert_eq!(
+            commits[0].message,
+            "Add parser\n\nCo-authored-by: Author 2 <author-2@corp.com>\nSigned-off-by: Author 1 <author-1@corp.com>"
+        );
+        assert_eq!(
+            commits[1].message,
+            "Merge pull request #7 from author-2/parser\n\nAsk other@example.com about it"
+        );
+    }
+
+    #[test]
+    fn replaces_domains_outside_of_organizations() {
+        let mut commits = vec![
+            commit("Alice Smith", "alice@alicesmith.dev", "Fix typo"),
+            commit(
+                "Bob Jones",
+                "bob@eng.corp.com",
+                "Thanks to Alice <alice@alicesmith.dev>",
+            ),
+        ];
+        let affiliations = HashMap::from([("corp.com".to_string(), "Corp".to_string())]);
+        let anonymizer = Anonymizer::new(&commits, &affiliations);
+        anonymizer.anonymize_commits(&mut commits);
+
+        assert_eq!(commits[0].email, "author-1@example.com");
+        assert_eq!(commits[1].email, "author-2@eng.corp.com");
+        assert_eq!(commits[1].message, "Thanks to Alice <author-1@example.com>");
+    }
+
+    #[test]
+    fn only_uses_logins_of_noreply_addresses() {
+        let commits = vec![
+            commit("Alice Smith", "dev@alicesmith.dev", "Fix typo"),
+            commit(
+                "Bob Jones",
+                "12345+bobj@users.noreply.github.com",
+                "Add parser",
+            ),
+        ];
+        let anonymizer = Anonymizer::new(&commits, &HashMap::new());
+
+        assert_eq!(anonymizer.name("dev@alicesmith.dev"), "Author 1");
+        assert_eq!(anonymizer.name("dev@unrelated.org"), "Other");
+        assert_eq!(anonymizer.name("dev"), "Other");
+        assert_eq!(anonymizer.name("bobj"), "Author 2");
+        assert_eq!(
+            anonymizer.message("Ask dev@unrelated.org\n\nMerge pull request #3 from bobj/parser"),
+            "Ask other@example.com\n\nMerge pull request #3 from author-2/parser"
+        );
+    }
+}
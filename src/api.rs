@@ -0,0 +1,284 @@
+use chrono::NaiveDate;
+use plotly::{
+    common::Title,
+    layout::{Axis, BarMode},
+    Bar, Layout, Plot,
+};
+use std::collections::BTreeSet;
+#[cfg(feature = "api")]
+use std::collections::HashMap;
+use std::path::Path;
+#[cfg(feature = "api")]
+use syn::{ImplItem, Item, UseTree, Visibility};
+
+#[cfg(feature = "api")]
+use crate::git::read_blobs;
+use crate::git::{get_tags, run_git};
+use crate::plots::PLOT_WIDTH;
+
+/// Label of the unreleased changes since the latest tag
+const UNRELEASED: &str = "HEAD";
+
+/// Public API of the library crates at a tag, compared to the previous tag
+pub struct ApiRelease {
+    pub tag: String,
+    pub date: NaiveDate,
+    pub items: usize,
+    pub added: usize,
+    /// Items that disappeared, which breaks code using them
+    pub removed: usize,
+}
+
+#[cfg(feature = "api")]
+fn is_public(visibility: &Visibility) -> bool {
+    matches!(visibility, Visibility::Public(_))
+}
+
+/// Names a `pub use` makes public, `*` for glob imports
+#[cfg(feature = "api")]
+fn use_names(tree: &UseTree, names: &mut Vec<String>) {
+    match tree {
+        UseTree::Path(path) => use_names(&path.tree, names),
+        UseTree::Name(name) => names.push(name.ident.to_string()),
+        UseTree::Rename(rename) => names.push(rename.rename.to_string()),
+        UseTree::Glob(_) => names.push("*".to_string()),
+        UseTree::Group(group) => group.items.iter().for_each(|tree| use_names(tree, names)),
+    }
+}
+
+/// Source files of a tag, used to resolve `mod` declarations
+#[cfg(feature = "api")]
+struct Sources {
+    files: HashMap<String, String>,
+}
+
+#[cfg(feature = "api")]
+impl Sources {
+    /// Collect the public items of a module, `dir` is where the files of its child modules are.
+    /// Files syn can't parse are left out
+    fn collect(&self, items: &[Item], module: &str, dir: &str, api: &mut BTreeSet<String>) {
+        for item in items {
+            let mut add = |kind: &str, visibility: &Visibility, name: &syn::Ident| {
+                if is_public(visibility) {
+                    api.insert(format!("{} {}::{}", kind, module, name));
+                }
+            };
+            match item {
+                Item::Const(item) => add("const", &item.vis, &item.ident),
+                Item::Enum(item) => add("enum", &item.vis, &item.ident),
+                Item::Fn(item) => add("fn", &item.vis, &item.sig.ident),
+                Item::Static(item) => add("static", &item.vis, &item.ident),
+                Item::Struct(item) => add("struct", &item.vis, &item.ident),
+                Item::Trait(item) => add("trait", &item.vis, &item.ident),
+                Item::Type(item) => add("type", &item.vis, &item.ident),
+                Item::Union(item) => add("union", &item.vis, &item.ident),
+                Item::Macro(item) => {
+                    // exported macros live at the root of the crate, wherever they are defined
+                    let exported = item
+                        .attrs
+                        .iter()
+                        .any(|attr| attr.path().is_ident("macro_export"));
+                    if let (true, Some(name)) = (exported, &item.ident) {
+                        let root = module.split("::").next().unwrap_or(module);
+                        api.insert(format!("macro {}::{}", root, name));
+                    }
+                }
+                Item::Use(item) if is_public(&item.vis) => {
+                    let mut names = vec![];
+                    use_names(&item.tree, &mut names);
+                    for name in names {
+                        api.insert(format!("use {}::{}", module, name));
+                    }
+                }
+                Item::Impl(item) if item.trait_.is_none() => {
+                    let syn::Type::Path(self_type) = item.self_ty.as_ref() else {
+                        continue;
+                    };
+                    let Some(self_type) = self_type.path.segments.last() else {
+                        continue;
+                    };
+                    for impl_item in &item.items {
+                        let (kind, visibility, name) = match impl_item {
+                            ImplItem::Fn(item) => ("fn", &item.vis, &item.sig.ident),
+                            ImplItem::Const(item) => ("const", &item.vis, &item.ident),
+                            _ => continue,
+                        };
+                        if is_public(visibility) {
+                            api.insert(format!(
+                                "{} {}::{}::{}",
+                                kind, module, self_type.ident, name
+                            ));
+                        }
+                    }
+                }
+                Item::Mod(item) if is_public(&item.vis) => {
+                    let name = item.ident.to_string();
+                    let path = format!("{}::{}", module, name);
+                    api.insert(format!("mod {}", path));
+                    let child_dir = format!("{}{}/", dir, name);
+                    match &item.content {
+                        Some((_, items)) => self.collect(items, &path, &child_dir, api),
+                        None => {
+                            let file = [
+                                format!("{}{}.rs", dir, name),
+                                format!("{}{}/mod.rs", dir, name),
+                            ]
+                            .into_iter()
+                            .find_map(|file| self.parse(&file));
+                            if let Some(file) = file {
+                                self.collect(&file.items, &path, &child_dir, api);
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn parse(&self, file: &str) -> Option<syn::File> {
+        syn::parse_file(self.files.get(file)?).ok()
+    }
+}
+
+/// Public items of every library crate at `rev` under non-empty `scopes`, like `fn my_crate::module::function`
+#[cfg(feature = "api")]
+fn get_public_api(path: &Path, rev: &str, scopes: &[String]) -> Result<BTreeSet<String>, String> {
+    let mut args = vec![
+        "-c",
+        "core.quotePath=false",
+        "ls-tree",
+        "-r",
+        "--name-only",
+        rev,
+        "--",
+    ];
+    args.extend(scopes.iter().map(String::as_str));
+    let tree = run_git(path, &args).map_err(|err| err.to_string())?;
+    let files: Vec<&str> = tree.lines().collect();
+    // the directories of the library crates, with `src/lib.rs` next to their `Cargo.toml`
+    let crates: Vec<&str> = files
+        .iter()
+        .filter_map(|file| file.strip_suffix("src/lib.rs"))
+        .filter(|dir| files.contains(&format!("{}Cargo.toml", dir).as_str()))
+        .collect();
+    if crates.is_empty() {
+        return Ok(BTreeSet::new());
+    }
+
+    let sources: Vec<String> = files
+        .iter()
+        .filter(|file| {
+            file.ends_with(".rs")
+                && crates
+                    .iter()
+                    .any(|dir| file.starts_with(&format!("{}src/", dir)))
+        })
+        .map(|file| file.to_string())
+        .collect();
+    let objects: Vec<String> = sources
+        .iter()
+        .map(|file| format!("{}:{}", rev, file))
+        .collect();
+    let sources = Sources {
+        files: sources
+            .into_iter()
+            .zip(read_blobs(path, &objects)?)
+            .filter_map(|(file, content)| Some((file, content?)))
+            .collect(),
+    };
+
+    let mut api = BTreeSet::new();
+    for dir in crates {
+        let name = match dir.trim_end_matches('/').rsplit('/').next() {
+            Some("") | None => "crate",
+            Some(name) => name,
+        };
+        if let Some(file) = sources.parse(&format!("{}src/lib.rs", dir)) {
+            sources.collect(&file.items, name, &format!("{}src/", dir), &mut api);
+        }
+    }
+    Ok(api)
+}
+
+#[cfg(not(feature = "api"))]
+fn get_public_api(
+    _path: &Path,
+    _rev: &str,
+    _scopes: &[String],
+) -> Result<BTreeSet<String>, String> {
+    Err("it is not available, reinstall git-report with `--features api`".to_string())
+}
+
+/// Count the public items of the library crates at every tag and at HEAD, if it isn't tagged, `None` if no tag
+/// contains a library crate. Non-empty `scopes` only count the crates under them
+pub fn get_api_releases(path: &Path, scopes: &[String]) -> Result<Option<Vec<ApiRelease>>, String> {
+    let tags = get_tags(path).map_err(|err| err.to_string())?;
+    // (label, revision, date)
+    let mut revs: Vec<(String, String, NaiveDate)> = tags
+        .iter()
+        .map(|tag| {
+            (
+                tag.name.clone(),
+                format!("refs/tags/{}", tag.name),
+                tag.date,
+            )
+        })
+        .collect();
+    let latest_tagged = tags.last().map_or("", |tag| tag.commit.as_str());
+    let head = run_git(path, &["rev-parse", "HEAD"]).map_err(|err| err.to_string())?;
+    if latest_tagged != head.trim() {
+        let date =
+            run_git(path, &["log", "-1", "--format=%as", "HEAD"]).map_err(|err| err.to_string())?;
+        if let Ok(date) = NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d") {
+            revs.push((UNRELEASED.to_string(), "HEAD".to_string(), date));
+        }
+    }
+
+    let mut releases = vec![];
+    let mut previous: Option<BTreeSet<String>> = None;
+    for (tag, rev, date) in revs {
+        let api = get_public_api(path, &rev, scopes)?;
+        if api.is_empty() && previous.is_none() {
+            continue;
+        }
+        let before = previous.unwrap_or_default();
+        releases.push(ApiRelease {
+            tag,
+            date,
+            items: api.len(),
+            added: api.difference(&before).count(),
+            removed: before.difference(&api).count(),
+        });
+        previous = Some(api);
+    }
+
+    Ok((!releases.is_empty()).then_some(releases))
+}
+
+pub fn plot_api_changes(releases: &[ApiRelease]) -> Plot {
+    let mut plot = Plot::new();
+
+    let x: Vec<String> = releases.iter().map(|release| release.tag.clone()).collect();
+    let added: Vec<i64> = releases
+        .iter()
+        .map(|release| release.added as i64)
+        .collect();
+    let removed: Vec<i64> = releases
+        .iter()
+        .map(|release| -(release.removed as i64))
+        .collect();
+    plot.add_trace(Bar::new(x.clone(), added).name("Added"));
+    plot.add_trace(Bar::new(x, removed).name("Removed"));
+
+    let layout = Layout::new()
+        .width(PLOT_WIDTH - 50)
+        .bar_mode(BarMode::Relative)
+        .title(Title::from(
+            "Public API items added and removed per release",
+        ))
+        .y_axis(Axis::new().title(Title::from("Items")));
+    plot.set_layout(layout);
+
+    plot
+}
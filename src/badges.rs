@@ -0,0 +1,121 @@
+use std::path::Path;
+
+use crate::output::write_atomic;
+use crate::text::safe_path;
+
+const LABEL_COLOR: &str = "#555";
+const GOOD_COLOR: &str = "#4c1";
+const WARNING_COLOR: &str = "#dfb317";
+const BAD_COLOR: &str = "#e05d44";
+const INFO_COLOR: &str = "#007ec6";
+
+/// Repository metrics shown as badges, the language ones are missing when languages weren't counted
+pub struct BadgeMetrics {
+    pub commits: usize,
+    pub contributors: usize,
+    pub bus_factor: usize,
+    pub lines_of_code: Option<usize>,
+    pub language: Option<String>,
+}
+
+/// Width of text in 11px Verdana, close enough to what shields.io measures for digits and latin letters
+fn text_width(text: &str) -> usize {
+    text.chars()
+        .map(|c| match c {
+            'i' | 'j' | 'l' | '.' | ',' | ':' | ' ' | '\'' | '|' => 4,
+            'f' | 'r' | 't' | 'I' | '(' | ')' | '-' => 5,
+            'm' | 'w' | 'M' | 'W' => 10,
+            c if c.is_ascii_uppercase() => 8,
+            _ => 7,
+        })
+        .sum()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Flat badge like the ones of shields.io, with the label on gray and the value on `color`
+fn render_badge(label: &str, value: &str, color: &str) -> String {
+    let label_width = text_width(label) + 10;
+    let value_width = text_width(value) + 10;
+    let width = label_width + value_width;
+    let (label, value) = (escape(label), escape(value));
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="20" role="img" aria-label="{label}: {value}">
+<title>{label}: {value}</title>
+<linearGradient id="s" x2="0" y2="100%"><stop offset="0" stop-color="#bbb" stop-opacity=".1"/><stop offset="1" stop-opacity=".1"/></linearGradient>
+<clipPath id="r"><rect width="{width}" height="20" rx="3" fill="#fff"/></clipPath>
+<g clip-path="url(#r)"><rect width="{label_width}" height="20" fill="{LABEL_COLOR}"/><rect x="{label_width}" width="{value_width}" height="20" fill="{color}"/><rect width="{width}" height="20" fill="url(#s)"/></g>
+<g fill="#fff" text-anchor="middle" font-family="Verdana,Geneva,DejaVu Sans,sans-serif" font-size="11">
+<text x="{label_x}" y="15" fill="#010101" fill-opacity=".3">{label}</text><text x="{label_x}" y="14">{label}</text>
+<text x="{value_x}" y="15" fill="#010101" fill-opacity=".3">{value}</text><text x="{value_x}" y="14">{value}</text>
+</g>
+</svg>
+"##,
+        label_x = label_width as f64 / 2.0,
+        value_x = label_width as f64 + value_width as f64 / 2.0,
+    )
+}
+
+/// Short form of a count like shields.io shows it, e.g. 1.2k or 3.4M
+fn compact(count: usize) -> String {
+    match count {
+        0..=999 => count.to_string(),
+        1_000..=999_999 => format!("{:.1}k", count as f64 / 1e3).replace(".0k", "k"),
+        _ => format!("{:.1}M", count as f64 / 1e6).replace(".0M", "M"),
+    }
+}
+
+/// A bus factor of 1 means a single author made half of the commits
+fn bus_factor_color(bus_factor: usize) -> &'static str {
+    match bus_factor {
+        0 | 1 => BAD_COLOR,
+        2 => WARNING_COLOR,
+        _ => GOOD_COLOR,
+    }
+}
+
+/// Write a badge per metric as SVG into `dir`
+pub fn write_badges(dir: &Path, metrics: &BadgeMetrics) -> Result<(), String> {
+    std::fs::create_dir_all(dir)
+        .map_err(|err| format!("Could not create directory {}: {}", safe_path(dir), err))?;
+
+    let mut badges = vec![
+        (
+            "commits",
+            render_badge("commits", &compact(metrics.commits), INFO_COLOR),
+        ),
+        (
+            "contributors",
+            render_badge("contributors", &compact(metrics.contributors), INFO_COLOR),
+        ),
+        (
+            "bus-factor",
+            render_badge(
+                "bus factor",
+                &metrics.bus_factor.to_string(),
+                bus_factor_color(metrics.bus_factor),
+            ),
+        ),
+    ];
+    if let Some(lines) = metrics.lines_of_code {
+        badges.push((
+            "lines-of-code",
+            render_badge("lines of code", &compact(lines), INFO_COLOR),
+        ));
+    }
+    if let Some(language) = &metrics.language {
+        badges.push(("language", render_badge("language", language, INFO_COLOR)));
+    }
+
+    for (name, badge) in badges {
+        let path = dir.join(format!("{}.svg", name));
+        write_atomic(&path, badge)
+            .map_err(|err| format!("Could not write {}: {}", safe_path(&path), err))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,240 @@
+use plotly::{
+    common::{Orientation, Title},
+    layout::{BarMode, Margin},
+    Bar, Layout, Plot,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+
+use crate::git::run_git;
+use crate::plots::PLOT_WIDTH;
+
+/// Upper bounds in days and labels of the line age buckets
+const AGE_BUCKETS: [(i64, &str); 6] = [
+    (30, "< 1 month"),
+    (182, "1-6 months"),
+    (365, "6-12 months"),
+    (730, "1-2 years"),
+    (1826, "2-5 years"),
+    (i64::MAX, "> 5 years"),
+];
+
+/// Surviving lines of a file last changed by the same commit
+#[derive(Serialize, Deserialize)]
+pub struct BlamedLines {
+    pub path: String,
+    pub author: String,
+    /// Days between the commit that last changed the lines and HEAD
+    pub age_days: i64,
+    pub lines: u32,
+}
+
+pub struct FileAge {
+    pub path: String,
+    pub lines: u32,
+    pub median_age_days: i64,
+}
+
+fn age_bucket(age_days: i64) -> usize {
+    AGE_BUCKETS
+        .iter()
+        .position(|(max, _)| age_days < *max)
+        .unwrap()
+}
+
+/// Blame every text file at HEAD under non-empty `scopes`, this runs `git blame` once per file
+pub fn get_blame_ages(path: &Path, scopes: &[String]) -> Result<Vec<BlamedLines>, String> {
+    let head_time: i64 = run_git(path, &["log", "-1", "--format=%ct"])
+        .map_err(|err| err.to_string())?
+        .trim()
+        .parse()
+        .map_err(|err| format!("Could not read the time of HEAD: {}", err))?;
+    // `-I` skips binary files, which can't be blamed line by line
+    let mut args = vec![
+        "-c",
+        "core.quotePath=false",
+        "grep",
+        "-I",
+        "-l",
+        "-e",
+        "",
+        "HEAD",
+        "--",
+    ];
+    args.extend(scopes.iter().map(String::as_str));
+    let files = run_git(path, &args).map_err(|err| err.to_string())?;
+    let files: Vec<&str> = files
+        .lines()
+        .filter_map(|line| line.strip_prefix("HEAD:"))
+        .collect();
+
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let chunks: Vec<&[&str]> = files.chunks(files.len().div_ceil(threads).max(1)).collect();
+    let results: Vec<Result<Vec<BlamedLines>, String>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = chunks
+            .into_iter()
+            .map(|chunk| {
+                scope.spawn(move || {
+                    let mut blamed = vec![];
+                    for file in chunk {
+                        blamed.extend(blame_file(path, file, head_time)?);
+                    }
+                    Ok(blamed)
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("git blame thread panicked"))
+            .collect()
+    });
+
+    let mut blamed = vec![];
+    for result in results {
+        blamed.extend(result?);
+    }
+    Ok(blamed)
+}
+
+fn blame_file(path: &Path, file: &str, head_time: i64) -> Result<Vec<BlamedLines>, String> {
+    let output = Command::new("git")
+        .args(["blame", "--porcelain", "HEAD", "--", file])
+        .current_dir(path)
+        .output()
+        .map_err(|err| format!("Failed to execute git blame: {}", err))?;
+    if !output.status.success() {
+        return Err(format!(
+            "git blame failed for {}: {}",
+            file,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let output = String::from_utf8_lossy(&output.stdout);
+
+    // the porcelain format repeats a `<hash> <line> <line>` header for every line, but only describes
+    // the commit the first time it appears
+    let mut lines: HashMap<&str, u32> = HashMap::new();
+    let mut committers: HashMap<&str, &str> = HashMap::new();
+    let mut times: HashMap<&str, i64> = HashMap::new();
+    let mut current = "";
+    for line in output.lines() {
+        if line.starts_with('\t') {
+            *lines.entry(current).or_insert(0) += 1;
+        } else if let Some(name) = line.strip_prefix("committer ") {
+            committers.insert(current, name);
+        } else if let Some(time) = line.strip_prefix("committer-time ") {
+            times.insert(current, time.parse().unwrap_or(head_time));
+        } else if let Some((hash, _)) = line.split_once(' ') {
+            if hash.len() >= 40 && hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+                current = hash;
+            }
+        }
+    }
+
+    Ok(lines
+        .into_iter()
+        .map(|(hash, count)| BlamedLines {
+            path: file.to_string(),
+            author: committers.get(hash).unwrap_or(&"").to_string(),
+            age_days: (head_time - times.get(hash).unwrap_or(&head_time)) / 86400,
+            lines: count,
+        })
+        .collect())
+}
+
+/// The largest files with the median age of their lines, most lines first
+pub fn get_file_ages(blamed: &[BlamedLines], n: usize) -> Vec<FileAge> {
+    let mut files: HashMap<&str, Vec<(i64, u32)>> = HashMap::new();
+    for lines in blamed {
+        files
+            .entry(&lines.path)
+            .or_default()
+            .push((lines.age_days, lines.lines));
+    }
+
+    let mut ages: Vec<FileAge> = files
+        .into_iter()
+        .map(|(path, mut ages)| {
+            ages.sort();
+            let total: u32 = ages.iter().map(|(_, lines)| lines).sum();
+            let mut seen = 0;
+            let median_age_days = ages
+                .iter()
+                .find(|(_, lines)| {
+                    seen += lines;
+                    seen * 2 >= total
+                })
+                .map_or(0, |(age, _)| *age);
+            FileAge {
+                path: path.to_string(),
+                lines: total,
+                median_age_days,
+            }
+        })
+        .collect();
+    ages.sort_by(|a, b| b.lines.cmp(&a.lines).then(a.path.cmp(&b.path)));
+    ages.truncate(n);
+    ages
+}
+
+pub fn plot_line_age_histogram(blamed: &[BlamedLines]) -> Plot {
+    let mut plot = Plot::new();
+
+    let mut lines = [0u32; AGE_BUCKETS.len()];
+    for blamed_lines in blamed {
+        lines[age_bucket(blamed_lines.age_days)] += blamed_lines.lines;
+    }
+    let x: Vec<&str> = AGE_BUCKETS.iter().map(|(_, label)| *label).collect();
+    plot.add_trace(Bar::new(x, lines.to_vec()));
+
+    let layout = Layout::new()
+        .width(PLOT_WIDTH / 2)
+        .title(Title::from("Age of surviving lines"));
+    plot.set_layout(layout);
+
+    plot
+}
+
+/// Share of each author's surviving lines per age bucket, for the authors with the most surviving lines
+pub fn plot_code_freshness(blamed: &[BlamedLines], n: usize) -> Plot {
+    let mut plot = Plot::new();
+
+    let mut per_author: HashMap<&str, [u32; AGE_BUCKETS.len()]> = HashMap::new();
+    for lines in blamed {
+        per_author.entry(&lines.author).or_default()[age_bucket(lines.age_days)] += lines.lines;
+    }
+    let mut authors: Vec<(&str, [u32; AGE_BUCKETS.len()])> = per_author.into_iter().collect();
+    authors.sort_by_key(|(author, lines)| (std::cmp::Reverse(lines.iter().sum::<u32>()), *author));
+    authors.truncate(n);
+    // most surviving lines at the top
+    authors.reverse();
+
+    let y: Vec<String> = authors
+        .iter()
+        .map(|(author, _)| author.to_string())
+        .collect();
+    for (bucket, (_, label)) in AGE_BUCKETS.iter().enumerate() {
+        let x: Vec<f64> = authors
+            .iter()
+            .map(|(_, lines)| {
+                100.0 * lines[bucket] as f64 / lines.iter().sum::<u32>().max(1) as f64
+            })
+            .collect();
+        plot.add_trace(
+            Bar::new(x, y.clone())
+                .orientation(Orientation::Horizontal)
+                .name(*label),
+        );
+    }
+
+    let layout = Layout::new()
+        .width(PLOT_WIDTH / 2)
+        .bar_mode(BarMode::Stack)
+        .title(Title::from("Code freshness per author (% of lines)"))
+        .margin(Margin::new().left(150));
+    plot.set_layout(layout);
+
+    plot
+}
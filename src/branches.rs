@@ -0,0 +1,125 @@
+use chrono::NaiveDate;
+use plotly::{common::Title, Bar, Layout, Plot};
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::error::Error;
+use crate::git::run_git;
+use crate::plots::PLOT_WIDTH;
+
+/// Upper bounds of the buckets of days since branches forked
+const AGE_BUCKETS: [i64; 5] = [7, 30, 90, 180, 365];
+/// Branches with their own commits that forked more days ago than this are an integration risk
+const RISK_DAYS: i64 = 90;
+
+pub struct Branch {
+    /// Short name, like `feature` or `origin/feature`
+    pub name: String,
+    /// Commits on the branch that HEAD doesn't have, and the other way around
+    pub ahead: usize,
+    pub behind: usize,
+    /// Date of the commit the branch forked from HEAD at
+    pub forked: NaiveDate,
+    /// Days from the fork point to the latest commit on HEAD
+    pub days: i64,
+    pub last_commit: NaiveDate,
+}
+
+impl Branch {
+    pub fn at_risk(&self) -> bool {
+        self.days > RISK_DAYS
+    }
+}
+
+fn count(path: &Path, range: &str, scopes: &[String]) -> Result<usize, Error> {
+    let mut args = vec!["rev-list", "--count", range, "--"];
+    args.extend(scopes.iter().map(String::as_str));
+    Ok(run_git(path, &args)?.trim().parse().unwrap_or(0))
+}
+
+fn commit_date(path: &Path, revision: &str) -> Result<Option<NaiveDate>, Error> {
+    let date = run_git(path, &["log", "-1", "--format=%cs", revision])?;
+    Ok(NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").ok())
+}
+
+/// Local and remote branches with commits that aren't on HEAD, longest diverged first. Remote branches pointing to
+/// the same commit as a local one are left out. Non-empty `scopes` only count the commits changing files under them
+pub fn get_divergent_branches(path: &Path, scopes: &[String]) -> Result<Vec<Branch>, Error> {
+    let refs = run_git(
+        path,
+        &[
+            "for-each-ref",
+            "--format=%(refname:short)%09%(objectname)%09%(symref)%09%(committerdate:short)",
+            "refs/heads",
+            "refs/remotes",
+        ],
+    )?;
+    let head = run_git(path, &["rev-parse", "HEAD"])?.trim().to_string();
+    let Some(head_date) = commit_date(path, "HEAD")? else {
+        return Ok(vec![]);
+    };
+
+    let mut seen: HashSet<&str> = HashSet::from([head.as_str()]);
+    let mut branches = vec![];
+    for line in refs.lines() {
+        let fields: Vec<&str> = line.split('\t').collect();
+        let [name, object, symref, date] = fields[..] else {
+            continue;
+        };
+        // like `origin/HEAD`, which points to another branch
+        if !symref.is_empty() || !seen.insert(object) {
+            continue;
+        }
+        let ahead = count(path, &format!("HEAD..{}", object), scopes)?;
+        if ahead == 0 {
+            continue;
+        }
+        let fork_point = run_git(path, &["merge-base", "HEAD", object])?;
+        let (Some(forked), Ok(last_commit)) = (
+            commit_date(path, fork_point.trim())?,
+            NaiveDate::parse_from_str(date, "%Y-%m-%d"),
+        ) else {
+            continue;
+        };
+        branches.push(Branch {
+            name: name.to_string(),
+            ahead,
+            behind: count(path, &format!("{}..HEAD", object), scopes)?,
+            forked,
+            days: (head_date - forked).num_days().max(0),
+            last_commit,
+        });
+    }
+    branches.sort_by(|a, b| b.days.cmp(&a.days).then(a.name.cmp(&b.name)));
+    Ok(branches)
+}
+
+/// Number of divergent branches per bucket of days since they forked
+pub fn plot_branch_ages(branches: &[Branch]) -> Plot {
+    let mut plot = Plot::new();
+
+    let mut labels: Vec<String> = vec![];
+    let mut lower = 0;
+    for upper in AGE_BUCKETS {
+        labels.push(format!("{}-{}", lower, upper));
+        lower = upper + 1;
+    }
+    labels.push(format!(">{}", AGE_BUCKETS[AGE_BUCKETS.len() - 1]));
+
+    let mut counts = vec![0; labels.len()];
+    for branch in branches {
+        let bucket = AGE_BUCKETS
+            .iter()
+            .position(|upper| branch.days <= *upper)
+            .unwrap_or(AGE_BUCKETS.len());
+        counts[bucket] += 1;
+    }
+
+    plot.add_trace(Bar::new(labels, counts));
+    let layout = Layout::new()
+        .width(PLOT_WIDTH / 2)
+        .title(Title::from("Days since branches forked"));
+    plot.set_layout(layout);
+
+    plot
+}
@@ -0,0 +1,112 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::config::Highlight;
+
+/// Version of the bundle format, bumped whenever a change would break rendering older bundles, see `docs/bundle.md`
+pub const BUNDLE_VERSION: u32 = 1;
+const FORMAT: &str = "gitreportbundle";
+
+/// Everything needed to render the report without the repository
+#[derive(Serialize, Deserialize)]
+pub struct Manifest {
+    pub format: String,
+    pub version: u32,
+    /// Version of git-report that wrote the bundle
+    pub generator: String,
+    pub locale: String,
+    #[serde(default)]
+    pub highlights: HashMap<String, Highlight>,
+}
+
+pub struct Bundle {
+    pub manifest: Manifest,
+    /// The data behind the report, like `--export-json` writes it
+    pub report: serde_json::Value,
+    /// Styled plotly figures by plot name
+    pub figures: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Manifest {
+    pub fn new(locale: &str, highlights: &HashMap<String, Highlight>) -> Self {
+        Manifest {
+            format: FORMAT.to_string(),
+            version: BUNDLE_VERSION,
+            generator: format!("git-report {}", env!("CARGO_PKG_VERSION")),
+            locale: locale.to_string(),
+            highlights: highlights.clone(),
+        }
+    }
+}
+
+/// Write the bundle as a zip archive of `manifest.json`, `report.json` and `figures.json`
+#[cfg(feature = "bundle")]
+pub fn write_bundle(path: &Path, bundle: &Bundle) -> Result<(), String> {
+    use crate::output::write_atomic;
+    use std::io::{Cursor, Write};
+
+    let mut zip = zip::ZipWriter::new(Cursor::new(vec![]));
+    let options = zip::write::SimpleFileOptions::default();
+    let entries = [
+        ("manifest.json", serde_json::to_vec_pretty(&bundle.manifest)),
+        ("report.json", serde_json::to_vec_pretty(&bundle.report)),
+        ("figures.json", serde_json::to_vec(&bundle.figures)),
+    ];
+    for (name, content) in entries {
+        let content = content.map_err(|err| err.to_string())?;
+        zip.start_file(name, options)
+            .map_err(|err| format!("Could not add {}: {}", name, err))?;
+        zip.write_all(&content)
+            .map_err(|err| format!("Could not add {}: {}", name, err))?;
+    }
+    let archive = zip.finish().map_err(|err| err.to_string())?;
+    write_atomic(path, archive.into_inner()).map_err(|err| err.to_string())
+}
+
+#[cfg(feature = "bundle")]
+fn read_entry<T: serde::de::DeserializeOwned>(
+    zip: &mut zip::ZipArchive<std::fs::File>,
+    name: &str,
+) -> Result<T, String> {
+    use std::io::Read;
+
+    let mut content = String::new();
+    zip.by_name(name)
+        .map_err(|err| format!("{} is missing: {}", name, err))?
+        .read_to_string(&mut content)
+        .map_err(|err| format!("Could not read {}: {}", name, err))?;
+    serde_json::from_str(&content).map_err(|err| format!("Could not parse {}: {}", name, err))
+}
+
+#[cfg(feature = "bundle")]
+pub fn read_bundle(path: &Path) -> Result<Bundle, String> {
+    let file = std::fs::File::open(path).map_err(|err| err.to_string())?;
+    let mut zip = zip::ZipArchive::new(file).map_err(|err| format!("Not a bundle: {}", err))?;
+    let manifest: Manifest = read_entry(&mut zip, "manifest.json")?;
+    if manifest.format != FORMAT {
+        return Err(format!("Not a bundle, the format is {:?}", manifest.format));
+    }
+    if manifest.version > BUNDLE_VERSION {
+        return Err(format!(
+            "The bundle was written by {} in version {} of the format, this version of git-report reads up to \
+             version {}",
+            manifest.generator, manifest.version, BUNDLE_VERSION
+        ));
+    }
+    Ok(Bundle {
+        report: read_entry(&mut zip, "report.json")?,
+        figures: read_entry(&mut zip, "figures.json")?,
+        manifest,
+    })
+}
+
+#[cfg(not(feature = "bundle"))]
+pub fn write_bundle(_path: &Path, _bundle: &Bundle) -> Result<(), String> {
+    Err("bundles are not available, reinstall git-report with `--features bundle`".to_string())
+}
+
+#[cfg(not(feature = "bundle"))]
+pub fn read_bundle(_path: &Path) -> Result<Bundle, String> {
+    Err("bundles are not available, reinstall git-report with `--features bundle`".to_string())
+}
@@ -0,0 +1,56 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, Once};
+
+static CANCELLED: AtomicBool = AtomicBool::new(false);
+/// Temporary directories to remove when the process exits early
+static TEMP_DIRS: Mutex<Vec<PathBuf>> = Mutex::new(vec![]);
+
+/// On SIGINT/SIGTERM, let the current stage finish and write a partial report, a second signal aborts. Only the
+/// first call installs it, so the reports of a run share the handler
+pub fn install_handler() {
+    static INSTALLED: Once = Once::new();
+    INSTALLED.call_once(|| {
+        let result = ctrlc::set_handler(|| {
+            if CANCELLED.swap(true, Ordering::SeqCst) {
+                exit(130);
+            }
+            eprintln!("Cancelling, finishing the current stage and writing a partial report. Press Ctrl-C again to abort.");
+        });
+        if let Err(err) = result {
+            eprintln!("Warning: Could not install signal handler: {}", err);
+        }
+    });
+}
+
+pub fn is_cancelled() -> bool {
+    CANCELLED.load(Ordering::SeqCst)
+}
+
+/// Run a stage, unless the run has been cancelled
+pub fn unless_cancelled<T>(f: impl FnOnce() -> T) -> Option<T> {
+    if is_cancelled() {
+        return None;
+    }
+    Some(f())
+}
+
+/// Remove the directory when the process exits through `exit`, or when it is aborted
+pub fn remove_on_exit(dir: &Path) {
+    TEMP_DIRS.lock().unwrap().push(dir.to_path_buf());
+}
+
+/// Stop tracking a directory that has been removed already
+pub fn forget_temp_dir(dir: &Path) {
+    TEMP_DIRS.lock().unwrap().retain(|temp_dir| temp_dir != dir);
+}
+
+/// Exit the process, removing the temporary directories first
+pub fn exit(code: i32) -> ! {
+    // a poisoned lock means another thread panicked while holding it, the list is still intact
+    let dirs = TEMP_DIRS.lock().unwrap_or_else(|err| err.into_inner());
+    for dir in dirs.iter() {
+        let _ = std::fs::remove_dir_all(dir);
+    }
+    std::process::exit(code);
+}
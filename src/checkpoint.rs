@@ -0,0 +1,105 @@
+use serde::{de::DeserializeOwned, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::error::Error;
+use crate::git::run_git;
+use crate::output::write_atomic;
+
+/// Stores the results of expensive stages with `--checkpoint`, so an interrupted run can continue with `--resume`
+pub struct Checkpoints {
+    dir: PathBuf,
+    /// Checkpoints are only valid for the commit they were created at, and the scopes they were created for
+    key: String,
+    /// Whether results are checkpointed, runs without `--checkpoint` or `--resume` leave no files behind
+    save: bool,
+    resume: bool,
+}
+
+/// FNV-1a of the scopes, unlike `DefaultHasher` stable across Rust releases
+fn scopes_hash(scopes: &[String]) -> u64 {
+    scopes
+        .iter()
+        .flat_map(|scope| scope.bytes().chain([0]))
+        .fold(0xcbf29ce484222325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        })
+}
+
+impl Checkpoints {
+    pub fn new(
+        repo_path: &Path,
+        scopes: &[String],
+        save: bool,
+        resume: bool,
+    ) -> Result<Self, Error> {
+        let git_dir = run_git(repo_path, &["rev-parse", "--absolute-git-dir"])?;
+        let head = run_git(repo_path, &["rev-parse", "HEAD"])?;
+        let key = match scopes {
+            [] => head.trim().to_string(),
+            _ => format!("{}-{:016x}", head.trim(), scopes_hash(scopes)),
+        };
+        Ok(Checkpoints {
+            dir: Path::new(git_dir.trim())
+                .join("git-report")
+                .join("checkpoints"),
+            key,
+            save: save || resume,
+            resume,
+        })
+    }
+
+    fn path(&self, stage: &str) -> PathBuf {
+        self.dir.join(format!("{}-{}.json", stage, self.key))
+    }
+
+    /// Return the checkpointed result of `stage` when resuming, otherwise run it and checkpoint the result when
+    /// checkpointing. Failed results are not checkpointed
+    pub fn try_stage<T, E, F>(&self, stage: &str, f: F) -> Result<T, E>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> Result<T, E>,
+    {
+        if self.resume {
+            if let Some(result) = self.load(stage) {
+                return Ok(result);
+            }
+        }
+        let result = f()?;
+        self.save(stage, &result);
+        Ok(result)
+    }
+
+    fn load<T: DeserializeOwned>(&self, stage: &str) -> Option<T> {
+        let content = std::fs::read(self.path(stage)).ok()?;
+        serde_json::from_slice(&content).ok()
+    }
+
+    /// Failing to write a checkpoint only means the stage has to be redone
+    fn save<T: Serialize>(&self, stage: &str, result: &T) {
+        if !self.save || std::fs::create_dir_all(&self.dir).is_err() {
+            return;
+        }
+        if let Ok(content) = serde_json::to_vec(result) {
+            let _ = write_atomic(&self.path(stage), content);
+        }
+    }
+
+    /// Remove the checkpoints of this commit and scopes once the report has been written, those of other scopes
+    /// are kept for the reports still to be written with `--report-per-scope`
+    pub fn clear(&self) {
+        if !self.save {
+            return;
+        }
+        let suffix = format!("-{}.json", self.key);
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            if entry.file_name().to_string_lossy().ends_with(&suffix) {
+                let _ = std::fs::remove_file(entry.path());
+            }
+        }
+        // only succeeds once no checkpoints are left
+        let _ = std::fs::remove_dir(&self.dir);
+    }
+}
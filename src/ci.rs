@@ -0,0 +1,252 @@
+use chrono::Months;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::{BTreeMap, HashSet};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+
+use crate::codeowners::{CodeOwners, OwnershipStats};
+use crate::config::Thresholds;
+use crate::git::{run_git, Commit};
+use crate::hosting::{post_json, HostingStats};
+use crate::issues::{find_issues, IssuePatterns, IssueReferences};
+use crate::sizes::percentile;
+
+/// Number of months shown in the activity chart of the job summary
+const SUMMARY_MONTHS: u32 = 12;
+
+#[derive(Serialize)]
+pub struct Violation {
+    pub metric: &'static str,
+    pub message: String,
+}
+
+/// Compare the report metrics against the configured thresholds, metrics of skipped sections are not checked
+pub fn check_thresholds(
+    thresholds: &Thresholds,
+    ownership: Option<&OwnershipStats>,
+    references: Option<&IssueReferences>,
+    commit_count: usize,
+    hosting: Option<&HostingStats>,
+) -> Vec<Violation> {
+    let mut violations = vec![];
+
+    if let (Some(max), Some(stats)) = (thresholds.max_unowned_files, ownership) {
+        let unowned = stats.unowned_files as f64 / stats.changed_files.max(1) as f64;
+        if unowned > max {
+            violations.push(Violation {
+                metric: "max_unowned_files",
+                message: format!(
+                    "{:.0}% of changed files have no code owner, the maximum is {:.0}%",
+                    unowned * 100.0,
+                    max * 100.0
+                ),
+            });
+        }
+    }
+
+    if let (Some(min), Some(references)) = (thresholds.min_issue_references, references) {
+        let referencing = references.referencing_commits as f64 / commit_count.max(1) as f64;
+        if referencing < min {
+            violations.push(Violation {
+                metric: "min_issue_references",
+                message: format!(
+                    "{:.0}% of commits reference an issue, the minimum is {:.0}%",
+                    referencing * 100.0,
+                    min * 100.0
+                ),
+            });
+        }
+    }
+
+    let hosting_medians = [
+        (
+            "max_median_days_to_merge",
+            "merge pull requests",
+            thresholds.max_median_days_to_merge,
+            hosting.and_then(|stats| stats.median_days_to_merge()),
+        ),
+        (
+            "max_median_days_to_close",
+            "close issues",
+            thresholds.max_median_days_to_close,
+            hosting.and_then(|stats| stats.median_days_to_close()),
+        ),
+    ];
+    for (metric, action, max, median) in hosting_medians {
+        if let (Some(max), Some(median)) = (max, median) {
+            if median > max {
+                violations.push(Violation {
+                    metric,
+                    message: format!(
+                        "The median time to {} is {} days, the maximum is {} days",
+                        action, median, max
+                    ),
+                });
+            }
+        }
+    }
+
+    violations
+}
+
+/// Commits per month over the last months of the history, oldest first
+pub fn recent_activity(commits: &[Commit]) -> Vec<(String, usize)> {
+    let latest = commits
+        .iter()
+        .map(|commit| commit.date)
+        .max()
+        .unwrap_or_default();
+    let since = latest - Months::new(SUMMARY_MONTHS - 1);
+
+    let mut months: BTreeMap<String, usize> = BTreeMap::new();
+    let mut month = since;
+    while month <= latest {
+        months.insert(month.format("%Y-%m").to_string(), 0);
+        month = month + Months::new(1);
+    }
+    for commit in commits {
+        if let Some(count) = months.get_mut(&commit.date.format("%Y-%m").to_string()) {
+            *count += 1;
+        }
+    }
+    months.into_iter().collect()
+}
+
+/// Escape a message for a workflow command, see
+/// https://docs.github.com/en/actions/using-workflows/workflow-commands-for-github-actions
+fn escape_workflow_data(data: &str) -> String {
+    data.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn append_to_env_file(variable: &str, content: &str) -> Result<(), String> {
+    let path = std::env::var_os(variable).ok_or_else(|| {
+        format!(
+            "${} is not set, is this running in GitHub Actions?",
+            variable
+        )
+    })?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|err| format!("Could not open ${}: {}", variable, err))?;
+    file.write_all(content.as_bytes())
+        .map_err(|err| format!("Could not write ${}: {}", variable, err))
+}
+
+/// Write the job summary, annotate threshold violations and expose the report path as the `report-path` output
+pub fn report_to_github_actions(
+    summary: &str,
+    violations: &[Violation],
+    report: &Path,
+) -> Result<(), String> {
+    for violation in violations {
+        println!(
+            "::warning title=git-report {}::{}",
+            violation.metric,
+            escape_workflow_data(&violation.message)
+        );
+    }
+
+    append_to_env_file("GITHUB_STEP_SUMMARY", &format!("{}\n", summary))?;
+    let report = std::fs::canonicalize(report)
+        .map_err(|err| format!("Could not resolve the report path: {}", err))?;
+    append_to_env_file(
+        "GITHUB_OUTPUT",
+        &format!("report-path={}\n", report.display()),
+    )
+}
+
+/// Metrics of a range of commits, to compare a merge request against its target branch
+#[derive(Serialize)]
+pub struct RangeMetrics {
+    pub commits: usize,
+    pub authors: usize,
+    /// `None` when the history was read without per file changes
+    pub median_lines_changed: Option<u32>,
+    /// Share of commits referencing an issue, from 0 to 1
+    pub issue_references: f64,
+    pub changed_files: usize,
+    /// `None` when the repository has no CODEOWNERS file
+    pub unowned_files: Option<usize>,
+}
+
+pub fn get_range_metrics(
+    commits: &[&Commit],
+    issue_patterns: &IssuePatterns,
+    code_owners: Option<&CodeOwners>,
+    with_files: bool,
+) -> RangeMetrics {
+    let authors: HashSet<&str> = commits
+        .iter()
+        .map(|commit| commit.author.as_str())
+        .collect();
+    let mut sizes: Vec<u32> = commits
+        .iter()
+        .filter(|commit| !commit.is_merge())
+        .map(|commit| commit.lines_changed())
+        .collect();
+    sizes.sort();
+    let referencing = commits
+        .iter()
+        .filter(|commit| !find_issues(&commit.message, issue_patterns).is_empty())
+        .count();
+    let files: HashSet<&str> = commits
+        .iter()
+        .flat_map(|commit| commit.files.iter().map(|file| file.path.as_str()))
+        .collect();
+
+    RangeMetrics {
+        commits: commits.len(),
+        authors: authors.len(),
+        median_lines_changed: with_files.then(|| percentile(&sizes, 50)),
+        issue_references: referencing as f64 / commits.len().max(1) as f64,
+        changed_files: files.len(),
+        unowned_files: code_owners.map(|code_owners| {
+            files
+                .iter()
+                .filter(|path| code_owners.owners(path).is_empty())
+                .count()
+        }),
+    }
+}
+
+fn gitlab_env(variable: &str) -> Result<String, String> {
+    std::env::var(variable).map_err(|_| {
+        format!(
+            "${} is not set, is this running in a GitLab merge request pipeline?",
+            variable
+        )
+    })
+}
+
+/// Commits of the current merge request, those between the merge base with the target branch and HEAD
+pub fn get_merge_request_commits(path: &Path) -> Result<HashSet<String>, String> {
+    let base = gitlab_env("CI_MERGE_REQUEST_DIFF_BASE_SHA")?;
+    Ok(run_git(path, &["rev-list", &format!("{}..HEAD", base)])
+        .map_err(|err| err.to_string())?
+        .lines()
+        .map(String::from)
+        .collect())
+}
+
+/// Post the comment to the current merge request, authenticating with $GITLAB_TOKEN if set and the CI job
+/// token otherwise
+pub fn comment_on_merge_request(comment: &str) -> Result<(), String> {
+    let url = format!(
+        "{}/projects/{}/merge_requests/{}/notes",
+        gitlab_env("CI_API_V4_URL")?,
+        gitlab_env("CI_PROJECT_ID")?,
+        gitlab_env("CI_MERGE_REQUEST_IID")?
+    );
+    let header = match std::env::var("GITLAB_TOKEN") {
+        Ok(token) => format!("PRIVATE-TOKEN: {}", token),
+        Err(_) => format!("JOB-TOKEN: {}", gitlab_env("CI_JOB_TOKEN")?),
+    };
+    post_json(&url, &[header], &json!({ "body": comment }))?;
+    Ok(())
+}
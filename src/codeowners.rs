@@ -0,0 +1,199 @@
+use chrono::Duration;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::git::Commit;
+
+/// Locations GitHub and GitLab look for a CODEOWNERS file, in order of precedence
+const LOCATIONS: [&str; 4] = [
+    ".github/CODEOWNERS",
+    ".gitlab/CODEOWNERS",
+    "CODEOWNERS",
+    "docs/CODEOWNERS",
+];
+/// Changes within this many days of the latest commit count as recent
+const RECENT_DAYS: i64 = 90;
+
+struct Rule {
+    matcher: GlobSet,
+    owners: Vec<String>,
+}
+
+pub struct CodeOwners {
+    /// CODEOWNERS file relative to the repository root
+    pub path: String,
+    rules: Vec<Rule>,
+}
+
+pub struct HotPath {
+    pub path: String,
+    pub changes: usize,
+    pub owners: Vec<String>,
+    /// Recent changes made by one of the owners, and recent changes in total
+    pub recent_by_owners: usize,
+    pub recent: usize,
+}
+
+pub struct OwnershipStats {
+    pub changed_files: usize,
+    pub unowned_files: usize,
+    /// Most changed files and directories, most changes first
+    pub hot_files: Vec<HotPath>,
+    pub hot_directories: Vec<HotPath>,
+}
+
+/// Translate a CODEOWNERS (gitignore style) pattern into globs
+fn pattern_globs(pattern: &str) -> Vec<String> {
+    let trimmed = pattern.trim_end_matches('/');
+    // patterns containing a slash are relative to the repository root, others match at any depth
+    let base = match trimmed.strip_prefix('/') {
+        Some(anchored) => anchored.to_string(),
+        None if trimmed.contains('/') => trimmed.to_string(),
+        None => format!("**/{}", trimmed),
+    };
+    // a pattern matching a directory owns everything inside it, the directory itself is matched as well so
+    // the owners of hot directories can be looked up
+    vec![format!("{}/**", base), base]
+}
+
+fn parse_rule(line: &str) -> Result<Rule, String> {
+    let mut parts = line.split_whitespace();
+    let pattern = parts.next().unwrap_or_default();
+    let mut builder = GlobSetBuilder::new();
+    for glob in pattern_globs(pattern) {
+        builder.add(
+            GlobBuilder::new(&glob)
+                .literal_separator(true)
+                .build()
+                .map_err(|err| format!("Invalid pattern {:?}: {}", pattern, err))?,
+        );
+    }
+    Ok(Rule {
+        matcher: builder
+            .build()
+            .map_err(|err| format!("Invalid pattern {:?}: {}", pattern, err))?,
+        owners: parts.map(String::from).collect(),
+    })
+}
+
+/// Read the CODEOWNERS file of the repository, `None` if there is none
+pub fn get_code_owners(path: &Path) -> Option<Result<CodeOwners, String>> {
+    let location = LOCATIONS
+        .iter()
+        .find(|location| path.join(location).is_file())?;
+    let content = match std::fs::read_to_string(path.join(location)) {
+        Ok(content) => content,
+        Err(err) => return Some(Err(format!("Could not read {}: {}", location, err))),
+    };
+    let rules = content
+        .lines()
+        .map(str::trim)
+        // GitLab sections like `[Docs]` only group rules, they don't change how paths are matched
+        .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with('['))
+        .map(parse_rule)
+        .collect::<Result<Vec<Rule>, String>>()
+        .map_err(|err| format!("{} in {}", err, location));
+    Some(rules.map(|rules| CodeOwners {
+        path: location.to_string(),
+        rules,
+    }))
+}
+
+impl CodeOwners {
+    /// Owners of a path, the last matching rule wins
+    pub fn owners(&self, path: &str) -> &[String] {
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| rule.matcher.is_match(path))
+            .map(|rule| rule.owners.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Replace every owner, e.g. with a pseudonym
+    pub fn map_owners(&mut self, f: impl Fn(&str) -> String) {
+        for rule in &mut self.rules {
+            for owner in &mut rule.owners {
+                *owner = f(owner);
+            }
+        }
+    }
+}
+
+/// Whether the author is one of the owners, teams like `@org/team` can't be resolved and never match
+fn is_owner(commit: &Commit, owners: &[String]) -> bool {
+    let local_part = commit.email.split('@').next().unwrap_or_default();
+    // GitHub noreply addresses look like `12345+login@users.noreply.github.com`
+    let login = local_part.rsplit('+').next().unwrap_or_default();
+    owners.iter().any(|owner| match owner.strip_prefix('@') {
+        Some(user) if !user.contains('/') => {
+            user.eq_ignore_ascii_case(login) || user.eq_ignore_ascii_case(&commit.author)
+        }
+        Some(_) => false,
+        None => owner.eq_ignore_ascii_case(&commit.email),
+    })
+}
+
+fn top_paths(
+    paths: HashMap<&str, (usize, usize, usize)>,
+    code_owners: &CodeOwners,
+    n: usize,
+) -> Vec<HotPath> {
+    let mut hot: Vec<HotPath> = paths
+        .into_iter()
+        .map(|(path, (changes, recent_by_owners, recent))| HotPath {
+            path: path.to_string(),
+            changes,
+            owners: code_owners.owners(path).to_vec(),
+            recent_by_owners,
+            recent,
+        })
+        .collect();
+    hot.sort_by(|a, b| b.changes.cmp(&a.changes).then(a.path.cmp(&b.path)));
+    hot.truncate(n);
+    hot
+}
+
+pub fn get_ownership_stats(
+    commits: &[Commit],
+    code_owners: &CodeOwners,
+    n: usize,
+) -> OwnershipStats {
+    let latest = commits
+        .iter()
+        .map(|commit| commit.date)
+        .max()
+        .unwrap_or_default();
+    let recent_since = latest - Duration::days(RECENT_DAYS);
+
+    // (changes, recent changes by owners, recent changes) per file and per directory
+    let mut files: HashMap<&str, (usize, usize, usize)> = HashMap::new();
+    let mut directories: HashMap<&str, (usize, usize, usize)> = HashMap::new();
+    for commit in commits {
+        let recent = commit.date >= recent_since;
+        for file in &commit.files {
+            let by_owner = recent && is_owner(commit, code_owners.owners(&file.path));
+            let directory = file.path.rsplit_once('/').map_or(".", |(dir, _)| dir);
+            for counts in [
+                files.entry(&file.path).or_default(),
+                directories.entry(directory).or_default(),
+            ] {
+                counts.0 += 1;
+                counts.1 += by_owner as usize;
+                counts.2 += recent as usize;
+            }
+        }
+    }
+
+    let unowned_files = files
+        .keys()
+        .filter(|path| code_owners.owners(path).is_empty())
+        .count();
+    OwnershipStats {
+        changed_files: files.len(),
+        unowned_files,
+        hot_files: top_paths(files, code_owners, n),
+        hot_directories: top_paths(directories, code_owners, n),
+    }
+}
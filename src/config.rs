@@ -0,0 +1,371 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::error::Error;
+use crate::publish::{PublishFormat, PublishTarget};
+use crate::text::safe_path;
+use crate::themes::BuiltinTheme;
+
+pub const CONFIG_FILENAME: &str = "git-report.toml";
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct Config {
+    pub theme: Theme,
+    pub limits: Limits,
+    /// Maps email domains to the organization their authors belong to
+    pub affiliations: HashMap<String, String>,
+    /// Maps team names to the emails or names of their members
+    pub teams: HashMap<String, Vec<String>>,
+    pub issues: Issues,
+    pub fixes: Fixes,
+    pub tests: Tests,
+    pub sensitive: Sensitive,
+    pub sprints: Sprints,
+    pub working_hours: WorkingHours,
+    pub tone: Tone,
+    /// Milestones for the burn-up chart, GitHub or GitLab milestones are used if there are none
+    pub milestones: Vec<Milestone>,
+    pub thresholds: Thresholds,
+    /// Highlighted table cells by column
+    pub highlights: HashMap<String, Highlight>,
+    pub layout: Layout,
+    /// External commands adding sections to the report, only run with `--allow-plugins`
+    pub plugins: Vec<Plugin>,
+    pub publish: Publish,
+}
+
+/// Where and what `git-report publish` publishes
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct Publish {
+    pub target: PublishTarget,
+    pub formats: Vec<PublishFormat>,
+    /// Directory in the target the files are put in, `{date}` is replaced with the date of the run
+    pub prefix: String,
+    /// Local directory with the `directory` target
+    pub directory: PathBuf,
+    /// Branch with the `gh-pages` target
+    pub branch: String,
+    /// Remote the branch is pushed to, it's only committed when empty
+    pub remote: String,
+    /// Bucket with the `s3` target
+    pub bucket: Option<String>,
+    /// S3 endpoint, the bucket is addressed in the path so S3-compatible stores work too
+    pub endpoint: String,
+    pub region: String,
+}
+
+impl Default for Publish {
+    fn default() -> Self {
+        Publish {
+            target: PublishTarget::Directory,
+            formats: vec![PublishFormat::Html, PublishFormat::Json],
+            prefix: String::new(),
+            directory: PathBuf::from("public"),
+            branch: "gh-pages".to_string(),
+            remote: "origin".to_string(),
+            bucket: None,
+            endpoint: "https://s3.amazonaws.com".to_string(),
+            region: "us-east-1".to_string(),
+        }
+    }
+}
+
+/// A command run in the repository, which gets the commits as JSON on stdin and prints HTML or a Plotly figure
+#[derive(Deserialize)]
+pub struct Plugin {
+    /// Heading of the section
+    pub name: String,
+    /// Program and its arguments
+    pub command: Vec<String>,
+}
+
+/// Arrangement of the report panels
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct Layout {
+    /// Rows of panels from top to bottom, the panels of a row are shown side by side
+    pub rows: Vec<Vec<String>>,
+}
+
+/// Limits for report metrics, violations are reported as annotations with `--github-actions`
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct Thresholds {
+    /// Maximum share of changed files without a code owner, from 0 to 1
+    pub max_unowned_files: Option<f64>,
+    /// Minimum share of commits referencing an issue, from 0 to 1
+    pub min_issue_references: Option<f64>,
+    pub max_median_days_to_merge: Option<u32>,
+    pub max_median_days_to_close: Option<u32>,
+}
+
+/// Highlights the table cells of a column with a value above or below a limit
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Highlight {
+    pub above: Option<f64>,
+    pub below: Option<f64>,
+    /// Any CSS color, red by default
+    pub color: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct Issues {
+    /// Regexes matching issue references in commit messages, the first capture group is used as the
+    /// issue name if there is one, otherwise the whole match
+    pub patterns: Vec<String>,
+    /// Keys before the dash of matches that are no issues, like the UTF of UTF-8
+    pub ignored_keys: Vec<String>,
+}
+
+impl Default for Issues {
+    fn default() -> Self {
+        Issues {
+            patterns: vec![
+                r"(?m)^(?:Fixes|Closes|Resolves|Refs):\s*(\S+)".to_string(),
+                r"(?:^|[\s(])(#\d+)\b".to_string(),
+                r"\b([A-Z]{2}[A-Z0-9]*-\d+)\b".to_string(),
+            ],
+            ignored_keys: ["UTF", "SHA", "ISO", "CVE", "RFC"]
+                .map(String::from)
+                .to_vec(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct Fixes {
+    /// Regexes matching the subject line of bug fix commits
+    pub patterns: Vec<String>,
+}
+
+impl Default for Fixes {
+    fn default() -> Self {
+        Fixes {
+            patterns: vec![
+                r"^(?:fix|bugfix|hotfix)(?:\([^)]*\))?!?:".to_string(),
+                r"(?i)\b(?:fix(?:es|ed)?|bugs?|defect|regression|crash(?:es)?)\b".to_string(),
+            ],
+        }
+    }
+}
+
+/// Sprints either follow a fixed cadence from `start`, or an explicit `schedule`
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct Sprints {
+    pub start: Option<NaiveDate>,
+    pub length_days: u32,
+    /// Each sprint lasts until the next one starts, the last one lasts `length_days`
+    pub schedule: Vec<ScheduledSprint>,
+}
+
+impl Sprints {
+    pub fn is_configured(&self) -> bool {
+        self.start.is_some() || !self.schedule.is_empty()
+    }
+}
+
+impl Default for Sprints {
+    fn default() -> Self {
+        Sprints {
+            start: None,
+            length_days: 14,
+            schedule: vec![],
+        }
+    }
+}
+
+/// Hours of the day in the author's time zone, commits outside them or on weekends count as after-hours work
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct WorkingHours {
+    /// First hour of the working day, from 0 to 23
+    pub start: u32,
+    /// Hour the working day ends at, from 1 to 24
+    pub end: u32,
+}
+
+impl Default for WorkingHours {
+    fn default() -> Self {
+        WorkingHours { start: 9, end: 18 }
+    }
+}
+
+/// Words marking the tone of commit messages, matched case-insensitively as whole words
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct Tone {
+    pub frustrated: Vec<String>,
+    pub upbeat: Vec<String>,
+}
+
+impl Default for Tone {
+    fn default() -> Self {
+        let words = |words: &[&str]| words.iter().map(|word| word.to_string()).collect();
+        Tone {
+            frustrated: words(&[
+                "argh",
+                "crap",
+                "damn",
+                "dammit",
+                "finally",
+                "hack",
+                "hacky",
+                "hate",
+                "kludge",
+                "oops",
+                "sigh",
+                "stupid",
+                "ugh",
+                "ugly",
+                "whatever",
+                "why",
+                "workaround",
+                "wtf",
+            ]),
+            upbeat: words(&[
+                "awesome", "cleaner", "cleanup", "elegant", "great", "love", "neat", "nice",
+                "polish", "simplify", "thanks", "tidy", "yay",
+            ]),
+        }
+    }
+}
+
+/// A milestone due at `date`, or at the date of the commit `tag` points to
+#[derive(Deserialize)]
+pub struct Milestone {
+    pub name: String,
+    pub date: Option<NaiveDate>,
+    pub tag: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ScheduledSprint {
+    pub name: Option<String>,
+    pub start: NaiveDate,
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct Tests {
+    /// Globs relative to the repository root of files containing test code
+    pub patterns: Vec<String>,
+}
+
+impl Default for Tests {
+    fn default() -> Self {
+        Tests {
+            patterns: [
+                "**/test/**",
+                "**/tests/**",
+                "**/__tests__/**",
+                "**/*_test.*",
+                "**/test_*.py",
+                "**/*.test.*",
+                "**/*.spec.*",
+                "**/*Test.java",
+                "**/*Tests.cs",
+            ]
+            .iter()
+            .map(|pattern| pattern.to_string())
+            .collect(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct Sensitive {
+    /// Globs relative to the repository root of files whose changes deserve a security review
+    pub patterns: Vec<String>,
+}
+
+impl Default for Sensitive {
+    fn default() -> Self {
+        Sensitive {
+            patterns: [
+                "**/auth/**",
+                "**/crypto/**",
+                "**/payments/**",
+                "**/secrets/**",
+                "**/security/**",
+                ".github/workflows/**",
+            ]
+            .iter()
+            .map(|pattern| pattern.to_string())
+            .collect(),
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct Theme {
+    /// Built-in theme the colors below are applied on top of
+    pub base: BuiltinTheme,
+    /// Hex colors used for the traces of every plot
+    pub palette: Vec<String>,
+    /// Hex color used for headings and table headers, defaults to the first palette color
+    pub accent: Option<String>,
+}
+
+impl Theme {
+    pub fn accent(&self) -> Option<&str> {
+        self.accent
+            .as_deref()
+            .or(self.palette.first().map(|c| c.as_str()))
+    }
+}
+
+/// Repositories above these sizes are analysed in degraded mode
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct Limits {
+    pub max_commits: usize,
+    pub max_files: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_commits: 100_000,
+            max_files: 100_000,
+        }
+    }
+}
+
+/// Load the config from `path`, or from a `git-report.toml` in the repository if no path is given
+pub fn load_config(path: Option<&PathBuf>, repo_path: &Path) -> Result<Config, Error> {
+    let path = match path {
+        Some(path) => path.clone(),
+        None => {
+            let path = repo_path.join(CONFIG_FILENAME);
+            if !path.exists() {
+                return Ok(Config::default());
+            }
+            path
+        }
+    };
+
+    let content = std::fs::read_to_string(&path).map_err(|err| {
+        Error::Input(format!(
+            "Could not read config file {}: {}",
+            safe_path(&path),
+            err
+        ))
+    })?;
+    toml::from_str(&content).map_err(|err| {
+        Error::Input(format!(
+            "Could not parse config file {}: {}",
+            safe_path(&path),
+            err
+        ))
+    })
+}
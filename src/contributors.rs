@@ -0,0 +1,99 @@
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::git::Commit;
+use crate::output::write_atomic;
+
+/// Files listed per contributor, the ones they changed most often
+const TOP_FILES: usize = 10;
+
+#[derive(Serialize)]
+pub struct FileChanges {
+    pub path: String,
+    pub commits: usize,
+}
+
+/// Everything an identity, a name and email pair, contributed to the history
+#[derive(Serialize)]
+pub struct Contributor {
+    pub name: String,
+    pub email: String,
+    pub commits: usize,
+    pub first_commit: NaiveDate,
+    pub last_commit: NaiveDate,
+    /// Empty when the history was read without per file changes
+    pub files: Vec<FileChanges>,
+}
+
+#[derive(Serialize)]
+struct ContributorMap<'a> {
+    generator: String,
+    /// Latest commit of the history the map was made from
+    head: &'a str,
+    contributors: &'a [Contributor],
+}
+
+/// Every name and email pair that authored commits, most commits first
+pub fn get_contributors(commits: &[Commit]) -> Vec<Contributor> {
+    let mut identities: HashMap<(&str, &str), Vec<&Commit>> = HashMap::new();
+    for commit in commits {
+        identities
+            .entry((&commit.author, &commit.email))
+            .or_default()
+            .push(commit);
+    }
+
+    let mut contributors: Vec<Contributor> = identities
+        .into_iter()
+        .map(|((name, email), commits)| {
+            let mut files: HashMap<&str, usize> = HashMap::new();
+            for commit in &commits {
+                for file in &commit.files {
+                    *files.entry(&file.path).or_insert(0) += 1;
+                }
+            }
+            let mut files: Vec<FileChanges> = files
+                .into_iter()
+                .map(|(path, commits)| FileChanges {
+                    path: path.to_string(),
+                    commits,
+                })
+                .collect();
+            files.sort_by(|a, b| b.commits.cmp(&a.commits).then(a.path.cmp(&b.path)));
+            files.truncate(TOP_FILES);
+
+            let dates = commits.iter().map(|commit| commit.date);
+            Contributor {
+                name: name.to_string(),
+                email: email.to_string(),
+                commits: commits.len(),
+                first_commit: dates.clone().min().unwrap_or_default(),
+                last_commit: dates.max().unwrap_or_default(),
+                files,
+            }
+        })
+        .collect();
+    contributors.sort_by(|a, b| {
+        b.commits
+            .cmp(&a.commits)
+            .then(a.name.cmp(&b.name))
+            .then(a.email.cmp(&b.email))
+    });
+    contributors
+}
+
+/// Write the contributors as JSON, with the commit they were collected at
+pub fn write_contributor_map(
+    path: &Path,
+    head: &str,
+    contributors: &[Contributor],
+) -> std::io::Result<()> {
+    let map = ContributorMap {
+        generator: format!("git-report {}", env!("CARGO_PKG_VERSION")),
+        head,
+        contributors,
+    };
+    write_atomic(path, serde_json::to_string_pretty(&map).unwrap())
+}
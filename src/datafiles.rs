@@ -0,0 +1,99 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::error::Error;
+use crate::extensions::file_extension;
+use crate::git::{get_file_sizes, Commit};
+
+/// Names and extensions of the kinds of files that make up data science repositories
+const CATEGORIES: [(&str, &[&str]); 3] = [
+    ("Notebooks", &[".ipynb", ".rmd", ".qmd"]),
+    (
+        "Data",
+        &[
+            ".csv", ".tsv", ".parquet", ".feather", ".arrow", ".avro", ".orc", ".jsonl", ".ndjson",
+            ".h5", ".hdf5", ".npy", ".npz", ".xls", ".xlsx",
+        ],
+    ),
+    (
+        "Models",
+        &[
+            ".pt",
+            ".pth",
+            ".ckpt",
+            ".onnx",
+            ".safetensors",
+            ".pkl",
+            ".pickle",
+            ".joblib",
+            ".pb",
+            ".tflite",
+            ".gguf",
+            ".keras",
+            ".mlmodel",
+        ],
+    ),
+];
+
+pub struct DataFiles {
+    pub category: &'static str,
+    /// Files and their size at HEAD
+    pub files: usize,
+    pub bytes: u64,
+    /// Commits changing files of the category and the lines they changed, binary files count as no lines
+    pub commits: usize,
+    pub lines_changed: u32,
+}
+
+fn category(path: &str) -> Option<usize> {
+    let extension = file_extension(path)?;
+    CATEGORIES
+        .iter()
+        .position(|(_, extensions)| extensions.contains(&extension.as_str()))
+}
+
+/// Count, size and churn of notebooks, data files and model weights, `None` if the repository has none of them.
+/// The churn is only counted when the commits include the changed files
+pub fn get_data_files(
+    path: &Path,
+    scopes: &[String],
+    commits: &[Commit],
+) -> Result<Option<Vec<DataFiles>>, Error> {
+    let mut stats: Vec<DataFiles> = CATEGORIES
+        .iter()
+        .map(|(name, _)| DataFiles {
+            category: name,
+            files: 0,
+            bytes: 0,
+            commits: 0,
+            lines_changed: 0,
+        })
+        .collect();
+
+    for (file, bytes) in get_file_sizes(path, scopes)? {
+        if let Some(i) = category(&file) {
+            stats[i].files += 1;
+            stats[i].bytes += bytes;
+        }
+    }
+    for commit in commits {
+        let mut changed = HashSet::new();
+        for file in &commit.files {
+            if let Some(i) = category(&file.path) {
+                changed.insert(i);
+                stats[i].lines_changed += file.added.unwrap_or(0) + file.deleted.unwrap_or(0);
+            }
+        }
+        for i in changed {
+            stats[i].commits += 1;
+        }
+    }
+
+    if stats
+        .iter()
+        .all(|stats| stats.files == 0 && stats.commits == 0)
+    {
+        return Ok(None);
+    }
+    Ok(Some(stats))
+}
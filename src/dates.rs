@@ -0,0 +1,89 @@
+use chrono::{DateTime, FixedOffset, Local, NaiveDate};
+use clap::ValueEnum;
+
+use crate::git::Commit;
+
+/// The timestamp that dates a commit
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DateKind {
+    /// When the change was written, kept by rebases and cherry-picks
+    Author,
+    /// When the commit was made, like when it was rebased or merged
+    Committer,
+}
+
+/// The time zone commits are assigned to days in
+#[derive(Clone, Copy)]
+pub enum Timezone {
+    Utc,
+    /// The time zone of the machine making the report, which follows $TZ
+    Local,
+    /// The time zone each commit was made in
+    Author,
+    Fixed(FixedOffset),
+}
+
+impl Timezone {
+    /// Parse `utc`, `local`, `author` or an offset like `+02:00` or `-0530`
+    pub fn parse(timezone: &str) -> Result<Timezone, String> {
+        match timezone.to_lowercase().as_str() {
+            "utc" => return Ok(Timezone::Utc),
+            "local" => return Ok(Timezone::Local),
+            "author" => return Ok(Timezone::Author),
+            _ => {}
+        }
+        let invalid = || {
+            format!(
+                "Invalid time zone {:?}, use utc, local, author or an offset like +02:00",
+                timezone
+            )
+        };
+        let sign = match timezone.get(..1) {
+            Some("+") => 1,
+            Some("-") => -1,
+            _ => return Err(invalid()),
+        };
+        let digits = timezone[1..].replace(':', "");
+        let (hours, minutes) = match digits.len() {
+            2 => (digits.as_str(), "0"),
+            4 => digits.split_at(2),
+            _ => return Err(invalid()),
+        };
+        let hours: i32 = hours.parse().map_err(|_| invalid())?;
+        let minutes: i32 = minutes.parse().map_err(|_| invalid())?;
+        if minutes >= 60 {
+            return Err(invalid());
+        }
+        FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+            .map(Timezone::Fixed)
+            .ok_or_else(invalid)
+    }
+
+    /// The day of a Unix timestamp, `offset` is the time zone the commit was made in in minutes east of UTC
+    fn day(self, time: i64, offset: i32) -> Option<NaiveDate> {
+        let utc = DateTime::from_timestamp(time, 0)?;
+        let date = match self {
+            Timezone::Utc => utc.date_naive(),
+            Timezone::Local => utc.with_timezone(&Local).date_naive(),
+            Timezone::Author => utc
+                .with_timezone(&FixedOffset::east_opt(offset * 60)?)
+                .date_naive(),
+            Timezone::Fixed(offset) => utc.with_timezone(&offset).date_naive(),
+        };
+        Some(date)
+    }
+}
+
+/// Date the commits by their author or committer timestamp, on the day it falls on in `timezone`. The time zone a
+/// commit was made in is kept, so working hours are still judged by the clock of the author or committer
+pub fn normalize_dates(commits: &mut [Commit], kind: DateKind, timezone: Timezone) {
+    for commit in commits {
+        if kind == DateKind::Committer {
+            commit.time = commit.committer_time;
+            commit.offset = commit.committer_offset;
+        }
+        if let Some(date) = timezone.day(commit.time, commit.offset) {
+            commit.date = date;
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use regex::Regex;
+use std::collections::HashMap;
+
+use crate::git::Commit;
+
+#[derive(Clone)]
+pub struct DefectFile {
+    pub path: String,
+    /// Commits changing the file, and the fix commits among them
+    pub changes: usize,
+    pub fixes: usize,
+}
+
+impl DefectFile {
+    /// Share of the changes to the file that were fixes
+    pub fn density(&self) -> f64 {
+        self.fixes as f64 / self.changes as f64
+    }
+}
+
+pub struct DefectStats {
+    pub commits: usize,
+    pub fix_commits: usize,
+    /// Most changed files, most changes first
+    pub hot_files: Vec<DefectFile>,
+    /// Files changed by the most fixes, most fixes first
+    pub defect_files: Vec<DefectFile>,
+}
+
+pub fn compile_fix_patterns(patterns: &[String]) -> Result<Vec<Regex>, String> {
+    patterns
+        .iter()
+        .map(|pattern| {
+            Regex::new(pattern).map_err(|err| format!("Invalid fix pattern {:?}: {}", pattern, err))
+        })
+        .collect()
+}
+
+/// Whether the subject line of the commit message matches one of the fix patterns
+pub fn is_fix(commit: &Commit, patterns: &[Regex]) -> bool {
+    let subject = commit.message.lines().next().unwrap_or_default();
+    patterns.iter().any(|pattern| pattern.is_match(subject))
+}
+
+/// The `n` files changed by the most fix commits, next to the `n` most changed files. Merge commits are left out,
+/// `None` if no commit is a fix
+pub fn get_defect_stats(commits: &[Commit], patterns: &[Regex], n: usize) -> Option<DefectStats> {
+    let mut files: HashMap<&str, (usize, usize)> = HashMap::new();
+    let mut total = 0;
+    let mut fix_commits = 0;
+    for commit in commits.iter().filter(|commit| !commit.is_merge()) {
+        let fix = is_fix(commit, patterns);
+        total += 1;
+        fix_commits += fix as usize;
+        for file in &commit.files {
+            let counts = files.entry(&file.path).or_default();
+            counts.0 += 1;
+            counts.1 += fix as usize;
+        }
+    }
+    if fix_commits == 0 {
+        return None;
+    }
+
+    let files: Vec<DefectFile> = files
+        .into_iter()
+        .map(|(path, (changes, fixes))| DefectFile {
+            path: path.to_string(),
+            changes,
+            fixes,
+        })
+        .collect();
+    let top = |key: fn(&DefectFile) -> (usize, usize)| {
+        let mut top: Vec<&DefectFile> = files.iter().filter(|file| key(file).0 > 0).collect();
+        top.sort_by(|a, b| key(b).cmp(&key(a)).then(a.path.cmp(&b.path)));
+        top.into_iter().take(n).cloned().collect()
+    };
+    Some(DefectStats {
+        commits: total,
+        fix_commits,
+        hot_files: top(|file| (file.changes, file.fixes)),
+        defect_files: top(|file| (file.fixes, file.changes)),
+    })
+}
@@ -0,0 +1,200 @@
+use plotly::{
+    common::Title,
+    layout::{Axis, BarMode},
+    Bar, Layout, Plot,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use crate::error::Error;
+use crate::git::{get_range_log, get_tree_changes, read_blobs, run_git, Commit};
+use crate::languages::{Config, LanguageType};
+use crate::plots::PLOT_WIDTH;
+
+/// Commits listed per side, the latest ones first
+pub const MAX_LISTED_COMMITS: usize = 100;
+
+/// Commits an author made on either side since the revisions diverged
+pub struct AuthorDivergence {
+    pub author: String,
+    pub a: usize,
+    pub b: usize,
+}
+
+pub struct FileChurn {
+    pub path: String,
+    pub lines_added: u32,
+    pub lines_deleted: u32,
+}
+
+/// Change in lines of a language from the tree of A to the tree of B
+pub struct LanguageDelta {
+    pub language: String,
+    pub code: i64,
+    pub comments: i64,
+    pub blanks: i64,
+}
+
+pub struct Divergence {
+    pub merge_base: Option<String>,
+    /// Commits reachable from A but not from B, and the other way around, newest first
+    pub only_a: Vec<Commit>,
+    pub only_b: Vec<Commit>,
+    /// Authors by commits on both sides, most commits first
+    pub authors: Vec<AuthorDivergence>,
+    pub changed_files: usize,
+    pub lines_added: u32,
+    pub lines_deleted: u32,
+    /// Most changed files between the trees
+    pub files: Vec<FileChurn>,
+    /// Languages whose lines changed, largest change first
+    pub languages: Vec<LanguageDelta>,
+}
+
+fn count_authors(only_a: &[Commit], only_b: &[Commit]) -> Vec<AuthorDivergence> {
+    let mut counts: HashMap<&str, (usize, usize)> = HashMap::new();
+    for commit in only_a {
+        counts.entry(&commit.author).or_default().0 += 1;
+    }
+    for commit in only_b {
+        counts.entry(&commit.author).or_default().1 += 1;
+    }
+    let mut authors: Vec<AuthorDivergence> = counts
+        .into_iter()
+        .map(|(author, (a, b))| AuthorDivergence {
+            author: author.to_string(),
+            a,
+            b,
+        })
+        .collect();
+    authors.sort_by(|x, y| (y.a + y.b).cmp(&(x.a + x.b)).then(x.author.cmp(&y.author)));
+    authors
+}
+
+/// Lines of code, comments and blanks per language of the files that differ between the trees of `a` and `b`,
+/// files tokei doesn't recognize are left out
+fn get_language_deltas(path: &Path, a: &str, b: &str) -> Result<Vec<LanguageDelta>, String> {
+    let diff = run_git(
+        path,
+        &[
+            "-c",
+            "core.quotePath=false",
+            "diff",
+            "--raw",
+            "--no-abbrev",
+            "--no-renames",
+            a,
+            b,
+        ],
+    )
+    .map_err(|err| err.to_string())?;
+    let config = Config::default();
+    // lines look like `:<mode> <mode> <object> <object> <status>\t<path>`, with a zero object for added and
+    // deleted files
+    let mut objects: Vec<(LanguageType, String, i64)> = vec![];
+    for line in diff.lines() {
+        let Some((info, file)) = line.split_once('\t') else {
+            continue;
+        };
+        let Some(language) = LanguageType::from_path(file, &config) else {
+            continue;
+        };
+        let fields: Vec<&str> = info.split_whitespace().collect();
+        let [_, _, before, after, _] = fields[..] else {
+            continue;
+        };
+        for (object, sign) in [(before, -1), (after, 1)] {
+            if object.bytes().any(|b| b != b'0') {
+                objects.push((language, object.to_string(), sign));
+            }
+        }
+    }
+
+    let names: Vec<String> = objects
+        .iter()
+        .map(|(_, object, _)| object.clone())
+        .collect();
+    let mut deltas: BTreeMap<LanguageType, (i64, i64, i64)> = BTreeMap::new();
+    for ((language, _, sign), content) in objects.iter().zip(read_blobs(path, &names)?) {
+        let Some(content) = content else {
+            continue;
+        };
+        let stats = language.parse_from_str(content, &config).summarise();
+        let delta = deltas.entry(*language).or_default();
+        delta.0 += sign * stats.code as i64;
+        delta.1 += sign * stats.comments as i64;
+        delta.2 += sign * stats.blanks as i64;
+    }
+
+    let mut languages: Vec<LanguageDelta> = deltas
+        .into_iter()
+        .filter(|(_, delta)| *delta != (0, 0, 0))
+        .map(|(language, (code, comments, blanks))| LanguageDelta {
+            language: language.name().to_string(),
+            code,
+            comments,
+            blanks,
+        })
+        .collect();
+    languages.sort_by_key(|delta| std::cmp::Reverse(delta.code.abs()));
+    Ok(languages)
+}
+
+/// Compare the commits and trees of the resolved revisions `a` and `b`
+pub fn get_divergence(path: &Path, a: &str, b: &str, n: usize) -> Result<Divergence, Error> {
+    let merge_base = run_git(path, &["merge-base", a, b])?.trim().to_string();
+    let only_a = get_range_log(path, &format!("{}..{}", b, a))?;
+    let only_b = get_range_log(path, &format!("{}..{}", a, b))?;
+
+    let changes = get_tree_changes(path, a, b)?;
+    let mut files: Vec<FileChurn> = changes
+        .iter()
+        .map(|change| FileChurn {
+            path: change.path.clone(),
+            lines_added: change.added.unwrap_or(0),
+            lines_deleted: change.deleted.unwrap_or(0),
+        })
+        .collect();
+    let lines_added = files.iter().map(|file| file.lines_added).sum();
+    let lines_deleted = files.iter().map(|file| file.lines_deleted).sum();
+    files.sort_by(|x, y| {
+        (y.lines_added + y.lines_deleted)
+            .cmp(&(x.lines_added + x.lines_deleted))
+            .then(x.path.cmp(&y.path))
+    });
+    files.truncate(n);
+
+    Ok(Divergence {
+        merge_base: (!merge_base.is_empty()).then_some(merge_base),
+        authors: count_authors(&only_a, &only_b),
+        only_a,
+        only_b,
+        changed_files: changes.len(),
+        lines_added,
+        lines_deleted,
+        files,
+        languages: get_language_deltas(path, a, b).map_err(Error::Git)?,
+    })
+}
+
+/// Commits per author on either side, for the `n` authors with the most commits. `names` label the sides
+pub fn plot_divergence_authors(divergence: &Divergence, names: [String; 2], n: usize) -> Plot {
+    let mut plot = Plot::new();
+
+    let authors = &divergence.authors[..divergence.authors.len().min(n)];
+    let x: Vec<String> = authors.iter().map(|author| author.author.clone()).collect();
+    let [a, b] = names;
+    plot.add_trace(Bar::new(x.clone(), authors.iter().map(|author| author.a).collect()).name(a));
+    plot.add_trace(Bar::new(x, authors.iter().map(|author| author.b).collect()).name(b));
+
+    let layout = Layout::new()
+        .width(PLOT_WIDTH - 50)
+        .bar_mode(BarMode::Group)
+        .title(Title::from(
+            "Commits per author since the revisions diverged",
+        ))
+        .y_axis(Axis::new().title(Title::from("Commits")));
+    plot.set_layout(layout);
+
+    plot
+}
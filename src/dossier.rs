@@ -0,0 +1,135 @@
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::codeowners::CodeOwners;
+use crate::git::Commit;
+use crate::plots::Granularity;
+
+/// Authors and coupled files listed per file
+const TOP_AUTHORS: usize = 5;
+const TOP_COUPLED: usize = 5;
+/// Quarters of churn history listed per file
+const QUARTERS: usize = 8;
+/// Commits changing more files than this are left out of the coupling, like formatting the whole codebase
+const MAX_COUPLING_FILES: usize = 50;
+/// Fewest commits two files have to share to be coupled
+const MIN_SHARED_COMMITS: usize = 2;
+
+pub struct Churn {
+    pub quarter: NaiveDate,
+    pub commits: usize,
+    pub lines_changed: u32,
+}
+
+/// A file changed together with the file of the dossier
+pub struct CoupledFile {
+    pub path: String,
+    pub commits: usize,
+    /// Share of the commits of the dossier's file that also changed this one
+    pub share: f64,
+}
+
+/// Everything the history tells about a file
+pub struct Dossier {
+    pub path: String,
+    pub commits: usize,
+    pub lines_added: u32,
+    pub lines_deleted: u32,
+    pub first_change: NaiveDate,
+    pub last_change: NaiveDate,
+    /// Latest quarters with changes, newest first
+    pub churn: Vec<Churn>,
+    /// Most active authors and their commits to the file
+    pub authors: Vec<(String, usize)>,
+    /// Owners in the CODEOWNERS file
+    pub owners: Vec<String>,
+    pub coupled: Vec<CoupledFile>,
+}
+
+/// A dossier for every path that was changed in the history, in the order of `paths`
+pub fn get_dossiers(
+    commits: &[Commit],
+    paths: &BTreeSet<String>,
+    code_owners: Option<&CodeOwners>,
+) -> Vec<Dossier> {
+    let mut changes: HashMap<&str, Vec<&Commit>> = HashMap::new();
+    for commit in commits.iter().filter(|commit| !commit.is_merge()) {
+        for file in &commit.files {
+            if paths.contains(&file.path) {
+                changes.entry(&file.path).or_default().push(commit);
+            }
+        }
+    }
+
+    paths
+        .iter()
+        .filter_map(|path| {
+            let commits = changes.get(path.as_str())?;
+            let file_changes = || {
+                commits
+                    .iter()
+                    .flat_map(|commit| commit.files.iter().filter(|file| &file.path == path))
+            };
+
+            let mut quarters: BTreeMap<NaiveDate, Churn> = BTreeMap::new();
+            let mut authors: HashMap<&str, usize> = HashMap::new();
+            let mut coupled: HashMap<&str, usize> = HashMap::new();
+            for commit in commits {
+                let quarter = Granularity::Quarter.bucket(commit.date);
+                let churn = quarters.entry(quarter).or_insert(Churn {
+                    quarter,
+                    commits: 0,
+                    lines_changed: 0,
+                });
+                churn.commits += 1;
+                churn.lines_changed += commit
+                    .files
+                    .iter()
+                    .filter(|file| &file.path == path)
+                    .map(|file| file.added.unwrap_or(0) + file.deleted.unwrap_or(0))
+                    .sum::<u32>();
+                *authors.entry(&commit.author).or_insert(0) += 1;
+                if commit.files.len() <= MAX_COUPLING_FILES {
+                    for file in commit.files.iter().filter(|file| &file.path != path) {
+                        *coupled.entry(&file.path).or_insert(0) += 1;
+                    }
+                }
+            }
+
+            let mut authors: Vec<(String, usize)> = authors
+                .into_iter()
+                .map(|(author, commits)| (author.to_string(), commits))
+                .collect();
+            authors.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+            authors.truncate(TOP_AUTHORS);
+
+            let mut coupled: Vec<CoupledFile> = coupled
+                .into_iter()
+                .filter(|(_, shared)| *shared >= MIN_SHARED_COMMITS)
+                .map(|(other, shared)| CoupledFile {
+                    path: other.to_string(),
+                    commits: shared,
+                    share: shared as f64 / commits.len() as f64,
+                })
+                .collect();
+            coupled.sort_by(|a, b| b.commits.cmp(&a.commits).then(a.path.cmp(&b.path)));
+            coupled.truncate(TOP_COUPLED);
+
+            let dates = commits.iter().map(|commit| commit.date);
+            Some(Dossier {
+                path: path.clone(),
+                commits: commits.len(),
+                lines_added: file_changes().filter_map(|file| file.added).sum(),
+                lines_deleted: file_changes().filter_map(|file| file.deleted).sum(),
+                first_change: dates.clone().min()?,
+                last_change: dates.max()?,
+                churn: quarters.into_values().rev().take(QUARTERS).collect(),
+                authors,
+                owners: code_owners
+                    .map(|code_owners| code_owners.owners(path).to_vec())
+                    .unwrap_or_default(),
+                coupled,
+            })
+        })
+        .collect()
+}
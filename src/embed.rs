@@ -0,0 +1,116 @@
+use minijinja::{context, Value};
+use std::path::Path;
+
+use crate::error::Error;
+use crate::highlights::Highlights;
+use crate::locale::ReportLocale;
+use crate::output::write_atomic;
+use crate::plots::{render_figure, PLOT_HEIGHT, PLOT_WIDTH};
+use crate::render::render_template;
+use crate::text::safe_path;
+
+static CHART_TEMPLATE: &str = include_str!("../templates/chart.html");
+static EMBED_TEMPLATE: &str = include_str!("../templates/embed.html");
+/// The version of plotly.js bundled with the plotly crate and inlined by --offline, the report loads it as well
+const PLOTLY_URL: &str = "https://cdn.plot.ly/plotly-2.12.1.min.js";
+
+/// Size of the iframe showing a figure, wide and high enough to avoid scrollbars
+fn frame_size(figure: &serde_json::Value) -> (u64, u64) {
+    let layout = &figure["layout"];
+    let width = layout["width"].as_u64().unwrap_or(PLOT_WIDTH as u64);
+    let height = layout["height"].as_u64().unwrap_or(PLOT_HEIGHT as u64);
+    (width + 20, height + 20)
+}
+
+/// Write a standalone page per chart into `dir`, and `embed.html` with an iframe and a script snippet to embed
+/// each of them. Iframes point to the pages below `base_url`, or next to the page they are embedded in without it
+pub fn write_embeds(
+    dir: &Path,
+    figures: &serde_json::Map<String, serde_json::Value>,
+    theme: &Value,
+    base_url: Option<&str>,
+    plotly_js: Option<String>,
+    locale: &ReportLocale,
+) -> Result<(), Error> {
+    std::fs::create_dir_all(dir).map_err(|err| {
+        Error::Output(format!(
+            "Could not create directory {}: {}",
+            safe_path(dir),
+            err
+        ))
+    })?;
+    let write = |filename: &str, content: String| {
+        let path = dir.join(filename);
+        write_atomic(&path, content).map_err(|source| Error::Write { path, source })
+    };
+
+    let mut charts = vec![];
+    for (name, figure) in figures {
+        let filename = format!("{}.html", name);
+        let page = render_template(
+            "chart.html",
+            CHART_TEMPLATE,
+            context! {
+                name => name,
+                plot => render_figure(name, figure),
+                theme => theme,
+                plotly_url => PLOTLY_URL,
+                plotly_js => plotly_js,
+            },
+            locale.clone(),
+            &Highlights::default(),
+        )?;
+        write(&filename, page)?;
+
+        let src = match base_url {
+            Some(url) => format!("{}/{}", url.trim_end_matches('/'), filename),
+            None => filename,
+        };
+        let (width, height) = frame_size(figure);
+        charts.push(context! {
+            name => name,
+            iframe => format!(
+                "<iframe src=\"{}\" width=\"{}\" height=\"{}\" style=\"border: none\"></iframe>",
+                src, width, height
+            ),
+            script => format!(
+                "<script src=\"{}\" charset=\"utf-8\"></script>\n{}",
+                PLOTLY_URL,
+                render_figure(&format!("git-report-{}", name), figure)
+            ),
+        });
+    }
+    let index = render_template(
+        "embed.html",
+        EMBED_TEMPLATE,
+        context! {
+            charts => charts,
+            base_url => base_url,
+            lang => locale.lang.code(),
+        },
+        locale.clone(),
+        &Highlights::default(),
+    )?;
+    write("embed.html", index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plots::bundled_plotly_js;
+
+    #[test]
+    fn loads_the_bundled_plotly_js_version() {
+        let bundled = bundled_plotly_js();
+        let version = bundled
+            .split("plotly.js v")
+            .nth(1)
+            .and_then(|rest| rest.split_whitespace().next())
+            .unwrap();
+        assert_eq!(
+            PLOTLY_URL,
+            format!("https://cdn.plot.ly/plotly-{}.min.js", version)
+        );
+        assert!(include_str!("../templates/index.html").contains(PLOTLY_URL));
+    }
+}
@@ -0,0 +1,56 @@
+use std::path::PathBuf;
+use thiserror::Error;
+
+use crate::cancel::exit;
+use crate::text::safe_path;
+
+/// Why a run failed. Each kind exits with its own code, so scripts can tell a bad config from a broken repository
+#[derive(Debug, Error)]
+pub enum Error {
+    /// Invalid arguments, config, templates or input files
+    #[error("{0}")]
+    Input(String),
+    #[error("Failed to execute git, is it installed and on your PATH? ({0})")]
+    GitNotFound(#[source] std::io::Error),
+    /// git failed, or the path isn't a repository git can read
+    #[error("{0}")]
+    Git(String),
+    #[error("Could not render {name}: {source}")]
+    Render {
+        name: String,
+        source: minijinja::Error,
+    },
+    #[error("Could not write {}: {source}", safe_path(.path))]
+    Write {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// Writing an output that isn't a single file, like a SQLite database or a directory of images
+    #[error("{0}")]
+    Output(String),
+    /// The hosting or CI service failed or rejected a request
+    #[error("{0}")]
+    Service(String),
+    /// The run was cancelled with Ctrl-C, after writing a partial report
+    #[error("{0}")]
+    Cancelled(String),
+}
+
+impl Error {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Input(_) => 2,
+            Error::GitNotFound(_) | Error::Git(_) => 3,
+            Error::Render { .. } => 4,
+            Error::Write { .. } | Error::Output(_) => 5,
+            Error::Service(_) => 6,
+            Error::Cancelled(_) => 130,
+        }
+    }
+
+    /// Print the error and exit with its code
+    pub fn exit(self) -> ! {
+        eprintln!("Error: {}", self);
+        exit(self.exit_code());
+    }
+}
@@ -0,0 +1,258 @@
+use minijinja::Value;
+use plotly::Plot;
+use std::path::Path;
+
+use crate::error::Error;
+use crate::output::write_atomic;
+use crate::plots::{plot_figure, PlotStyle};
+use crate::text::safe_path;
+use crate::timing::Timings;
+
+/// Write every plot as a PNG and an SVG into `dir`, rendered with kaleido
+#[cfg(feature = "kaleido")]
+pub fn export_images(
+    plots: &[(&str, Plot)],
+    style: &PlotStyle,
+    dir: &Path,
+    scale: f64,
+) -> Result<(), Error> {
+    use crate::output::temp_path;
+    use crate::plots::{styled, PLOT_WIDTH};
+    use plotly::ImageFormat;
+
+    std::fs::create_dir_all(dir).map_err(|err| {
+        Error::Output(format!(
+            "Could not create directory {}: {}",
+            safe_path(dir),
+            err
+        ))
+    })?;
+    for (name, plot) in plots {
+        let plot = styled(plot, style);
+        for (format, extension) in [(ImageFormat::PNG, "png"), (ImageFormat::SVG, "svg")] {
+            let filename = dir.join(format!("{}.{}", name, extension));
+            // kaleido appends the extension itself
+            let temp = temp_path(&filename.with_extension(""));
+            let width = style.width.unwrap_or(PLOT_WIDTH);
+            let height = style.height.unwrap_or(PLOT_WIDTH / 2);
+            plot.write_image(&temp, format, width, height, scale);
+            std::fs::rename(temp.with_extension(extension), &filename).map_err(|source| {
+                Error::Write {
+                    path: filename.clone(),
+                    source,
+                }
+            })?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "kaleido"))]
+pub fn export_images(
+    _plots: &[(&str, Plot)],
+    _style: &PlotStyle,
+    _dir: &Path,
+    _scale: f64,
+) -> Result<(), Error> {
+    check_image_export()
+}
+
+/// Fail when git-report is built without image export, to check `--export-images` before the analysis
+pub fn check_image_export() -> Result<(), Error> {
+    match cfg!(feature = "kaleido") {
+        true => Ok(()),
+        false => Err(Error::Input(
+            "Image export is not available, reinstall git-report with `--features kaleido`"
+                .to_string(),
+        )),
+    }
+}
+
+/// Render every frame with kaleido and join them into an animated GIF or WebM with ffmpeg, depending on the
+/// extension of `path`
+#[cfg(feature = "kaleido")]
+pub fn export_animation(
+    frames: &[Plot],
+    style: &PlotStyle,
+    path: &Path,
+    scale: f64,
+) -> Result<(), Error> {
+    use crate::output::temp_path;
+    use crate::plots::{styled, PLOT_WIDTH};
+    use plotly::ImageFormat;
+    use std::process::Command;
+
+    let fail = |message: String| {
+        Error::Output(format!("Could not export {}: {}", safe_path(path), message))
+    };
+
+    let dir = temp_path(path).with_extension("frames");
+    std::fs::create_dir_all(&dir).map_err(|err| fail(err.to_string()))?;
+    for (i, frame) in frames.iter().enumerate() {
+        // kaleido appends the extension itself
+        let filename = dir.join(format!("frame{:05}", i));
+        styled(frame, style).write_image(
+            &filename,
+            ImageFormat::PNG,
+            PLOT_WIDTH,
+            PLOT_WIDTH / 2,
+            scale,
+        );
+    }
+
+    let filter = match path.extension().and_then(|extension| extension.to_str()) {
+        // a palette generated from the frames keeps GIF colors close to the original
+        Some("gif") => "split[a][b];[a]palettegen[palette];[b][palette]paletteuse",
+        Some("webm") => "format=yuv420p",
+        _ => {
+            return Err(fail(
+                "the animation has to be a .gif or .webm file".to_string(),
+            ))
+        }
+    };
+    let temp = temp_path(path);
+    let output = Command::new("ffmpeg")
+        .args(["-y", "-loglevel", "error", "-framerate", "2", "-i"])
+        .arg(dir.join("frame%05d.png"))
+        .args(["-vf", filter, "-f"])
+        .arg(path.extension().unwrap())
+        .arg(&temp)
+        .output()
+        .map_err(|err| {
+            fail(format!(
+                "failed to execute ffmpeg, is it installed? ({})",
+                err
+            ))
+        })?;
+    let _ = std::fs::remove_dir_all(&dir);
+    if !output.status.success() {
+        return Err(fail(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    std::fs::rename(&temp, path).map_err(|err| fail(err.to_string()))
+}
+
+#[cfg(not(feature = "kaleido"))]
+pub fn export_animation(
+    _frames: &[Plot],
+    _style: &PlotStyle,
+    _path: &Path,
+    _scale: f64,
+) -> Result<(), Error> {
+    Err(Error::Input(
+        "Animation export is not available, reinstall git-report with `--features kaleido`"
+            .to_string(),
+    ))
+}
+
+/// Columns of the exported chart data, in the order they are written, trace properties that aren't arrays are left out
+const DATA_COLUMNS: [&str; 6] = ["ids", "labels", "parents", "x", "y", "values"];
+
+fn csv_field(value: Option<&serde_json::Value>) -> String {
+    let text = match value {
+        None | Some(serde_json::Value::Null) => return String::new(),
+        Some(serde_json::Value::String(text)) => text.clone(),
+        Some(value) => value.to_string(),
+    };
+    match text.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", text.replace('"', "\"\"")),
+        false => text,
+    }
+}
+
+/// The traces of a plotly figure as CSV, one row per data point with the name of its trace as the series.
+/// Heatmaps get a row per cell with its `x`, `y` and `z`
+fn figure_csv(figure: &serde_json::Value) -> String {
+    let traces = figure["data"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let heatmap = traces.iter().any(|trace| trace["z"].is_array());
+    let columns: Vec<&str> = match heatmap {
+        true => vec!["x", "y", "z"],
+        false => DATA_COLUMNS
+            .into_iter()
+            .filter(|column| traces.iter().any(|trace| trace[column].is_array()))
+            .collect(),
+    };
+
+    let mut csv = format!("series,{}\n", columns.join(","));
+    for trace in traces {
+        let series = csv_field(trace.get("name").or(trace.get("type")));
+        let array = |column: &str| {
+            trace[column]
+                .as_array()
+                .map(Vec::as_slice)
+                .unwrap_or_default()
+        };
+        if trace["z"].is_array() {
+            for (i, row) in array("z").iter().enumerate() {
+                for (j, z) in row.as_array().into_iter().flatten().enumerate() {
+                    let cells = [array("x").get(j), array("y").get(i), Some(z)];
+                    let cells: Vec<String> = cells.into_iter().map(csv_field).collect();
+                    csv.push_str(&format!("{},{}\n", series, cells.join(",")));
+                }
+            }
+            continue;
+        }
+        let rows = columns
+            .iter()
+            .map(|column| array(column).len())
+            .max()
+            .unwrap_or(0);
+        for i in 0..rows {
+            let cells: Vec<String> = columns
+                .iter()
+                .map(|column| csv_field(array(column).get(i)))
+                .collect();
+            csv.push_str(&format!("{},{}\n", series, cells.join(",")));
+        }
+    }
+    csv
+}
+
+/// Write the data behind every chart as a CSV file into `dir`
+pub fn export_data(plots: &[(&str, Plot)], dir: &Path) -> Result<(), Error> {
+    std::fs::create_dir_all(dir).map_err(|err| {
+        Error::Output(format!(
+            "Could not create directory {}: {}",
+            safe_path(dir),
+            err
+        ))
+    })?;
+    for (name, plot) in plots {
+        let figure: serde_json::Value = serde_json::from_str(&plot.to_json()).unwrap();
+        let filename = dir.join(format!("{}.csv", name));
+        write_atomic(&filename, figure_csv(&figure)).map_err(|source| Error::Write {
+            path: filename.clone(),
+            source,
+        })?;
+    }
+    Ok(())
+}
+
+/// Write the data behind the report as JSON, with plots as plotly figures instead of rendered HTML
+pub fn export_json(
+    path: &Path,
+    ctx: &Value,
+    plots: &[(&str, Plot)],
+    style: &PlotStyle,
+    timings: &Timings,
+) -> Result<(), Error> {
+    let figures: serde_json::Map<String, serde_json::Value> = plots
+        .iter()
+        .map(|(name, plot)| (name.to_string(), plot_figure(plot, style)))
+        .collect();
+    let json = serde_json::json!({
+        "report": ctx,
+        "plots": figures,
+        "timings": timings,
+    });
+
+    let content = serde_json::to_string_pretty(&json).unwrap();
+    write_atomic(path, content).map_err(|source| Error::Write {
+        path: path.to_path_buf(),
+        source,
+    })
+}
@@ -0,0 +1,41 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::error::Error;
+use crate::git::get_file_sizes;
+
+const NO_EXTENSION: &str = "(none)";
+
+pub struct Extension {
+    pub extension: String,
+    pub files: usize,
+    pub bytes: u64,
+}
+
+/// Lowercased extension of a path including the dot, e.g. `.rs`
+pub fn file_extension(path: &str) -> Option<String> {
+    Path::new(path)
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy().to_lowercase()))
+}
+
+/// Files and bytes per extension of every file at HEAD, including binary files that tokei doesn't count,
+/// largest first
+pub fn get_extensions(path: &Path, scopes: &[String], n: usize) -> Result<Vec<Extension>, Error> {
+    let mut extensions: HashMap<String, Extension> = HashMap::new();
+    for (file, bytes) in get_file_sizes(path, scopes)? {
+        let extension = file_extension(&file).unwrap_or_else(|| NO_EXTENSION.to_string());
+        let entry = extensions.entry(extension.clone()).or_insert(Extension {
+            extension,
+            files: 0,
+            bytes: 0,
+        });
+        entry.files += 1;
+        entry.bytes += bytes;
+    }
+
+    let mut extensions: Vec<Extension> = extensions.into_values().collect();
+    extensions.sort_by(|a, b| b.bytes.cmp(&a.bytes).then(a.extension.cmp(&b.extension)));
+    extensions.truncate(n);
+    Ok(extensions)
+}
@@ -0,0 +1,92 @@
+use std::fs::File;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::cancel::remove_on_exit;
+use crate::git::run_git;
+use crate::remote::TempClone;
+use crate::text::safe_path;
+
+/// Branches HEAD points to after the import, in order of preference, the stream doesn't record HEAD
+const DEFAULT_BRANCHES: [&str; 2] = ["refs/heads/main", "refs/heads/master"];
+
+/// Import a history recorded with `git fast-export` into a repository in a temporary directory, and check out the
+/// `head` ref if the stream has it, or else `main`, `master` or the first branch in the stream. The stream has to
+/// include the file contents, which `--no-data` leaves out
+pub fn import_fast_export(file: &Path, head: Option<&str>) -> Result<TempClone, String> {
+    let stream =
+        File::open(file).map_err(|err| format!("Could not read {}: {}", safe_path(file), err))?;
+    let name = file
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let path = std::env::temp_dir().join(format!("git-report-{}-{}", std::process::id(), name));
+    // registered before importing, so an aborted import is removed as well
+    remove_on_exit(&path);
+    eprintln!("Importing {} into a temporary directory", safe_path(file));
+    let clone = TempClone {
+        url: file.to_string_lossy().to_string(),
+        path,
+    };
+
+    let status = Command::new("git")
+        .args(["init", "--quiet"])
+        .arg(&clone.path)
+        .status()
+        .map_err(|err| format!("Failed to execute git init: {}", err))?;
+    if !status.success() {
+        return Err(format!(
+            "Could not create a repository in {}",
+            safe_path(&clone.path)
+        ));
+    }
+    let output = Command::new("git")
+        .args(["fast-import", "--quiet"])
+        .current_dir(&clone.path)
+        .stdin(stream)
+        .stdout(Stdio::null())
+        .output()
+        .map_err(|err| format!("Failed to execute git fast-import: {}", err))?;
+    if !output.status.success() {
+        let errors = String::from_utf8_lossy(&output.stderr);
+        return Err(format!(
+            "Could not import {}: {}",
+            safe_path(file),
+            errors.lines().next().unwrap_or_default()
+        ));
+    }
+
+    let refs = run_git(&clone.path, &["for-each-ref", "--format=%(refname)"])
+        .map_err(|err| err.to_string())?;
+    let refs: Vec<&str> = refs.lines().collect();
+    let branches: Vec<&str> = refs
+        .iter()
+        .copied()
+        .filter(|name| name.starts_with("refs/heads/"))
+        .collect();
+    let head = head.filter(|head| refs.contains(head));
+    let Some(head) = head.or_else(|| {
+        DEFAULT_BRANCHES
+            .into_iter()
+            .find(|branch| branches.contains(branch))
+            .or(branches.first().copied())
+    }) else {
+        return Err(format!("{} has no branches", safe_path(file)));
+    };
+    if head.starts_with("refs/heads/") {
+        run_git(&clone.path, &["symbolic-ref", "HEAD", head]).map_err(|err| err.to_string())?;
+    } else {
+        // a ref outside of the branches, like the detached HEAD of a recording
+        run_git(&clone.path, &["update-ref", "--no-deref", "HEAD", head])
+            .map_err(|err| err.to_string())?;
+    }
+    let status = Command::new("git")
+        .args(["reset", "--quiet", "--hard"])
+        .current_dir(&clone.path)
+        .status()
+        .map_err(|err| format!("Failed to execute git reset: {}", err))?;
+    if !status.success() {
+        return Err(format!("Could not check out {}", head));
+    }
+    Ok(clone)
+}
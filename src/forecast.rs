@@ -0,0 +1,255 @@
+use chrono::{Datelike, NaiveDate};
+use plotly::{
+    common::{DashType, Fill, HoverInfo, Line, Mode, Title},
+    layout::Axis,
+    Layout, Plot, Scatter,
+};
+use std::collections::{HashMap, HashSet};
+
+use crate::git::Commit;
+use crate::plots::{Granularity, PLOT_WIDTH};
+
+/// Areas forecast, the ones with the most commits
+const TOP_AREAS: usize = 5;
+/// Months of history the model is fitted to
+const FIT_MONTHS: usize = 36;
+/// Fewest months of history to forecast an area, and to estimate the seasonality
+const MIN_MONTHS: usize = 12;
+const SEASONAL_MONTHS: usize = 24;
+/// Months forecast, two quarters
+const FORECAST_MONTHS: usize = 6;
+/// Months of history shown before the forecast
+const SHOWN_MONTHS: usize = 24;
+/// 95% of the activity is expected within the band
+const Z_95: f64 = 1.96;
+/// Name of the area of files at the root of the repository
+const ROOT_AREA: &str = "(root)";
+
+pub struct Projection {
+    pub month: NaiveDate,
+    pub commits: f64,
+    pub lower: f64,
+    pub upper: f64,
+}
+
+pub struct AreaForecast {
+    /// Top-level directory
+    pub area: String,
+    /// Commits per complete month, oldest first
+    pub history: Vec<(NaiveDate, f64)>,
+    pub projections: Vec<Projection>,
+}
+
+impl AreaForecast {
+    /// Commits in the last months as long as the forecast, to compare the forecast to
+    pub fn recent_commits(&self) -> f64 {
+        self.history
+            .iter()
+            .rev()
+            .take(FORECAST_MONTHS)
+            .map(|(_, commits)| commits)
+            .sum()
+    }
+
+    pub fn projected_commits(&self) -> (f64, f64, f64) {
+        self.projections
+            .iter()
+            .fold((0.0, 0.0, 0.0), |(commits, lower, upper), projection| {
+                (
+                    commits + projection.commits,
+                    lower + projection.lower,
+                    upper + projection.upper,
+                )
+            })
+    }
+}
+
+/// Top-level directory of a path, files at the root of the repository form an area of their own
+pub fn area(path: &str) -> &str {
+    match path.split_once('/') {
+        Some((directory, _)) => directory,
+        None => ROOT_AREA,
+    }
+}
+
+/// Linear trend plus the mean deviation from it per calendar month, with prediction intervals from the residuals
+fn fit(history: &[(NaiveDate, f64)]) -> Vec<Projection> {
+    let n = history.len() as f64;
+    let mean_t = (n - 1.0) / 2.0;
+    let mean_y = history.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let sxx: f64 = (0..history.len())
+        .map(|t| (t as f64 - mean_t).powi(2))
+        .sum();
+    let sxy: f64 = history
+        .iter()
+        .enumerate()
+        .map(|(t, (_, y))| (t as f64 - mean_t) * (y - mean_y))
+        .sum();
+    let slope = sxy / sxx;
+    let trend = |t: f64| mean_y + slope * (t - mean_t);
+
+    let mut seasonal = [0.0; 12];
+    let seasonal_parameters = match history.len() >= SEASONAL_MONTHS {
+        true => {
+            let mut deviations: [Vec<f64>; 12] = Default::default();
+            for (t, (month, y)) in history.iter().enumerate() {
+                deviations[month.month0() as usize].push(y - trend(t as f64));
+            }
+            for (index, deviations) in seasonal.iter_mut().zip(&deviations) {
+                *index = deviations.iter().sum::<f64>() / deviations.len().max(1) as f64;
+            }
+            let mean = seasonal.iter().sum::<f64>() / 12.0;
+            for index in seasonal.iter_mut() {
+                *index -= mean;
+            }
+            11.0
+        }
+        false => 0.0,
+    };
+    let squares: f64 = history
+        .iter()
+        .enumerate()
+        .map(|(t, (month, y))| (y - trend(t as f64) - seasonal[month.month0() as usize]).powi(2))
+        .sum();
+    let sigma = (squares / (n - 2.0 - seasonal_parameters).max(1.0)).sqrt();
+
+    let mut month = history[history.len() - 1].0;
+    (1..=FORECAST_MONTHS)
+        .map(|h| {
+            month = Granularity::Month.next(month);
+            let t = n - 1.0 + h as f64;
+            let commits = trend(t) + seasonal[month.month0() as usize];
+            let margin = Z_95 * sigma * (1.0 + 1.0 / n + (t - mean_t).powi(2) / sxx).sqrt();
+            Projection {
+                month,
+                commits: commits.max(0.0),
+                lower: (commits - margin).max(0.0),
+                upper: (commits + margin).max(0.0),
+            }
+        })
+        .collect()
+}
+
+/// Forecast the commits per month of the top-level directories with the most commits for the next two quarters.
+/// The month of the latest commit is left out of the fit as it isn't complete. `None` without enough history
+pub fn get_forecasts(commits: &[Commit]) -> Option<Vec<AreaForecast>> {
+    let commits: Vec<&Commit> = commits.iter().filter(|commit| !commit.is_merge()).collect();
+    let first = commits.iter().map(|commit| commit.date).min()?;
+    let latest = commits.iter().map(|commit| commit.date).max()?;
+    let mut months = Granularity::Month.periods(first, latest);
+    // the month of the latest commit
+    months.pop();
+    if months.len() < MIN_MONTHS {
+        return None;
+    }
+    let months = &months[months.len().saturating_sub(FIT_MONTHS)..];
+
+    let mut counts: HashMap<&str, HashMap<NaiveDate, f64>> = HashMap::new();
+    for commit in &commits {
+        let month = Granularity::Month.bucket(commit.date);
+        if month < months[0] || month > months[months.len() - 1] {
+            continue;
+        }
+        let areas: HashSet<&str> = commit.files.iter().map(|file| area(&file.path)).collect();
+        for area in areas {
+            *counts.entry(area).or_default().entry(month).or_insert(0.0) += 1.0;
+        }
+    }
+    let mut areas: Vec<(&str, f64)> = counts
+        .iter()
+        .map(|(area, months)| (*area, months.values().sum()))
+        .collect();
+    areas.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(b.0)));
+
+    let forecasts: Vec<AreaForecast> = areas
+        .into_iter()
+        .take(TOP_AREAS)
+        .map(|(area, _)| {
+            let history: Vec<(NaiveDate, f64)> = months
+                .iter()
+                .map(|month| (*month, *counts[area].get(month).unwrap_or(&0.0)))
+                .collect();
+            AreaForecast {
+                area: area.to_string(),
+                projections: fit(&history),
+                history,
+            }
+        })
+        .collect();
+    Some(forecasts).filter(|forecasts| !forecasts.is_empty())
+}
+
+/// `#rrggbb` as a CSS color with `alpha`, gray if it isn't a hex color
+fn transparent(color: &str, alpha: f64) -> String {
+    let hex = color.trim_start_matches('#');
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    match (hex.len(), channel(0), channel(2), channel(4)) {
+        (6, Some(r), Some(g), Some(b)) => format!("rgba({}, {}, {}, {})", r, g, b, alpha),
+        _ => format!("rgba(128, 128, 128, {})", alpha),
+    }
+}
+
+/// Recent commits per month of every area, followed by the forecast with its 95% band
+pub fn plot_forecasts(forecasts: &[AreaForecast], colors: &[String]) -> Plot {
+    let mut plot = Plot::new();
+
+    for (forecast, color) in forecasts.iter().zip(colors.iter().cycle()) {
+        let history = &forecast.history[forecast.history.len().saturating_sub(SHOWN_MONTHS)..];
+        let (last_month, last_commits) = history[history.len() - 1];
+        // the forecast and its band start at the last month of history, so they connect to it
+        let mut x = vec![last_month.to_string()];
+        x.extend(forecast.projections.iter().map(|p| p.month.to_string()));
+        let band = |bound: fn(&Projection) -> f64| {
+            let mut y = vec![last_commits];
+            y.extend(forecast.projections.iter().map(bound));
+            y
+        };
+
+        plot.add_trace(
+            Scatter::new(x.clone(), band(|p| p.lower))
+                .mode(Mode::Lines)
+                .line(Line::new().width(0.0))
+                .legend_group(&forecast.area)
+                .show_legend(false)
+                .hover_info(HoverInfo::Skip),
+        );
+        plot.add_trace(
+            Scatter::new(x.clone(), band(|p| p.upper))
+                .mode(Mode::Lines)
+                .line(Line::new().width(0.0))
+                .fill(Fill::ToNextY)
+                .fill_color(transparent(color, 0.2))
+                .legend_group(&forecast.area)
+                .show_legend(false)
+                .hover_info(HoverInfo::Skip),
+        );
+        plot.add_trace(
+            Scatter::new(
+                history.iter().map(|(month, _)| month.to_string()).collect(),
+                history.iter().map(|(_, commits)| *commits).collect(),
+            )
+            .mode(Mode::Lines)
+            .line(Line::new().color(color.clone()))
+            .legend_group(&forecast.area)
+            .name(&forecast.area),
+        );
+        plot.add_trace(
+            Scatter::new(x, band(|p| p.commits))
+                .mode(Mode::Lines)
+                .line(Line::new().color(color.clone()).dash(DashType::Dash))
+                .legend_group(&forecast.area)
+                .show_legend(false)
+                .name(format!("{} forecast", forecast.area)),
+        );
+    }
+
+    let layout = Layout::new()
+        .width(PLOT_WIDTH - 50)
+        .title(Title::from(
+            "Commits per month and forecast per top-level directory",
+        ))
+        .y_axis(Axis::new().title(Title::from("Commits")));
+    plot.set_layout(layout);
+
+    plot
+}
@@ -0,0 +1,632 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::error::Error;
+use crate::text::safe_path;
+
+#[derive(Eq, Hash, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Commit {
+    pub hash: String,
+    pub parents: Vec<String>,
+    /// Day of the commit, in the time zone of `--timezone`
+    pub date: NaiveDate,
+    /// Unix timestamp of the author date, or of the committer date with `--date committer`
+    #[serde(default)]
+    pub time: i64,
+    /// Offset of the time zone of `time` from UTC in minutes
+    #[serde(default)]
+    pub offset: i32,
+    /// Unix timestamp and time zone offset of the committer date
+    pub committer_time: i64,
+    pub committer_offset: i32,
+    pub author: String,
+    pub email: String,
+    pub co_authors: Vec<String>,
+    pub message: String,
+    pub files: Vec<FileChange>,
+    /// Path of the submodule the commit was made in, `None` for commits to the repository itself
+    #[serde(default)]
+    pub submodule: Option<String>,
+}
+
+#[derive(Eq, Hash, PartialEq, Debug, Serialize, Deserialize)]
+pub struct FileChange {
+    pub path: String,
+    /// Path before the file was renamed in this commit
+    #[serde(default)]
+    pub renamed_from: Option<String>,
+    /// Lines added and deleted, `None` for binary files
+    pub added: Option<u32>,
+    pub deleted: Option<u32>,
+}
+
+impl Commit {
+    pub fn is_merge(&self) -> bool {
+        self.parents.len() > 1
+    }
+
+    /// Everyone who should be credited for this commit, with co-authors credited by `co_author_weight`
+    pub fn credits(&self, co_author_weight: f64) -> Vec<(&str, f64)> {
+        let mut credits = vec![(self.author.as_str(), 1.0)];
+        for co_author in &self.co_authors {
+            if !credits.iter().any(|(name, _)| name == co_author) {
+                credits.push((co_author.as_str(), co_author_weight));
+            }
+        }
+        credits
+    }
+
+    /// Domain part of the email address, e.g. `example.com`
+    pub fn email_domain(&self) -> &str {
+        self.email
+            .rsplit_once('@')
+            .map(|(_, domain)| domain)
+            .unwrap_or("")
+    }
+
+    /// Author date and time in the author's time zone
+    pub fn local_time(&self) -> NaiveDateTime {
+        DateTime::from_timestamp(self.time + self.offset as i64 * 60, 0)
+            .unwrap_or_default()
+            .naive_utc()
+    }
+
+    /// Total number of lines added and deleted
+    pub fn lines_changed(&self) -> u32 {
+        self.files
+            .iter()
+            .map(|file| file.added.unwrap_or(0) + file.deleted.unwrap_or(0))
+            .sum()
+    }
+}
+
+/// Output of a git command, fails when git can't be run. Author names and paths are not guaranteed to be valid UTF-8
+pub fn run_git(path: &Path, args: &[&str]) -> Result<String, Error> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(path)
+        .output()
+        .map_err(Error::GitNotFound)?;
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Path and size in bytes of every file at HEAD under `scopes`, or in the whole repository when empty. Submodules
+/// are left out
+pub fn get_file_sizes(path: &Path, scopes: &[String]) -> Result<Vec<(String, u64)>, Error> {
+    let mut args = vec![
+        "-c",
+        "core.quotePath=false",
+        "ls-tree",
+        "-r",
+        "-l",
+        "HEAD",
+        "--",
+    ];
+    args.extend(scopes.iter().map(String::as_str));
+    let tree = run_git(path, &args)?;
+    // lines look like `<mode> <type> <object> <size>\t<path>`, submodules have no size
+    Ok(tree
+        .lines()
+        .filter_map(|line| {
+            let (info, file) = line.split_once('\t')?;
+            let bytes = info.split_whitespace().nth(3)?.parse().ok()?;
+            Some((file.to_string(), bytes))
+        })
+        .collect())
+}
+
+/// Read the contents of many objects like `v1.0:src/lib.rs` with a single git process, `None` for objects that don't
+/// exist or aren't valid UTF-8
+pub fn read_blobs(path: &Path, objects: &[String]) -> Result<Vec<Option<String>>, String> {
+    let mut child = Command::new("git")
+        .args(["cat-file", "--batch"])
+        .current_dir(path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("Failed to execute git cat-file: {}", err))?;
+
+    // write from another thread, git stops reading its input while its output isn't consumed
+    let mut stdin = child.stdin.take().unwrap();
+    let input: String = objects
+        .iter()
+        .map(|object| format!("{}\n", object))
+        .collect();
+    let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+    let output = child
+        .wait_with_output()
+        .map_err(|err| format!("Failed to read from git cat-file: {}", err))?;
+    writer
+        .join()
+        .expect("git cat-file writer panicked")
+        .map_err(|err| format!("Failed to write to git cat-file: {}", err))?;
+
+    // every object is answered with `<object> <type> <size>\n<content>\n`, or `<object> missing\n`
+    let mut blobs = vec![];
+    let mut rest = output.stdout.as_slice();
+    for _ in objects {
+        let Some(newline) = rest.iter().position(|&b| b == b'\n') else {
+            return Err("Unexpected end of git cat-file output".to_string());
+        };
+        let header = String::from_utf8_lossy(&rest[..newline]);
+        rest = &rest[newline + 1..];
+        let size = header
+            .rsplit_once(' ')
+            .and_then(|(_, size)| size.parse::<usize>().ok());
+        match size {
+            Some(size) if size < rest.len() => {
+                blobs.push(String::from_utf8(rest[..size].to_vec()).ok());
+                rest = &rest[size + 1..];
+            }
+            Some(_) => return Err("Unexpected end of git cat-file output".to_string()),
+            None => blobs.push(None),
+        }
+    }
+    Ok(blobs)
+}
+
+/// Fields of the custom log format, separated by the ASCII unit separator so that no value can clash.
+/// The multi-line commit message comes last and is terminated by the file separator.
+const LOG_FORMAT: &str = "--format=%x1e%H%x1f%P%x1f%as%x1f%at%x1f%ai%x1f%ct%x1f%ci%x1f%an%x1f%ae%x1f%(trailers:key=Co-authored-by,valueonly,separator=%x1d)%x1f%B%x1c";
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Repository {
+    /// A repository with a working tree, including linked worktrees where `.git` is a file
+    WorkTree,
+    /// A repository without a working tree, like `repo.git` on a server
+    Bare,
+}
+
+/// Detect the repository at `path`, which has to be the root of a working tree or a bare repository
+pub fn detect_repository(path: &Path) -> Result<Repository, Error> {
+    let output = Command::new("git")
+        .args([
+            "rev-parse",
+            "--is-bare-repository",
+            "--git-dir",
+            "--show-prefix",
+        ])
+        .current_dir(path)
+        .output()
+        .map_err(Error::GitNotFound)?;
+    if !output.status.success() {
+        return Err(Error::Git(format!(
+            "Path is not a git repository: {}",
+            safe_path(path)
+        )));
+    }
+    let output = String::from_utf8_lossy(&output.stdout);
+    let mut lines = output.lines();
+    let (is_bare, git_dir, prefix) = (lines.next(), lines.next(), lines.next());
+    match (is_bare, git_dir, prefix.unwrap_or_default()) {
+        (Some("true"), Some("."), _) => Ok(Repository::Bare),
+        (Some("false"), _, "") => Ok(Repository::WorkTree),
+        _ => Err(Error::Input(format!(
+            "Path is inside a git repository, pass the root of the repository instead: {}",
+            safe_path(path)
+        ))),
+    }
+}
+
+pub struct Tag {
+    pub name: String,
+    /// Date of the tag object for annotated tags, of the commit for lightweight tags
+    pub date: NaiveDate,
+    /// The tagged commit
+    pub commit: String,
+}
+
+/// Every tag pointing to a commit, oldest first
+pub fn get_tags(path: &Path) -> Result<Vec<Tag>, Error> {
+    // annotated tags point to a tag object, `*objectname` is the commit behind it
+    let tags = run_git(
+        path,
+        &[
+            "for-each-ref",
+            "--sort=creatordate",
+            "--format=%(refname:lstrip=2)%09%(creatordate:short)%09%(objectname)%09%(*objectname)",
+            "refs/tags",
+        ],
+    )?;
+    Ok(tags
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split('\t').collect();
+            let [name, date, object, commit] = fields[..] else {
+                return None;
+            };
+            Some(Tag {
+                name: name.to_string(),
+                date: NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?,
+                commit: if commit.is_empty() { object } else { commit }.to_string(),
+            })
+        })
+        .collect())
+}
+
+/// Whether HEAD points to a commit, which is not the case in a freshly initialized repository
+pub fn has_commits(path: &Path) -> bool {
+    Command::new("git")
+        .args(["rev-parse", "--verify", "--quiet", "HEAD"])
+        .current_dir(path)
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+/// The full hash of the commit a revision like a branch or tag points to
+pub fn resolve_revision(path: &Path, revision: &str) -> Result<String, Error> {
+    let hash = run_git(
+        path,
+        &[
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            &format!("{}^{{commit}}", revision),
+        ],
+    )?;
+    match hash.trim() {
+        "" => Err(Error::Input(format!("Unknown revision: {}", revision))),
+        hash => Ok(hash.to_string()),
+    }
+}
+
+/// Lines added and deleted per file between the trees of two commits, renames are detected
+pub fn get_tree_changes(path: &Path, from: &str, to: &str) -> Result<Vec<FileChange>, Error> {
+    let diff = run_git(
+        path,
+        &[
+            "-c",
+            "core.quotePath=false",
+            "diff",
+            "--numstat",
+            "-M",
+            from,
+            to,
+        ],
+    )?;
+    Ok(diff.lines().filter_map(parse_numstat).collect())
+}
+
+/// Read the history of HEAD, `with_files` includes the lines changed per file.
+/// With `follow_renames` the changes to a file are reported under its latest name, also from before it was renamed.
+/// Non-empty `scopes` restrict the history to the commits changing files under them, and to those files.
+/// All commits are kept in memory for the sections, use `for_each_commit` to fold the history without them
+pub fn get_commit_log(
+    path: &Path,
+    with_files: bool,
+    follow_renames: bool,
+    scopes: &[String],
+) -> Result<Vec<Commit>, Error> {
+    if !has_commits(path) {
+        return Ok(vec![]);
+    }
+    let mut commits = vec![];
+    for_each_commit(path, "HEAD", with_files, follow_renames, scopes, |commit| {
+        commits.push(commit)
+    })?;
+    Ok(commits)
+}
+
+/// Read the commits of a revision range like `main..feature`, without the changed files
+pub fn get_range_log(path: &Path, range: &str) -> Result<Vec<Commit>, Error> {
+    let mut commits = vec![];
+    for_each_commit(path, range, false, false, &[], |commit| {
+        commits.push(commit)
+    })?;
+    Ok(commits)
+}
+
+/// Stream the history of a revision from new to old, parsing one commit at a time instead of buffering the whole
+/// `git log` output, which runs into gigabytes on large repositories
+pub fn for_each_commit(
+    path: &Path,
+    revision: &str,
+    with_files: bool,
+    follow_renames: bool,
+    scopes: &[String],
+    mut f: impl FnMut(Commit),
+) -> Result<(), Error> {
+    let mut command = Command::new("git");
+    command
+        .args(["-c", "core.quotePath=false", "log", LOG_FORMAT, revision])
+        .current_dir(path)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    if with_files {
+        command.arg("--numstat");
+        command.arg(if follow_renames { "-M" } else { "--no-renames" });
+    }
+    if !scopes.is_empty() {
+        command.arg("--").args(scopes);
+    }
+    let mut child = command.spawn().map_err(Error::GitNotFound)?;
+    // drain stderr on another thread, git blocks when either pipe is full
+    let mut stderr = child.stderr.take().unwrap();
+    let errors = std::thread::spawn(move || {
+        let mut errors = String::new();
+        let _ = stderr.read_to_string(&mut errors);
+        errors
+    });
+
+    // every record starts with the commit fields, followed by one numstat line per changed file
+    let mut renames = RenamedFiles::default();
+    let mut stdout = BufReader::new(child.stdout.take().unwrap());
+    let mut record = vec![];
+    let mut result = Ok(());
+    loop {
+        record.clear();
+        match stdout.read_until(b'\x1e', &mut record) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(err) => {
+                result = Err(format!("Could not read the git log: {}", err));
+                break;
+            }
+        }
+        let record = record.strip_suffix(b"\x1e").unwrap_or(&record);
+        if record.is_empty() {
+            continue;
+        }
+        // author names and paths are not guaranteed to be valid UTF-8
+        match parse_record(&String::from_utf8_lossy(record)) {
+            Ok(mut commit) => {
+                if follow_renames {
+                    renames.follow(&mut commit);
+                }
+                f(commit);
+            }
+            Err(err) => {
+                result = Err(err);
+                break;
+            }
+        }
+    }
+    if result.is_err() {
+        let _ = child.kill();
+    }
+
+    let status = child
+        .wait()
+        .map_err(|err| Error::Git(format!("Could not wait for git log: {}", err)))?;
+    let errors = errors.join().expect("git log stderr reader panicked");
+    result.map_err(Error::Git)?;
+    if !status.success() {
+        return Err(Error::Git(format!("git log failed: {}", errors.trim())));
+    }
+    Ok(())
+}
+
+/// Reports changes under the latest name of a file. The log is ordered from new to old, so renames are seen
+/// before the changes made under the previous name
+#[derive(Default)]
+struct RenamedFiles {
+    latest_names: HashMap<String, String>,
+}
+
+impl RenamedFiles {
+    fn follow(&mut self, commit: &mut Commit) {
+        for file in &mut commit.files {
+            if let Some(latest) = self.latest_names.get(&file.path) {
+                file.path = latest.clone();
+            }
+            if let Some(previous) = &file.renamed_from {
+                self.latest_names
+                    .insert(previous.clone(), file.path.clone());
+            }
+        }
+    }
+}
+
+fn parse_record(record: &str) -> Result<Commit, String> {
+    let (header, numstat) = record.split_once('\x1c').unwrap_or((record, ""));
+    let parts: Vec<&str> = header.splitn(11, '\x1f').collect();
+    let [hash, parents, date, time, iso_date, committer_time, committer_iso_date, author, email, co_authors, message] =
+        parts[..]
+    else {
+        return Err(format!("Unexpected git log output: {:?}", header));
+    };
+
+    Ok(Commit {
+        hash: hash.to_string(),
+        parents: parents.split_whitespace().map(String::from).collect(),
+        date: NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|err| {
+            format!(
+                "Could not parse date {:?} of commit {}: {}",
+                date, hash, err
+            )
+        })?,
+        time: time.parse().map_err(|err| {
+            format!(
+                "Could not parse time {:?} of commit {}: {}",
+                time, hash, err
+            )
+        })?,
+        offset: parse_offset(iso_date).ok_or_else(|| {
+            format!(
+                "Could not parse time zone of {:?} of commit {}",
+                iso_date, hash
+            )
+        })?,
+        committer_time: committer_time.parse().map_err(|err| {
+            format!(
+                "Could not parse committer time {:?} of commit {}: {}",
+                committer_time, hash, err
+            )
+        })?,
+        committer_offset: parse_offset(committer_iso_date).ok_or_else(|| {
+            format!(
+                "Could not parse time zone of {:?} of commit {}",
+                committer_iso_date, hash
+            )
+        })?,
+        author: author.to_string(),
+        email: email.to_lowercase(),
+        co_authors: co_authors
+            .split('\x1d')
+            .filter(|trailer| !trailer.is_empty())
+            .map(trailer_name)
+            .collect(),
+        message: message.trim_end().to_string(),
+        files: numstat.lines().filter_map(parse_numstat).collect(),
+        submodule: None,
+    })
+}
+
+/// Minutes east of UTC of a date like `2024-01-31 09:30:00 +0100`
+fn parse_offset(iso_date: &str) -> Option<i32> {
+    let offset = iso_date.rsplit(' ').next()?;
+    let sign = match offset.get(..1)? {
+        "+" => 1,
+        "-" => -1,
+        _ => return None,
+    };
+    let hours: i32 = offset.get(1..3)?.parse().ok()?;
+    let minutes: i32 = offset.get(3..5)?.parse().ok()?;
+    Some(sign * (hours * 60 + minutes))
+}
+
+/// Strip the email from a `Name <email>` trailer value
+fn trailer_name(trailer: &str) -> String {
+    match trailer.split_once('<') {
+        Some((name, _)) => name.trim().to_string(),
+        None => trailer.trim().to_string(),
+    }
+}
+
+/// Parse a `--numstat` line of the form `added<TAB>deleted<TAB>path`
+fn parse_numstat(line: &str) -> Option<FileChange> {
+    let mut parts = line.splitn(3, '\t');
+    let added = parts.next()?;
+    let deleted = parts.next()?;
+    let (renamed_from, path) = parse_rename(parts.next()?);
+    Some(FileChange {
+        path,
+        renamed_from,
+        added: added.parse().ok(),
+        deleted: deleted.parse().ok(),
+    })
+}
+
+/// Split a renamed path like `src/{old => new}/lib.rs` or `old.rs => new.rs` into the previous and new path
+fn parse_rename(path: &str) -> (Option<String>, String) {
+    let Some((before, after)) = path.split_once(" => ") else {
+        return (None, path.to_string());
+    };
+    let (previous, new) = match (before.rsplit_once('{'), after.split_once('}')) {
+        (Some((prefix, old)), Some((new, suffix))) => (
+            format!("{}{}{}", prefix, old, suffix),
+            format!("{}{}{}", prefix, new, suffix),
+        ),
+        _ => (before.to_string(), after.to_string()),
+    };
+    // an empty side of the braces, like `src/{ => lib}/main.rs`, leaves a double slash
+    (Some(previous.replace("//", "/")), new.replace("//", "/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A record like `LOG_FORMAT` writes it, between the record separators
+    fn record(author: &str, iso_date: &str, numstat: &str) -> String {
+        [
+            "abc123",
+            "def456",
+            "2024-01-31",
+            "1706690000",
+            iso_date,
+            "1706690000",
+            "2024-01-31 09:30:00 +0000",
+            author,
+            "Alice@Example.com",
+            "Bob Jones <bob@example.com>\x1dCarol <carol@example.com>",
+            &format!("Fix parser\n\nMore details\n\x1c\n{}", numstat),
+        ]
+        .join("\x1f")
+    }
+
+    #[test]
+    fn parses_names_with_commas() {
+        let commit =
+            parse_record(&record("Smith, Alice", "2024-01-31 09:30:00 +0100", "")).unwrap();
+        assert_eq!(commit.author, "Smith, Alice");
+        assert_eq!(commit.email, "alice@example.com");
+        assert_eq!(commit.co_authors, ["Bob Jones", "Carol"]);
+        assert_eq!(commit.parents, ["def456"]);
+        assert_eq!(commit.message, "Fix parser\n\nMore details");
+        assert!(commit.files.is_empty());
+    }
+
+    #[test]
+    fn parses_numstat_with_renames_and_binary_files() {
+        let numstat = "3\t1\tsrc/{old => new}/lib.rs\n0\t0\told.rs => new.rs\n-\t-\tlogo.png\n";
+        let commit = parse_record(&record("Alice", "2024-01-31 09:30:00 +0100", numstat)).unwrap();
+        assert_eq!(
+            commit.files,
+            [
+                FileChange {
+                    path: "src/new/lib.rs".to_string(),
+                    renamed_from: Some("src/old/lib.rs".to_string()),
+                    added: Some(3),
+                    deleted: Some(1),
+                },
+                FileChange {
+                    path: "new.rs".to_string(),
+                    renamed_from: Some("old.rs".to_string()),
+                    added: Some(0),
+                    deleted: Some(0),
+                },
+                FileChange {
+                    path: "logo.png".to_string(),
+                    renamed_from: None,
+                    added: None,
+                    deleted: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_renames_out_of_and_into_directories() {
+        assert_eq!(
+            parse_rename("src/{ => lib}/main.rs"),
+            (
+                Some("src/main.rs".to_string()),
+                "src/lib/main.rs".to_string()
+            )
+        );
+        assert_eq!(
+            parse_rename("a/{lib => }/x.rs"),
+            (Some("a/lib/x.rs".to_string()), "a/x.rs".to_string())
+        );
+        assert_eq!(parse_rename("plain.rs"), (None, "plain.rs".to_string()));
+    }
+
+    #[test]
+    fn parses_time_zone_offsets() {
+        assert_eq!(parse_offset("2024-01-31 09:30:00 +0530"), Some(330));
+        assert_eq!(parse_offset("2024-01-31 09:30:00 -0000"), Some(0));
+        assert_eq!(parse_offset("2024-01-31 09:30:00 -0800"), Some(-480));
+        assert_eq!(parse_offset("2024-01-31 09:30:00"), None);
+    }
+
+    #[test]
+    fn rejects_incomplete_records() {
+        assert!(parse_record("abc123\x1fdef456").is_err());
+    }
+
+    #[test]
+    fn reads_an_empty_history() {
+        let path = std::env::temp_dir().join(format!("git-report-{}-empty", std::process::id()));
+        std::fs::create_dir_all(&path).unwrap();
+        run_git(&path, &["init", "--quiet"]).unwrap();
+        let commits = get_commit_log(&path, true, true, &[]);
+        std::fs::remove_dir_all(&path).unwrap();
+        assert!(commits.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,50 @@
+use plotly::{common::Title, layout::Margin, Layout, Plot};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use crate::git::Commit;
+use crate::plots::{Granularity, Treemap, PLOT_WIDTH};
+
+/// One treemap of the lines changed per directory for every period, to animate how activity moves through
+/// the codebase
+pub fn plot_heat_frames(commits: &[Commit], granularity: Granularity) -> Vec<Plot> {
+    let mut periods: BTreeMap<_, HashMap<&str, usize>> = BTreeMap::new();
+    // merge commits are skipped, as their changes are already counted in the commits they merge
+    for commit in commits.iter().filter(|commit| !commit.is_merge()) {
+        let files = periods.entry(granularity.bucket(commit.date)).or_default();
+        for file in &commit.files {
+            *files.entry(&file.path).or_insert(0) +=
+                (file.added.unwrap_or(0) + file.deleted.unwrap_or(0)) as usize;
+        }
+    }
+
+    periods
+        .into_iter()
+        .map(|(period, files)| {
+            let mut plot = Plot::new();
+            let mut treemap = Treemap::new();
+            treemap.push(
+                "All".to_string(),
+                "All".to_string(),
+                String::new(),
+                files.values().sum(),
+            );
+            treemap.push_directories(
+                "All",
+                files.iter().map(|(path, lines)| (Path::new(*path), *lines)),
+            );
+            treemap.heat();
+            plot.add_trace(treemap);
+
+            let layout = Layout::new()
+                .width(PLOT_WIDTH)
+                .height(PLOT_WIDTH / 2)
+                .title(Title::from(
+                    format!("Lines changed from {}", period).as_str(),
+                ))
+                .margin(Margin::new().left(10).right(10));
+            plot.set_layout(layout);
+            plot
+        })
+        .collect()
+}
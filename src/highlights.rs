@@ -0,0 +1,49 @@
+use std::collections::HashMap;
+
+use crate::config::Highlight;
+
+/// Table columns that can be highlighted, the same column may appear in several tables
+const COLUMNS: [&str; 13] = [
+    "commits",
+    "contributors",
+    "lines",
+    "lines_added",
+    "lines_deleted",
+    "lines_changed",
+    "median_lines_changed",
+    "p90_lines_changed",
+    "median_age_days",
+    "added_items",
+    "removed_items",
+    "changes",
+    "unowned_files",
+];
+const DEFAULT_COLOR: &str = "red";
+
+/// Highlights of the config, checked against the known columns
+#[derive(Clone, Default)]
+pub struct Highlights(HashMap<String, Highlight>);
+
+impl Highlights {
+    pub fn new(highlights: &HashMap<String, Highlight>) -> Result<Self, String> {
+        if let Some(column) = highlights
+            .keys()
+            .find(|column| !COLUMNS.contains(&column.as_str()))
+        {
+            return Err(format!(
+                "Unknown column {:?} in the highlights, expected one of {}",
+                column,
+                COLUMNS.join(", ")
+            ));
+        }
+        Ok(Highlights(highlights.clone()))
+    }
+
+    /// Color of a cell, `None` if its value is within the limits of its column
+    pub fn color(&self, column: &str, value: f64) -> Option<String> {
+        let highlight = self.0.get(column)?;
+        let outside = highlight.above.is_some_and(|above| value > above)
+            || highlight.below.is_some_and(|below| value < below);
+        outside.then(|| highlight.color.clone().unwrap_or(DEFAULT_COLOR.to_string()))
+    }
+}
@@ -0,0 +1,170 @@
+use chrono::{Duration, NaiveDate, Utc};
+use plotly::{
+    common::{AxisSide, Mode, Title},
+    layout::Axis,
+    Layout, Plot, Scatter,
+};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+
+use crate::git::Commit;
+use crate::plots::PLOT_WIDTH;
+use crate::text::safe_path;
+
+/// Days up to the latest commit the churn of a snapshot covers
+const CHURN_DAYS: i64 = 30;
+
+/// Headline metrics of one report run, a line of JSON in the history file
+#[derive(Serialize, Deserialize)]
+pub struct Snapshot {
+    /// When the report was made
+    pub generated: String,
+    /// Latest commit and its date, snapshots are placed in time by this date
+    pub head: String,
+    pub date: NaiveDate,
+    pub commits: usize,
+    pub contributors: usize,
+    pub bus_factor: usize,
+    /// `None` when languages weren't counted
+    pub lines_of_code: Option<usize>,
+    /// Lines added and deleted in the 30 days up to the latest commit
+    pub churn: u32,
+}
+
+impl Snapshot {
+    pub fn new(
+        head: String,
+        commits: &[Commit],
+        contributors: usize,
+        bus_factor: usize,
+        lines_of_code: Option<usize>,
+    ) -> Self {
+        Snapshot {
+            generated: Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            head,
+            date: commits
+                .iter()
+                .map(|commit| commit.date)
+                .max()
+                .unwrap_or_default(),
+            commits: commits.len(),
+            contributors,
+            bus_factor,
+            lines_of_code,
+            churn: recent_churn(commits),
+        }
+    }
+}
+
+/// Lines changed in the 30 days up to the latest commit
+fn recent_churn(commits: &[Commit]) -> u32 {
+    let Some(latest) = commits.iter().map(|commit| commit.date).max() else {
+        return 0;
+    };
+    commits
+        .iter()
+        .filter(|commit| commit.date > latest - Duration::days(CHURN_DAYS) && !commit.is_merge())
+        .map(|commit| commit.lines_changed())
+        .sum()
+}
+
+/// Snapshots of earlier runs in the history file, none if it doesn't exist yet
+pub fn read_history(path: &Path) -> Result<Vec<Snapshot>, String> {
+    let file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => return Err(format!("Could not read {}: {}", safe_path(path), err)),
+    };
+    BufReader::new(file)
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| line.as_ref().map_or(true, |line| !line.trim().is_empty()))
+        .map(|(i, line)| {
+            let line =
+                line.map_err(|err| format!("Could not read {}: {}", safe_path(path), err))?;
+            serde_json::from_str(&line).map_err(|err| {
+                format!(
+                    "Could not parse line {} of {}: {}",
+                    i + 1,
+                    safe_path(path),
+                    err
+                )
+            })
+        })
+        .collect()
+}
+
+/// Append the snapshot to the history file as a line of JSON, creating the file if needed
+pub fn append_snapshot(path: &Path, snapshot: &Snapshot) -> std::io::Result<()> {
+    let line = serde_json::to_string(snapshot).unwrap();
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| writeln!(file, "{}", line))
+}
+
+/// The latest snapshot per commit, in the order of their commit dates
+pub fn latest_per_head(snapshots: &[Snapshot]) -> Vec<&Snapshot> {
+    let mut latest: Vec<&Snapshot> = vec![];
+    for snapshot in snapshots {
+        latest.retain(|earlier| earlier.head != snapshot.head);
+        latest.push(snapshot);
+    }
+    latest.sort_by_key(|snapshot| snapshot.date);
+    latest
+}
+
+/// Headline metrics of every snapshot, counts of people on the left axis and lines on the right one
+pub fn plot_history(snapshots: &[&Snapshot]) -> Plot {
+    let mut plot = Plot::new();
+
+    let x: Vec<String> = snapshots
+        .iter()
+        .map(|snapshot| snapshot.date.to_string())
+        .collect();
+    plot.add_trace(
+        Scatter::new(
+            x.clone(),
+            snapshots.iter().map(|s| s.contributors).collect(),
+        )
+        .mode(Mode::LinesMarkers)
+        .name("Contributors"),
+    );
+    plot.add_trace(
+        Scatter::new(x.clone(), snapshots.iter().map(|s| s.bus_factor).collect())
+            .mode(Mode::LinesMarkers)
+            .name("Bus factor"),
+    );
+    let (loc_x, loc): (Vec<String>, Vec<usize>) = snapshots
+        .iter()
+        .filter_map(|snapshot| Some((snapshot.date.to_string(), snapshot.lines_of_code?)))
+        .unzip();
+    plot.add_trace(
+        Scatter::new(loc_x, loc)
+            .mode(Mode::LinesMarkers)
+            .name("Lines of code")
+            .y_axis("y2"),
+    );
+    plot.add_trace(
+        Scatter::new(x, snapshots.iter().map(|s| s.churn).collect())
+            .mode(Mode::LinesMarkers)
+            .name("Lines changed in 30 days")
+            .y_axis("y2"),
+    );
+
+    let layout = Layout::new()
+        .width(PLOT_WIDTH - 50)
+        .title(Title::from("Project metrics over time"))
+        .y_axis(Axis::new().title(Title::from("People")))
+        .y_axis2(
+            Axis::new()
+                .title(Title::from("Lines"))
+                .overlaying("y")
+                .side(AxisSide::Right),
+        );
+    plot.set_layout(layout);
+
+    plot
+}
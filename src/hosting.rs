@@ -0,0 +1,444 @@
+use chrono::{DateTime, NaiveDate};
+use plotly::{common::Title, layout::BarMode, Bar, Layout, Plot};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::git::Commit;
+use crate::plots::PLOT_WIDTH;
+use crate::recording::{record_response, replayed_response};
+use crate::sizes::percentile;
+
+/// Maximum number of pages of 100 items fetched per endpoint
+const MAX_PAGES: usize = 10;
+
+#[derive(Debug)]
+pub enum Hosting {
+    GitHub { repo: String },
+    GitLab { project: String, url: String },
+}
+
+impl Hosting {
+    /// Page of a commit on the web interface
+    pub fn commit_url(&self, hash: &str) -> String {
+        match self {
+            Hosting::GitHub { repo } => format!("https://github.com/{}/commit/{}", repo, hash),
+            Hosting::GitLab { project, url } => {
+                format!(
+                    "{}/{}/-/commit/{}",
+                    url.trim_end_matches('/'),
+                    project,
+                    hash
+                )
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Hosting {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Hosting::GitHub { repo } => write!(f, "GitHub repository {}", repo),
+            Hosting::GitLab { project, url } => write!(f, "GitLab project {} on {}", project, url),
+        }
+    }
+}
+
+/// A pull request (GitHub) or merge request (GitLab)
+pub struct PullRequest {
+    pub created: NaiveDate,
+    pub merged: Option<NaiveDate>,
+}
+
+pub struct Issue {
+    pub created: NaiveDate,
+    pub closed: Option<NaiveDate>,
+}
+
+pub struct Profile {
+    pub login: Option<String>,
+    pub url: Option<String>,
+    pub avatar_url: Option<String>,
+}
+
+/// A milestone with a due date
+pub struct Milestone {
+    pub title: String,
+    pub due: NaiveDate,
+}
+
+pub struct HostingStats {
+    pub pull_requests: Vec<PullRequest>,
+    pub issues: Vec<Issue>,
+    pub milestones: Vec<Milestone>,
+    /// Profiles by author name as it appears in the git history
+    pub profiles: HashMap<String, Profile>,
+}
+
+impl HostingStats {
+    pub fn median_days_to_merge(&self) -> Option<u32> {
+        median_days(self.pull_requests.iter().map(|pr| (pr.created, pr.merged)))
+    }
+
+    pub fn median_days_to_close(&self) -> Option<u32> {
+        median_days(
+            self.issues
+                .iter()
+                .map(|issue| (issue.created, issue.closed)),
+        )
+    }
+}
+
+fn median_days(items: impl Iterator<Item = (NaiveDate, Option<NaiveDate>)>) -> Option<u32> {
+    let mut days: Vec<u32> = items
+        .filter_map(|(start, end)| Some((end? - start).num_days().max(0) as u32))
+        .collect();
+    if days.is_empty() {
+        return None;
+    }
+    days.sort();
+    Some(percentile(&days, 50))
+}
+
+/// GET a JSON document using curl, so no TLS stack has to be compiled in. Responses are kept with `--record` and
+/// answered from the recording with `--replay`
+fn get_json(url: &str, headers: &[String]) -> Result<Value, String> {
+    if let Some(response) = replayed_response(url) {
+        return response;
+    }
+    let response = curl(url, headers, None)?;
+    record_response(url, &response);
+    Ok(response)
+}
+
+/// POST a JSON document using curl and return the JSON response
+pub fn post_json(url: &str, headers: &[String], body: &Value) -> Result<Value, String> {
+    curl(url, headers, Some(body))
+}
+
+/// Quote a value for a curl config file, which reads backslash escapes in quoted values
+#[cfg(feature = "hosting")]
+fn config_string(value: &str) -> String {
+    format!(
+        "\"{}\"",
+        value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+            .replace('\r', "\\r")
+    )
+}
+
+#[cfg(feature = "hosting")]
+fn curl(url: &str, headers: &[String], body: Option<&Value>) -> Result<Value, String> {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    // the headers with the tokens are passed as curl config on stdin, so they don't show up in the process list,
+    // and so is the body, as it can exceed the maximum argument length
+    let mut config: String = headers
+        .iter()
+        .map(|header| format!("header = {}\n", config_string(header)))
+        .collect();
+    if let Some(body) = body {
+        config.push_str("header = \"Content-Type: application/json\"\n");
+        config.push_str(&format!(
+            "data-binary = {}\n",
+            config_string(&body.to_string())
+        ));
+    }
+    let mut child = Command::new("curl")
+        .args([
+            "--silent",
+            "--show-error",
+            "--fail",
+            "--location",
+            "--config",
+            "-",
+        ])
+        .arg(url)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("Failed to execute curl, is it installed? ({})", err))?;
+    // dropping stdin closes it, so curl continues after reading the config
+    let mut stdin = child.stdin.take().unwrap();
+    stdin
+        .write_all(config.as_bytes())
+        .map_err(|err| format!("Failed to send request to {}: {}", url, err))?;
+    drop(stdin);
+    let output = child
+        .wait_with_output()
+        .map_err(|err| format!("Failed to execute curl: {}", err))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Request to {} failed: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    serde_json::from_slice(&output.stdout)
+        .map_err(|err| format!("Invalid response from {}: {}", url, err))
+}
+
+#[cfg(not(feature = "hosting"))]
+fn curl(_url: &str, _headers: &[String], _body: Option<&Value>) -> Result<Value, String> {
+    Err("the GitHub and GitLab APIs are not available, reinstall git-report with `--features hosting`".to_string())
+}
+
+/// Fetch pages of a list endpoint until a page comes back incomplete
+fn get_pages(url: &str, headers: &[String]) -> Result<Vec<Value>, String> {
+    let separator = if url.contains('?') { '&' } else { '?' };
+    let mut items = vec![];
+    for page in 1..=MAX_PAGES {
+        let response = get_json(
+            &format!("{}{}per_page=100&page={}", url, separator, page),
+            headers,
+        )?;
+        let page_items = response.as_array().cloned().unwrap_or_default();
+        let done = page_items.len() < 100;
+        items.extend(page_items);
+        if done {
+            break;
+        }
+    }
+    Ok(items)
+}
+
+fn date(value: &Value) -> Option<NaiveDate> {
+    DateTime::parse_from_rfc3339(value.as_str()?)
+        .ok()
+        .map(|date| date.date_naive())
+}
+
+/// Parse a plain `YYYY-MM-DD` date, or the date part of an RFC 3339 timestamp
+fn due_date(value: &Value) -> Option<NaiveDate> {
+    let value = value.as_str()?;
+    NaiveDate::parse_from_str(value.get(..10)?, "%Y-%m-%d").ok()
+}
+
+fn string(value: &Value) -> Option<String> {
+    value.as_str().map(String::from)
+}
+
+pub fn get_hosting_stats(hosting: &Hosting, commits: &[Commit]) -> Result<HostingStats, String> {
+    match hosting {
+        Hosting::GitHub { repo } => get_github_stats(repo, commits),
+        Hosting::GitLab { project, url } => get_gitlab_stats(url, project, commits),
+    }
+}
+
+fn github_headers() -> Vec<String> {
+    let mut headers = vec!["Accept: application/vnd.github+json".to_string()];
+    if let Ok(token) = std::env::var("GITHUB_TOKEN") {
+        headers.push(format!("Authorization: Bearer {}", token));
+    }
+    headers
+}
+
+/// GitHub logins of the commit authors of the repository by lowercased email
+pub fn get_github_logins(repo: &str) -> Result<HashMap<String, String>, String> {
+    let url = format!("https://api.github.com/repos/{}/commits", repo);
+    let mut logins = HashMap::new();
+    for commit in get_pages(&url, &github_headers())? {
+        if let (Some(email), Some(login)) = (
+            string(&commit["commit"]["author"]["email"]),
+            string(&commit["author"]["login"]),
+        ) {
+            logins.entry(email.to_lowercase()).or_insert(login);
+        }
+    }
+    Ok(logins)
+}
+
+fn get_github_stats(repo: &str, commits: &[Commit]) -> Result<HostingStats, String> {
+    let api = format!("https://api.github.com/repos/{}", repo);
+    let headers = github_headers();
+
+    let pull_requests = get_pages(&format!("{}/pulls?state=all", api), &headers)?
+        .iter()
+        .filter_map(|pr| {
+            Some(PullRequest {
+                created: date(&pr["created_at"])?,
+                merged: date(&pr["merged_at"]),
+            })
+        })
+        .collect();
+
+    // the issues endpoint also lists pull requests
+    let issues = get_pages(&format!("{}/issues?state=all", api), &headers)?
+        .iter()
+        .filter(|issue| issue.get("pull_request").is_none())
+        .filter_map(|issue| {
+            Some(Issue {
+                created: date(&issue["created_at"])?,
+                closed: date(&issue["closed_at"]),
+            })
+        })
+        .collect();
+
+    let milestones = get_pages(&format!("{}/milestones?state=all", api), &headers)?
+        .iter()
+        .filter_map(|milestone| {
+            Some(Milestone {
+                title: string(&milestone["title"])?,
+                due: due_date(&milestone["due_on"])?,
+            })
+        })
+        .collect();
+
+    // link the authors in the history to their GitHub accounts
+    let mut by_email: HashMap<String, Profile> = HashMap::new();
+    for commit in get_pages(&format!("{}/commits", api), &headers)? {
+        let (Some(email), Some(login)) = (
+            string(&commit["commit"]["author"]["email"]),
+            string(&commit["author"]["login"]),
+        ) else {
+            continue;
+        };
+        by_email.entry(email.to_lowercase()).or_insert(Profile {
+            login: Some(login),
+            url: string(&commit["author"]["html_url"]),
+            avatar_url: string(&commit["author"]["avatar_url"]),
+        });
+    }
+    let mut profiles = HashMap::new();
+    for commit in commits {
+        if let Some(profile) = by_email.remove(&commit.email) {
+            profiles.insert(commit.author.clone(), profile);
+        }
+    }
+
+    Ok(HostingStats {
+        pull_requests,
+        issues,
+        milestones,
+        profiles,
+    })
+}
+
+fn get_gitlab_stats(url: &str, project: &str, commits: &[Commit]) -> Result<HostingStats, String> {
+    let url = url.trim_end_matches('/');
+    let api = format!("{}/api/v4/projects/{}", url, project.replace('/', "%2F"));
+    let mut headers = vec![];
+    if let Ok(token) = std::env::var("GITLAB_TOKEN") {
+        headers.push(format!("PRIVATE-TOKEN: {}", token));
+    }
+
+    let pull_requests = get_pages(&format!("{}/merge_requests?state=all", api), &headers)?
+        .iter()
+        .filter_map(|mr| {
+            Some(PullRequest {
+                created: date(&mr["created_at"])?,
+                merged: date(&mr["merged_at"]),
+            })
+        })
+        .collect();
+
+    let issues = get_pages(&format!("{}/issues?state=all", api), &headers)?
+        .iter()
+        .filter_map(|issue| {
+            Some(Issue {
+                created: date(&issue["created_at"])?,
+                closed: date(&issue["closed_at"]),
+            })
+        })
+        .collect();
+
+    let milestones = get_pages(&format!("{}/milestones", api), &headers)?
+        .iter()
+        .filter_map(|milestone| {
+            Some(Milestone {
+                title: string(&milestone["title"])?,
+                due: due_date(&milestone["due_date"])?,
+            })
+        })
+        .collect();
+
+    // GitLab only exposes accounts by email to admins, but avatars are public
+    let mut profiles: HashMap<String, Profile> = HashMap::new();
+    for commit in commits {
+        if profiles.contains_key(&commit.author) {
+            continue;
+        }
+        let avatar = get_json(
+            &format!("{}/api/v4/avatar?email={}", url, commit.email),
+            &headers,
+        )
+        .ok()
+        .and_then(|avatar| string(&avatar["avatar_url"]));
+        profiles.insert(
+            commit.author.clone(),
+            Profile {
+                login: None,
+                url: None,
+                avatar_url: avatar,
+            },
+        );
+    }
+
+    Ok(HostingStats {
+        pull_requests,
+        issues,
+        milestones,
+        profiles,
+    })
+}
+
+fn count_per_month(dates: impl Iterator<Item = NaiveDate>) -> BTreeMap<String, u32> {
+    let mut count = BTreeMap::new();
+    for date in dates {
+        *count.entry(date.format("%Y-%m").to_string()).or_insert(0) += 1;
+    }
+    count
+}
+
+fn plot_opened_and_closed(
+    title: &str,
+    opened: BTreeMap<String, u32>,
+    closed: (&str, BTreeMap<String, u32>),
+) -> Plot {
+    let mut plot = Plot::new();
+    plot.add_trace(
+        Bar::new(
+            opened.keys().cloned().collect(),
+            opened.values().cloned().collect(),
+        )
+        .name("Opened"),
+    );
+    plot.add_trace(
+        Bar::new(
+            closed.1.keys().cloned().collect(),
+            closed.1.values().cloned().collect(),
+        )
+        .name(closed.0),
+    );
+    let layout = Layout::new()
+        .width(PLOT_WIDTH - 50)
+        .bar_mode(BarMode::Group)
+        .title(Title::from(title));
+    plot.set_layout(layout);
+    plot
+}
+
+pub fn plot_pull_request_throughput(stats: &HostingStats) -> Plot {
+    plot_opened_and_closed(
+        "Pull requests per month",
+        count_per_month(stats.pull_requests.iter().map(|pr| pr.created)),
+        (
+            "Merged",
+            count_per_month(stats.pull_requests.iter().filter_map(|pr| pr.merged)),
+        ),
+    )
+}
+
+pub fn plot_issue_throughput(stats: &HostingStats) -> Plot {
+    plot_opened_and_closed(
+        "Issues per month",
+        count_per_month(stats.issues.iter().map(|issue| issue.created)),
+        (
+            "Closed",
+            count_per_month(stats.issues.iter().filter_map(|issue| issue.closed)),
+        ),
+    )
+}
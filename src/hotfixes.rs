@@ -0,0 +1,100 @@
+use globset::{Glob, GlobMatcher};
+use plotly::{common::Title, layout::BarMode, Bar, Layout, Plot};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::error::Error;
+use crate::git::run_git;
+use crate::plots::PLOT_WIDTH;
+
+/// Commits per month that landed on a release branch after it was cut
+pub struct ReleaseBranch {
+    pub name: String,
+    pub hotfixes: BTreeMap<String, u32>,
+}
+
+impl ReleaseBranch {
+    pub fn total(&self) -> u32 {
+        self.hotfixes.values().sum()
+    }
+}
+
+pub fn compile_release_pattern(pattern: &str) -> Result<GlobMatcher, String> {
+    Glob::new(pattern)
+        .map(|glob| glob.compile_matcher())
+        .map_err(|err| format!("Invalid release branch pattern {:?}: {}", pattern, err))
+}
+
+/// Release branches matching `matcher` with the commits HEAD doesn't have per month, only counting the commits
+/// changing files under non-empty `scopes`
+pub fn get_release_branches(
+    path: &Path,
+    matcher: &GlobMatcher,
+    scopes: &[String],
+) -> Result<Vec<ReleaseBranch>, Error> {
+    // prefer local branches, only fall back to a remote branch if there is no local one
+    let mut names: BTreeMap<String, String> = BTreeMap::new();
+    let remotes = run_git(
+        path,
+        &[
+            "for-each-ref",
+            "--format=%(refname:lstrip=3) %(refname:lstrip=2)",
+            "refs/remotes",
+        ],
+    )?;
+    let locals = run_git(
+        path,
+        &[
+            "for-each-ref",
+            "--format=%(refname:lstrip=2) %(refname:lstrip=2)",
+            "refs/heads",
+        ],
+    )?;
+    for line in remotes.lines().chain(locals.lines()) {
+        let Some((short, name)) = line.split_once(' ') else {
+            continue;
+        };
+        if short != "HEAD" && matcher.is_match(short) {
+            names.insert(short.to_string(), name.to_string());
+        }
+    }
+
+    let mut branches = vec![];
+    for (short, name) in names {
+        // commits on the release branch that never made it into HEAD
+        let range = format!("HEAD..{}", name);
+        let mut args = vec!["log", "--format=%as", &range, "--"];
+        args.extend(scopes.iter().map(String::as_str));
+        let log = run_git(path, &args)?;
+        let mut hotfixes: BTreeMap<String, u32> = BTreeMap::new();
+        for date in log.lines() {
+            *hotfixes.entry(date[..7].to_string()).or_insert(0) += 1;
+        }
+        let branch = ReleaseBranch {
+            name: short,
+            hotfixes,
+        };
+        if branch.total() > 0 {
+            branches.push(branch);
+        }
+    }
+    Ok(branches)
+}
+
+pub fn plot_hotfixes(branches: &[ReleaseBranch]) -> Plot {
+    let mut plot = Plot::new();
+
+    for branch in branches {
+        let x: Vec<String> = branch.hotfixes.keys().cloned().collect();
+        let y: Vec<u32> = branch.hotfixes.values().cloned().collect();
+        plot.add_trace(Bar::new(x, y).name(&branch.name));
+    }
+
+    let layout = Layout::new()
+        .width(PLOT_WIDTH - 50)
+        .bar_mode(BarMode::Stack)
+        .title(Title::from("Hotfixes per release branch"));
+    plot.set_layout(layout);
+
+    plot
+}
@@ -0,0 +1,60 @@
+use clap::ValueEnum;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Language of the report text
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Lang {
+    #[default]
+    En,
+    De,
+    Nl,
+    Ja,
+}
+
+impl Lang {
+    /// ISO 639-1 code, as used for `--lang` and the `lang` attribute of the report
+    pub fn code(&self) -> &'static str {
+        match self {
+            Lang::En => "en",
+            Lang::De => "de",
+            Lang::Nl => "nl",
+            Lang::Ja => "ja",
+        }
+    }
+
+    /// Locale the numbers and dates of a report in the language are formatted in by default
+    pub fn locale(&self) -> &'static str {
+        match self {
+            Lang::En => "en",
+            Lang::De => "de-DE",
+            Lang::Nl => "nl-NL",
+            Lang::Ja => "ja-JP",
+        }
+    }
+
+    /// Translations of the English report text, parsed on first use
+    fn catalog(&self) -> Option<&'static HashMap<String, String>> {
+        static DE: OnceLock<HashMap<String, String>> = OnceLock::new();
+        static NL: OnceLock<HashMap<String, String>> = OnceLock::new();
+        static JA: OnceLock<HashMap<String, String>> = OnceLock::new();
+        let (catalog, source) = match self {
+            Lang::En => return None,
+            Lang::De => (&DE, include_str!("../locales/de.toml")),
+            Lang::Nl => (&NL, include_str!("../locales/nl.toml")),
+            Lang::Ja => (&JA, include_str!("../locales/ja.toml")),
+        };
+        Some(
+            catalog.get_or_init(|| {
+                toml::from_str(source).expect("the message catalogs are valid TOML")
+            }),
+        )
+    }
+
+    /// The translation of an English message, or the message itself when the catalog doesn't have it
+    pub fn translate<'a>(&self, message: &'a str) -> &'a str {
+        self.catalog()
+            .and_then(|catalog| catalog.get(message))
+            .map_or(message, |translation| translation.as_str())
+    }
+}
@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+
+use crate::git::Commit;
+
+const NOREPLY_DOMAIN: &str = "users.noreply.github.com";
+
+/// Login of a GitHub noreply address like `12345+login@users.noreply.github.com` or `login@users.noreply.github.com`
+fn noreply_login(email: &str) -> Option<String> {
+    let (local_part, domain) = email.rsplit_once('@')?;
+    if !domain.eq_ignore_ascii_case(NOREPLY_DOMAIN) {
+        return None;
+    }
+    let login = local_part.rsplit('+').next()?;
+    Some(login.to_lowercase()).filter(|login| !login.is_empty())
+}
+
+/// Credit the commits made with a GitHub noreply address to the name and email the same person uses elsewhere in the
+/// history, so they aren't counted as two contributors. An identity is the same person when the local part of its
+/// email or its name is the login of the noreply address, or when `logins` (lowercased emails to GitHub logins)
+/// links its email to the login. Of several matching identities the one with the most commits is used, without one
+/// the noreply identities of a login are merged into their most common one
+pub fn unify_noreply_identities(commits: &mut [Commit], logins: &HashMap<String, String>) {
+    // commits per (name, email) identity
+    let mut identities: HashMap<(String, String), usize> = HashMap::new();
+    for commit in commits.iter() {
+        *identities
+            .entry((commit.author.clone(), commit.email.clone()))
+            .or_insert(0) += 1;
+    }
+    let noreply_logins: Vec<String> = identities
+        .keys()
+        .filter_map(|(_, email)| noreply_login(email))
+        .collect();
+    if noreply_logins.is_empty() {
+        return;
+    }
+
+    let mut canonical: HashMap<String, (String, String)> = HashMap::new();
+    for login in noreply_logins {
+        if canonical.contains_key(&login) {
+            continue;
+        }
+        let is_login = |name: &str, email: &str| {
+            let local_part = email.split('@').next().unwrap_or_default();
+            local_part.eq_ignore_ascii_case(&login)
+                || name.eq_ignore_ascii_case(&login)
+                || logins
+                    .get(email)
+                    .is_some_and(|other| other.eq_ignore_ascii_case(&login))
+        };
+        // prefer a real email, and the identity with the most commits
+        let best = identities
+            .iter()
+            .filter(|((name, email), _)| match noreply_login(email) {
+                Some(other) => other == login,
+                None => is_login(name, email),
+            })
+            .max_by(|(a, a_commits), (b, b_commits)| {
+                let rank = |(_, email): &(String, String), commits: usize| {
+                    (noreply_login(email).is_none(), commits)
+                };
+                rank(a, **a_commits)
+                    .cmp(&rank(b, **b_commits))
+                    .then(b.cmp(a))
+            });
+        if let Some((identity, _)) = best {
+            canonical.insert(login, identity.clone());
+        }
+    }
+
+    for commit in commits.iter_mut() {
+        let Some(login) = noreply_login(&commit.email) else {
+            continue;
+        };
+        let Some((name, email)) = canonical.get(&login) else {
+            continue;
+        };
+        commit.author = name.clone();
+        commit.email = email.clone();
+    }
+}
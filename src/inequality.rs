@@ -0,0 +1,90 @@
+use plotly::{
+    common::{DashType, Line, Mode, Title},
+    layout::Axis,
+    Layout, Plot, Scatter,
+};
+
+use crate::plots::PLOT_WIDTH;
+
+/// Share of the commits the fewest authors together account for in the Pareto statistic
+const PARETO_SHARE: f64 = 0.8;
+/// Share of the commits the fewest authors together account for in the bus factor
+const BUS_FACTOR_SHARE: f64 = 0.5;
+
+pub struct Inequality {
+    /// Gini coefficient of the commits per author, from 0 when everyone contributes equally to 1 when one author
+    /// made all commits
+    pub gini: f64,
+    /// Fewest authors who together made at least 80% of the commits
+    pub pareto_authors: usize,
+    /// Fewest authors who together made at least half of the commits
+    pub bus_factor: usize,
+    pub authors: usize,
+}
+
+/// `counts` are the commits per author in ascending order, as returned by `count_commits_per_author`
+pub fn get_inequality(counts: &[(String, f64)]) -> Inequality {
+    let n = counts.len() as f64;
+    let total: f64 = counts.iter().map(|(_, count)| count).sum();
+    let weighted: f64 = counts
+        .iter()
+        .enumerate()
+        .map(|(i, (_, count))| (i + 1) as f64 * count)
+        .sum();
+    let gini = match total > 0.0 {
+        true => 2.0 * weighted / (n * total) - (n + 1.0) / n,
+        false => 0.0,
+    };
+
+    Inequality {
+        gini,
+        pareto_authors: fewest_authors(counts, PARETO_SHARE * total),
+        bus_factor: fewest_authors(counts, BUS_FACTOR_SHARE * total),
+        authors: counts.len(),
+    }
+}
+
+/// Fewest of the most active authors who together made at least `commits` commits
+fn fewest_authors(counts: &[(String, f64)], commits: f64) -> usize {
+    let mut covered = 0.0;
+    counts
+        .iter()
+        .rev()
+        .take_while(|(_, count)| {
+            let done = covered >= commits;
+            covered += count;
+            !done
+        })
+        .count()
+}
+
+/// Cumulative share of the commits against the cumulative share of the authors, from the least active author
+pub fn plot_lorenz_curve(counts: &[(String, f64)]) -> Plot {
+    let mut plot = Plot::new();
+
+    let total: f64 = counts.iter().map(|(_, count)| count).sum::<f64>().max(1.0);
+    let mut x = vec![0.0];
+    let mut y = vec![0.0];
+    let mut cumulative = 0.0;
+    for (i, (_, count)) in counts.iter().enumerate() {
+        cumulative += count;
+        x.push(100.0 * (i + 1) as f64 / counts.len() as f64);
+        y.push(100.0 * cumulative / total);
+    }
+    plot.add_trace(
+        Scatter::new(vec![0.0, 100.0], vec![0.0, 100.0])
+            .mode(Mode::Lines)
+            .line(Line::new().dash(DashType::Dash))
+            .name("Equal contributions"),
+    );
+    plot.add_trace(Scatter::new(x, y).mode(Mode::Lines).name("Commits"));
+
+    let layout = Layout::new()
+        .width(PLOT_WIDTH / 2)
+        .title(Title::from("Lorenz curve of commits per author"))
+        .x_axis(Axis::new().title(Title::from("% of authors")))
+        .y_axis(Axis::new().title(Title::from("% of commits")));
+    plot.set_layout(layout);
+
+    plot
+}
@@ -0,0 +1,226 @@
+use chrono::NaiveDate;
+use plotly::{
+    common::{Mode, Title},
+    layout::Axis,
+    Layout, Plot, Scatter,
+};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+use crate::error::Error;
+use crate::git::{run_git, Commit};
+use crate::plots::{Granularity, PLOT_WIDTH};
+
+const KINDS: [&str; 4] = ["Terraform", "Kubernetes", "Helm", "Docker"];
+
+pub struct InfraFiles {
+    pub kind: &'static str,
+    /// Text files and their lines at HEAD
+    pub files: usize,
+    pub lines: usize,
+    /// Commits changing files of this kind and the lines they changed
+    pub commits: usize,
+    pub lines_changed: u32,
+}
+
+pub struct Infra {
+    pub kinds: Vec<InfraFiles>,
+    /// Lines of infrastructure and of other code at the end of each period, added minus deleted lines since the
+    /// first commit
+    pub growth: BTreeMap<NaiveDate, (i64, i64)>,
+}
+
+/// Tells infrastructure files apart. Kubernetes manifests are recognized by their content at HEAD, so manifests that
+/// have since been deleted count as other code
+struct Classifier {
+    kubernetes: HashSet<String>,
+    /// Directories containing a `Chart.yaml`, everything inside them belongs to the Helm chart
+    helm_charts: Vec<String>,
+}
+
+impl Classifier {
+    fn new(path: &Path) -> Result<Self, Error> {
+        let manifests = run_git(
+            path,
+            &[
+                "-c",
+                "core.quotePath=false",
+                "grep",
+                "-l",
+                "--all-match",
+                "-e",
+                "^apiVersion:",
+                "-e",
+                "^kind:",
+                "HEAD",
+                "--",
+                "*.yaml",
+                "*.yml",
+            ],
+        )?;
+        let files = run_git(
+            path,
+            &[
+                "-c",
+                "core.quotePath=false",
+                "ls-tree",
+                "-r",
+                "--name-only",
+                "HEAD",
+            ],
+        )?;
+        Ok(Classifier {
+            kubernetes: manifests
+                .lines()
+                .filter_map(|line| line.strip_prefix("HEAD:"))
+                .map(String::from)
+                .collect(),
+            helm_charts: files
+                .lines()
+                .filter_map(|file| match file.rsplit_once('/') {
+                    Some((dir, "Chart.yaml")) => Some(format!("{}/", dir)),
+                    None if file == "Chart.yaml" => Some(String::new()),
+                    _ => None,
+                })
+                .collect(),
+        })
+    }
+
+    /// Index into `KINDS`, `None` for other code
+    fn kind(&self, path: &str) -> Option<usize> {
+        let name = path.rsplit('/').next().unwrap_or(path);
+        if self
+            .helm_charts
+            .iter()
+            .any(|chart| path.starts_with(chart.as_str()))
+        {
+            Some(2)
+        } else if name == "Dockerfile"
+            || name == "Containerfile"
+            || name.starts_with("Dockerfile.")
+            || name.ends_with(".dockerfile")
+            || ((name.starts_with("docker-compose") || name.starts_with("compose."))
+                && (name.ends_with(".yml") || name.ends_with(".yaml")))
+        {
+            Some(3)
+        } else if name.ends_with(".tf") || name.ends_with(".tfvars") || name.ends_with(".tf.json") {
+            Some(0)
+        } else if self.kubernetes.contains(path)
+            || name == "kustomization.yaml"
+            || name == "kustomization.yml"
+        {
+            Some(1)
+        } else {
+            None
+        }
+    }
+}
+
+/// Volume and churn of Terraform, Kubernetes, Helm and Docker files, `None` if the repository has none of them.
+/// The churn and growth are only counted when the commits include the changed files
+pub fn get_infra(
+    path: &Path,
+    commits: &[Commit],
+    granularity: Granularity,
+) -> Result<Option<Infra>, Error> {
+    let classifier = Classifier::new(path)?;
+    let mut kinds: Vec<InfraFiles> = KINDS
+        .iter()
+        .map(|kind| InfraFiles {
+            kind,
+            files: 0,
+            lines: 0,
+            commits: 0,
+            lines_changed: 0,
+        })
+        .collect();
+
+    // lines look like `HEAD:<path>:<lines>`
+    let lines = run_git(
+        path,
+        &[
+            "-c",
+            "core.quotePath=false",
+            "grep",
+            "-I",
+            "-c",
+            "-e",
+            "",
+            "HEAD",
+            "--",
+        ],
+    )?;
+    for line in lines.lines() {
+        let Some((file, count)) = line
+            .strip_prefix("HEAD:")
+            .and_then(|line| line.rsplit_once(':'))
+        else {
+            continue;
+        };
+        if let Some(i) = classifier.kind(file) {
+            kinds[i].files += 1;
+            kinds[i].lines += count.parse::<usize>().unwrap_or(0);
+        }
+    }
+
+    let mut changes: HashMap<NaiveDate, (i64, i64)> = HashMap::new();
+    for commit in commits {
+        let mut changed = HashSet::new();
+        let period = changes.entry(granularity.bucket(commit.date)).or_default();
+        for file in &commit.files {
+            let (added, deleted) = (file.added.unwrap_or(0), file.deleted.unwrap_or(0));
+            let net = added as i64 - deleted as i64;
+            match classifier.kind(&file.path) {
+                Some(i) => {
+                    changed.insert(i);
+                    kinds[i].lines_changed += added + deleted;
+                    period.0 += net;
+                }
+                None => period.1 += net,
+            }
+        }
+        for i in changed {
+            kinds[i].commits += 1;
+        }
+    }
+
+    if kinds
+        .iter()
+        .all(|kind| kind.files == 0 && kind.commits == 0)
+    {
+        return Ok(None);
+    }
+
+    let mut total = (0, 0);
+    let growth = changes
+        .into_iter()
+        .collect::<BTreeMap<_, _>>()
+        .into_iter()
+        .map(|(period, (infra, other))| {
+            total = (total.0 + infra, total.1 + other);
+            (period, total)
+        })
+        .collect();
+    Ok(Some(Infra { kinds, growth }))
+}
+
+/// Share of infrastructure code in all lines over time
+pub fn plot_infra_share(infra: &Infra) -> Plot {
+    let mut plot = Plot::new();
+
+    let x: Vec<String> = infra.growth.keys().map(|date| date.to_string()).collect();
+    let y: Vec<f64> = infra
+        .growth
+        .values()
+        .map(|(infra, other)| 100.0 * *infra.max(&0) as f64 / (infra + other).max(1) as f64)
+        .collect();
+    plot.add_trace(Scatter::new(x, y).mode(Mode::Lines).name("Infrastructure"));
+
+    let layout = Layout::new()
+        .width(PLOT_WIDTH / 2)
+        .title(Title::from("Infrastructure code (% of lines)"))
+        .y_axis(Axis::new().range(vec![0, 100]));
+    plot.set_layout(layout);
+
+    plot
+}
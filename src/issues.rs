@@ -0,0 +1,130 @@
+use plotly::{common::Title, layout::BarMode, Bar, Layout, Plot};
+use regex::Regex;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::config::Issues;
+use crate::git::Commit;
+use crate::plots::PLOT_WIDTH;
+
+pub struct IssueReferences {
+    /// Per month counts of (commits referencing an issue, commits without a reference)
+    pub per_month: BTreeMap<String, (u32, u32)>,
+    /// Issues and the number of commits referencing them, most referenced first
+    pub top_issues: Vec<(String, usize)>,
+    pub referencing_commits: usize,
+}
+
+/// The compiled issue patterns of the config
+pub struct IssuePatterns {
+    patterns: Vec<Regex>,
+    ignored_keys: Vec<String>,
+}
+
+impl IssuePatterns {
+    pub fn new(issues: &Issues) -> Result<Self, String> {
+        let patterns = issues
+            .patterns
+            .iter()
+            .map(|pattern| {
+                Regex::new(pattern)
+                    .map_err(|err| format!("Invalid issue pattern {:?}: {}", pattern, err))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(IssuePatterns {
+            patterns,
+            ignored_keys: issues.ignored_keys.clone(),
+        })
+    }
+
+    fn is_ignored(&self, issue: &str) -> bool {
+        issue
+            .split_once('-')
+            .is_some_and(|(key, _)| self.ignored_keys.iter().any(|ignored| ignored == key))
+    }
+}
+
+/// All distinct issues referenced in a commit message
+pub fn find_issues(message: &str, patterns: &IssuePatterns) -> BTreeSet<String> {
+    let mut issues = BTreeSet::new();
+    for pattern in &patterns.patterns {
+        for captures in pattern.captures_iter(message) {
+            let issue = captures.get(1).or(captures.get(0)).unwrap().as_str();
+            if !patterns.is_ignored(issue) {
+                issues.insert(issue.to_string());
+            }
+        }
+    }
+    issues
+}
+
+pub fn get_issue_references(
+    commits: &[Commit],
+    patterns: &IssuePatterns,
+    n: usize,
+) -> IssueReferences {
+    let mut per_month: BTreeMap<String, (u32, u32)> = BTreeMap::new();
+    let mut count: HashMap<String, usize> = HashMap::new();
+    let mut referencing_commits = 0;
+    for commit in commits {
+        let issues = find_issues(&commit.message, patterns);
+        let month = per_month
+            .entry(commit.date.format("%Y-%m").to_string())
+            .or_default();
+        if issues.is_empty() {
+            month.1 += 1;
+            continue;
+        }
+        month.0 += 1;
+        referencing_commits += 1;
+        for issue in issues {
+            *count.entry(issue).or_insert(0) += 1;
+        }
+    }
+
+    let mut top_issues: Vec<(String, usize)> = count.into_iter().collect();
+    top_issues.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+    top_issues.truncate(n);
+
+    IssueReferences {
+        per_month,
+        top_issues,
+        referencing_commits,
+    }
+}
+
+pub fn plot_issue_references(references: &IssueReferences) -> Plot {
+    let mut plot = Plot::new();
+
+    let x: Vec<String> = references.per_month.keys().cloned().collect();
+    let with: Vec<u32> = references.per_month.values().map(|(w, _)| *w).collect();
+    let without: Vec<u32> = references.per_month.values().map(|(_, w)| *w).collect();
+    plot.add_trace(Bar::new(x.clone(), with).name("References an issue"));
+    plot.add_trace(Bar::new(x, without).name("No reference"));
+
+    let layout = Layout::new()
+        .width(PLOT_WIDTH - 50)
+        .bar_mode(BarMode::Stack)
+        .title(Title::from("Commits referencing issues"));
+    plot.set_layout(layout);
+
+    plot
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ignores_encodings_hashes_and_standards() {
+        let patterns = IssuePatterns::new(&Issues::default()).unwrap();
+        let issues = find_issues(
+            "Read UTF-8 files and check SHA-256 sums\n\n\
+             Dates are ISO-8601 and CVE-2024-1234 is fixed per RFC-3339, on X86-64 as well. Fixes PROJ-12, see #34",
+            &patterns,
+        );
+        assert_eq!(
+            issues,
+            BTreeSet::from(["#34".to_string(), "PROJ-12".to_string()])
+        );
+    }
+}
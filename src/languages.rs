@@ -0,0 +1,346 @@
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use plotly::{
+    common::{Orientation, Title},
+    layout::{BarMode, Margin},
+    Bar, Layout, Plot,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+#[cfg(feature = "tokei")]
+pub use tokei::{Config, Language, LanguageType, Report};
+
+#[cfg(not(feature = "tokei"))]
+pub use crate::linecount::{Config, Language, LanguageType, Report};
+
+use crate::error::Error;
+use crate::git::{run_git, Commit};
+use crate::plots::{Treemap, PLOT_WIDTH};
+
+/// Languages shown separately in the per author breakdown, the others are grouped together
+const TOP_LANGUAGES: usize = 8;
+
+/// Move the files of the line counts from under `from` to under `to`, to replay the line counts of a recording in
+/// another directory
+pub fn rebase_languages(languages: &mut [(LanguageType, Language)], from: &Path, to: &Path) {
+    for (_, language) in languages {
+        for report in &mut language.reports {
+            if let Ok(path) = report.name.strip_prefix(from) {
+                report.name = to.join(path);
+            }
+        }
+    }
+}
+
+/// Count the lines of code of every file tracked by git, including the files of checked out submodules, leaving out
+/// the `excluded` paths relative to the repository and the files outside non-empty `scopes`. Untracked and ignored
+/// files in the working tree are left out, so build output and dependencies aren't counted
+pub fn get_repo_languages(
+    repo_path: &Path,
+    excluded: &[String],
+    scopes: &[String],
+) -> Result<Vec<(LanguageType, Language)>, Error> {
+    let mut args = vec![
+        "-c",
+        "core.quotePath=false",
+        "ls-files",
+        "-z",
+        "--recurse-submodules",
+        "--",
+    ];
+    args.extend(scopes.iter().map(String::as_str));
+    let tracked = run_git(repo_path, &args)?;
+    let config = Config::default();
+    let files: Vec<(LanguageType, PathBuf)> = tracked
+        .split('\0')
+        .filter(|file| {
+            !file.is_empty()
+                && !excluded
+                    .iter()
+                    .any(|path| Path::new(file).starts_with(path))
+        })
+        .map(|file| repo_path.join(file))
+        // deleted files and symlinks, which tokei doesn't follow either
+        .filter(|path| path.symlink_metadata().is_ok_and(|meta| meta.is_file()))
+        .filter_map(|path| Some((LanguageType::from_path(&path, &config)?, path)))
+        .collect();
+
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let chunks = files.chunks(files.len().div_ceil(threads).max(1));
+    let reports: Vec<Vec<(LanguageType, Option<Report>)>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = chunks
+            .map(|chunk| {
+                let config = &config;
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|(language, path)| {
+                            (*language, language.parse(path.clone(), config).ok())
+                        })
+                        .collect()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("line counting thread panicked"))
+            .collect()
+    });
+
+    let mut languages: BTreeMap<LanguageType, Language> = BTreeMap::new();
+    for (language_type, report) in reports.into_iter().flatten() {
+        let language = languages.entry(language_type).or_default();
+        match report {
+            Some(report) => language.add_report(report),
+            // unreadable files, like files that were deleted while counting
+            None => language.mark_inaccurate(),
+        }
+    }
+    for language in languages.values_mut() {
+        language.total();
+    }
+
+    // sort languages by total number of lines
+    let mut languages = languages.into_iter().collect::<Vec<_>>();
+    languages.sort_by_key(|(_, language)| std::cmp::Reverse(language.lines()));
+    Ok(languages)
+}
+
+/// Lines of code across all languages, without comments and blanks
+pub fn count_lines_of_code(languages: &[(LanguageType, Language)]) -> usize {
+    languages.iter().map(|(_, language)| language.code).sum()
+}
+
+/// A counted file
+pub struct FileSize {
+    /// Relative to the repository
+    pub path: String,
+    pub language: LanguageType,
+    pub code: usize,
+    pub comments: usize,
+}
+
+impl FileSize {
+    /// Share of comments in the lines that aren't blank
+    pub fn comment_share(&self) -> f64 {
+        self.comments as f64 / (self.code + self.comments).max(1) as f64
+    }
+}
+
+/// The `n` files with the most lines of code, including code embedded in other languages
+pub fn get_largest_files(
+    repo_path: &Path,
+    languages: &[(LanguageType, Language)],
+    n: usize,
+) -> Vec<FileSize> {
+    let mut files: Vec<FileSize> = languages
+        .iter()
+        .flat_map(|(language_type, language)| {
+            language.reports.iter().map(|report| {
+                let stats = report.stats.summarise();
+                let path = report.name.strip_prefix(repo_path).unwrap_or(&report.name);
+                FileSize {
+                    path: path.to_string_lossy().to_string(),
+                    language: *language_type,
+                    code: stats.code,
+                    comments: stats.comments,
+                }
+            })
+        })
+        .collect();
+    files.sort_by(|a, b| b.code.cmp(&a.code).then(a.path.cmp(&b.path)));
+    files.truncate(n);
+    files
+}
+
+/// Lines of code, comments and blanks across all languages, with code split into test and production code
+pub struct CodeRatios {
+    pub code: usize,
+    pub comments: usize,
+    pub blanks: usize,
+    pub test_code: usize,
+}
+
+impl CodeRatios {
+    fn lines(&self) -> usize {
+        (self.code + self.comments + self.blanks).max(1)
+    }
+
+    pub fn code_share(&self) -> f64 {
+        self.code as f64 / self.lines() as f64
+    }
+
+    pub fn comment_share(&self) -> f64 {
+        self.comments as f64 / self.lines() as f64
+    }
+
+    pub fn blank_share(&self) -> f64 {
+        self.blanks as f64 / self.lines() as f64
+    }
+
+    /// Lines of test code per line of production code
+    pub fn test_to_code(&self) -> f64 {
+        self.test_code as f64 / (self.code - self.test_code).max(1) as f64
+    }
+}
+
+pub fn compile_test_patterns(patterns: &[String]) -> Result<GlobSet, String> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(
+            Glob::new(pattern)
+                .map_err(|err| format!("Invalid test pattern {:?}: {}", pattern, err))?,
+        );
+    }
+    builder
+        .build()
+        .map_err(|err| format!("Invalid test patterns: {}", err))
+}
+
+/// Sum the per file statistics, files matching `tests` (relative to the repository) count as test code
+pub fn get_code_ratios(
+    repo_path: &Path,
+    languages: &[(LanguageType, Language)],
+    tests: &GlobSet,
+) -> CodeRatios {
+    let mut ratios = CodeRatios {
+        code: 0,
+        comments: 0,
+        blanks: 0,
+        test_code: 0,
+    };
+    for report in languages.iter().flat_map(|(_, language)| &language.reports) {
+        // include code embedded in other languages, like code blocks in Markdown
+        let stats = report.stats.summarise();
+        ratios.code += stats.code;
+        ratios.comments += stats.comments;
+        ratios.blanks += stats.blanks;
+        let path = report.name.strip_prefix(repo_path).unwrap_or(&report.name);
+        if tests.is_match(path) {
+            ratios.test_code += stats.code;
+        }
+    }
+    ratios
+}
+
+/// Lines of code per language, broken down by directory
+pub fn plot_code_treemap(repo_path: &Path, languages: &[(LanguageType, Language)]) -> Plot {
+    let mut plot = Plot::new();
+
+    let mut treemap = Treemap::new();
+    let total: usize = languages.iter().map(|(_, language)| language.code).sum();
+    treemap.push("All".to_string(), "All".to_string(), String::new(), total);
+    for (language_type, language) in languages {
+        let language_id = format!("All/{}", language_type);
+        treemap.push(
+            language_id.clone(),
+            language_type.to_string(),
+            "All".to_string(),
+            language.code,
+        );
+        treemap.push_directories(
+            &language_id,
+            language.reports.iter().map(|report| {
+                let path = report.name.strip_prefix(repo_path).unwrap_or(&report.name);
+                (path, report.stats.summarise().code)
+            }),
+        );
+    }
+    plot.add_trace(treemap);
+
+    let layout = Layout::new()
+        .width(PLOT_WIDTH - 50)
+        .height(600)
+        .title(Title::from("Lines of code per language and directory"))
+        .margin(Margin::new().left(10).right(10));
+    plot.set_layout(layout);
+
+    plot
+}
+
+/// Lines changed per language by the authors who changed the most lines of code, languages are recognised like
+/// tokei does from the file name or extension
+pub fn plot_languages_per_author(repo_path: &Path, commits: &[Commit], n: usize) -> Plot {
+    let mut plot = Plot::new();
+
+    let config = Config::default();
+    let mut languages: HashMap<&str, Option<LanguageType>> = HashMap::new();
+    let mut per_author: HashMap<&str, HashMap<LanguageType, u32>> = HashMap::new();
+    // merge commits are skipped, as their changes are already counted in the commits they merge
+    for commit in commits.iter().filter(|commit| !commit.is_merge()) {
+        for file in &commit.files {
+            let language = *languages
+                .entry(&file.path)
+                .or_insert_with(|| LanguageType::from_path(repo_path.join(&file.path), &config));
+            let Some(language) = language else {
+                continue;
+            };
+            let lines = file.added.unwrap_or(0) + file.deleted.unwrap_or(0);
+            *per_author
+                .entry(&commit.author)
+                .or_default()
+                .entry(language)
+                .or_insert(0) += lines;
+        }
+    }
+
+    let mut totals: HashMap<LanguageType, u32> = HashMap::new();
+    for lines in per_author.values() {
+        for (language, count) in lines {
+            *totals.entry(*language).or_insert(0) += count;
+        }
+    }
+    let mut top_languages: Vec<(LanguageType, u32)> = totals.into_iter().collect();
+    top_languages.sort_by_key(|(language, lines)| (std::cmp::Reverse(*lines), language.name()));
+
+    let mut authors: Vec<(&str, HashMap<LanguageType, u32>)> = per_author.into_iter().collect();
+    authors
+        .sort_by_key(|(author, lines)| (std::cmp::Reverse(lines.values().sum::<u32>()), *author));
+    authors.truncate(n);
+    // most lines changed at the top
+    authors.reverse();
+
+    let y: Vec<String> = authors
+        .iter()
+        .map(|(author, _)| author.to_string())
+        .collect();
+    for (language, _) in top_languages.iter().take(TOP_LANGUAGES) {
+        let x: Vec<u32> = authors
+            .iter()
+            .map(|(_, lines)| lines.get(language).copied().unwrap_or(0))
+            .collect();
+        plot.add_trace(
+            Bar::new(x, y.clone())
+                .orientation(Orientation::Horizontal)
+                .name(language.name()),
+        );
+    }
+    if top_languages.len() > TOP_LANGUAGES {
+        let other: Vec<&LanguageType> = top_languages[TOP_LANGUAGES..]
+            .iter()
+            .map(|(language, _)| language)
+            .collect();
+        let x: Vec<u32> = authors
+            .iter()
+            .map(|(_, lines)| {
+                other
+                    .iter()
+                    .filter_map(|language| lines.get(language))
+                    .sum()
+            })
+            .collect();
+        plot.add_trace(
+            Bar::new(x, y)
+                .orientation(Orientation::Horizontal)
+                .name("Other"),
+        );
+    }
+
+    let layout = Layout::new()
+        .width(PLOT_WIDTH)
+        .bar_mode(BarMode::Stack)
+        .title(Title::from("Lines changed per language and author"))
+        .margin(Margin::new().left(150));
+    plot.set_layout(layout);
+
+    plot
+}
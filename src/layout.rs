@@ -0,0 +1,80 @@
+/// Rows of panels in the report template by default, panels without data are left out when rendering
+const DEFAULT_ROWS: [&[&str]; 42] = [
+    &["activity"],
+    &["metrics-history"],
+    &["tenure"],
+    &["active-contributors"],
+    &["new-contributors"],
+    &["forecast"],
+    &["streaks"],
+    &["after-hours"],
+    &["commit-tone"],
+    &["merges"],
+    &["branches"],
+    &["notable-events"],
+    &["sensitive-changes"],
+    &["signatures"],
+    &["commit-sizes"],
+    &["squashed-commits"],
+    &["pairing"],
+    &["first-response"],
+    &["sprints"],
+    &["milestones"],
+    &["release-cadence"],
+    &["code-age"],
+    &["public-api"],
+    &["code-owners"],
+    &["defect-prone-files"],
+    &["issues"],
+    &["hotfixes"],
+    &["pull-requests"],
+    &["contributors"],
+    &["affiliations"],
+    &["team-weeks"],
+    &["top-contributors", "languages"],
+    &["inequality"],
+    &["author-languages"],
+    &["file-types"],
+    &["submodules"],
+    &["data-files"],
+    &["infrastructure"],
+    &["code-size"],
+    &["largest-files"],
+    &["file-dossiers"],
+    &["plugins"],
+];
+
+/// The configured rows of panels, followed by the rows of the panels they leave out in the default order
+pub fn get_layout(rows: &[Vec<String>]) -> Result<Vec<Vec<String>>, String> {
+    let panels: Vec<&str> = DEFAULT_ROWS
+        .iter()
+        .flat_map(|row| row.iter().copied())
+        .collect();
+    let mut placed: Vec<&str> = vec![];
+    for panel in rows.iter().flatten() {
+        let Some(panel) = panels.iter().find(|name| *name == panel) else {
+            return Err(format!(
+                "Unknown panel {:?} in the layout, expected one of {}",
+                panel,
+                panels.join(", ")
+            ));
+        };
+        if placed.contains(panel) {
+            return Err(format!("Panel {:?} appears twice in the layout", panel));
+        }
+        placed.push(panel);
+    }
+
+    let remaining = DEFAULT_ROWS.iter().map(|row| {
+        row.iter()
+            .filter(|panel| !placed.contains(panel))
+            .map(|panel| panel.to_string())
+            .collect::<Vec<_>>()
+    });
+    Ok(rows
+        .iter()
+        .cloned()
+        .chain(remaining)
+        .filter(|row| !row.is_empty())
+        .collect())
+}
@@ -0,0 +1,403 @@
+//! A minimal line counter used instead of tokei when building without the `tokei` feature. It mirrors the part of
+//! tokei's API the report uses, and recognizes the most common languages by file name or extension. Comments are
+//! found line by line, skipping only double-quoted strings, and code embedded in other languages isn't counted separately
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Counting has no options, it exists to keep the same calls as with tokei
+#[derive(Default)]
+pub struct Config {}
+
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+pub struct CodeStats {
+    pub blanks: usize,
+    pub code: usize,
+    pub comments: usize,
+}
+
+impl CodeStats {
+    /// The stats themselves, tokei adds the stats of embedded languages here
+    pub fn summarise(&self) -> CodeStats {
+        self.clone()
+    }
+}
+
+/// The lines of a single file
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Report {
+    pub stats: CodeStats,
+    pub name: PathBuf,
+}
+
+/// The lines of all files of a language
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+pub struct Language {
+    pub blanks: usize,
+    pub code: usize,
+    pub comments: usize,
+    pub reports: Vec<Report>,
+    /// Whether some files couldn't be read
+    pub inaccurate: bool,
+}
+
+impl Language {
+    pub fn add_report(&mut self, report: Report) {
+        self.reports.push(report);
+    }
+
+    pub fn mark_inaccurate(&mut self) {
+        self.inaccurate = true;
+    }
+
+    /// Sum the lines of the reports
+    pub fn total(&mut self) {
+        self.blanks = self.reports.iter().map(|report| report.stats.blanks).sum();
+        self.code = self.reports.iter().map(|report| report.stats.code).sum();
+        self.comments = self
+            .reports
+            .iter()
+            .map(|report| report.stats.comments)
+            .sum();
+    }
+
+    pub fn lines(&self) -> usize {
+        self.blanks + self.code + self.comments
+    }
+}
+
+/// How comments are written in a language
+struct Syntax {
+    line_comments: &'static [&'static str],
+    block_comments: &'static [(&'static str, &'static str)],
+    /// Strings counted as comments when they start a line, like Python docstrings
+    doc_quotes: &'static [(&'static str, &'static str)],
+    /// Every line that isn't blank is a comment, like in Markdown
+    prose: bool,
+}
+
+const C_LIKE: Syntax = Syntax {
+    line_comments: &["//"],
+    block_comments: &[("/*", "*/")],
+    doc_quotes: &[],
+    prose: false,
+};
+const HASH: Syntax = Syntax {
+    line_comments: &["#"],
+    block_comments: &[],
+    doc_quotes: &[],
+    prose: false,
+};
+const MARKUP: Syntax = Syntax {
+    line_comments: &[],
+    block_comments: &[("<!--", "-->")],
+    doc_quotes: &[],
+    prose: false,
+};
+
+/// The languages of the built-in counter, named and serialized like tokei does
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum LanguageType {
+    C,
+    CHeader,
+    Cpp,
+    CppHeader,
+    CSharp,
+    Css,
+    Dockerfile,
+    Go,
+    Hcl,
+    Html,
+    Java,
+    JavaScript,
+    Json,
+    Jsx,
+    Kotlin,
+    Lua,
+    Makefile,
+    Markdown,
+    Php,
+    Python,
+    Ruby,
+    Rust,
+    Scala,
+    Sh,
+    Sql,
+    Swift,
+    Toml,
+    Tsx,
+    TypeScript,
+    Xml,
+    Yaml,
+}
+
+const LANGUAGES: [LanguageType; 31] = [
+    LanguageType::C,
+    LanguageType::CHeader,
+    LanguageType::Cpp,
+    LanguageType::CppHeader,
+    LanguageType::CSharp,
+    LanguageType::Css,
+    LanguageType::Dockerfile,
+    LanguageType::Go,
+    LanguageType::Hcl,
+    LanguageType::Html,
+    LanguageType::Java,
+    LanguageType::JavaScript,
+    LanguageType::Json,
+    LanguageType::Jsx,
+    LanguageType::Kotlin,
+    LanguageType::Lua,
+    LanguageType::Makefile,
+    LanguageType::Markdown,
+    LanguageType::Php,
+    LanguageType::Python,
+    LanguageType::Ruby,
+    LanguageType::Rust,
+    LanguageType::Scala,
+    LanguageType::Sh,
+    LanguageType::Sql,
+    LanguageType::Swift,
+    LanguageType::Toml,
+    LanguageType::Tsx,
+    LanguageType::TypeScript,
+    LanguageType::Xml,
+    LanguageType::Yaml,
+];
+
+impl LanguageType {
+    pub fn name(self) -> &'static str {
+        match self {
+            LanguageType::C => "C",
+            LanguageType::CHeader => "C Header",
+            LanguageType::Cpp => "C++",
+            LanguageType::CppHeader => "C++ Header",
+            LanguageType::CSharp => "C#",
+            LanguageType::Css => "CSS",
+            LanguageType::Dockerfile => "Dockerfile",
+            LanguageType::Go => "Go",
+            LanguageType::Hcl => "HCL",
+            LanguageType::Html => "HTML",
+            LanguageType::Java => "Java",
+            LanguageType::JavaScript => "JavaScript",
+            LanguageType::Json => "JSON",
+            LanguageType::Jsx => "JSX",
+            LanguageType::Kotlin => "Kotlin",
+            LanguageType::Lua => "Lua",
+            LanguageType::Makefile => "Makefile",
+            LanguageType::Markdown => "Markdown",
+            LanguageType::Php => "PHP",
+            LanguageType::Python => "Python",
+            LanguageType::Ruby => "Ruby",
+            LanguageType::Rust => "Rust",
+            LanguageType::Scala => "Scala",
+            LanguageType::Sh => "Shell",
+            LanguageType::Sql => "SQL",
+            LanguageType::Swift => "Swift",
+            LanguageType::Toml => "TOML",
+            LanguageType::Tsx => "TSX",
+            LanguageType::TypeScript => "TypeScript",
+            LanguageType::Xml => "XML",
+            LanguageType::Yaml => "YAML",
+        }
+    }
+
+    /// Lowercase extensions, and file names without one
+    fn extensions(self) -> &'static [&'static str] {
+        match self {
+            LanguageType::C => &["c"],
+            LanguageType::CHeader => &["h"],
+            LanguageType::Cpp => &["cc", "cpp", "cxx", "c++"],
+            LanguageType::CppHeader => &["hh", "hpp", "hxx", "inl"],
+            LanguageType::CSharp => &["cs", "csx"],
+            LanguageType::Css => &["css"],
+            LanguageType::Dockerfile => &["dockerfile"],
+            LanguageType::Go => &["go"],
+            LanguageType::Hcl => &["tf", "tfvars", "hcl"],
+            LanguageType::Html => &["html", "htm"],
+            LanguageType::Java => &["java"],
+            LanguageType::JavaScript => &["js", "mjs", "cjs"],
+            LanguageType::Json => &["json"],
+            LanguageType::Jsx => &["jsx"],
+            LanguageType::Kotlin => &["kt", "kts"],
+            LanguageType::Lua => &["lua"],
+            LanguageType::Makefile => &["makefile", "mak", "mk"],
+            LanguageType::Markdown => &["md", "markdown"],
+            LanguageType::Php => &["php"],
+            LanguageType::Python => &["py", "pyw"],
+            LanguageType::Ruby => &["rb", "rake", "gemfile"],
+            LanguageType::Rust => &["rs"],
+            LanguageType::Scala => &["sc", "scala"],
+            LanguageType::Sh => &["sh", "bash", "zsh"],
+            LanguageType::Sql => &["sql"],
+            LanguageType::Swift => &["swift"],
+            LanguageType::Toml => &["toml"],
+            LanguageType::Tsx => &["tsx"],
+            LanguageType::TypeScript => &["ts", "mts", "cts"],
+            LanguageType::Xml => &["xml"],
+            LanguageType::Yaml => &["yaml", "yml"],
+        }
+    }
+
+    fn syntax(self) -> Syntax {
+        match self {
+            LanguageType::Dockerfile
+            | LanguageType::Makefile
+            | LanguageType::Sh
+            | LanguageType::Toml
+            | LanguageType::Yaml => HASH,
+            LanguageType::Hcl | LanguageType::Php => Syntax {
+                line_comments: &["#", "//"],
+                ..C_LIKE
+            },
+            LanguageType::Html | LanguageType::Xml => MARKUP,
+            LanguageType::Json => Syntax {
+                line_comments: &[],
+                ..MARKUP
+            },
+            LanguageType::Lua => Syntax {
+                line_comments: &["--"],
+                block_comments: &[("--[[", "]]")],
+                ..C_LIKE
+            },
+            LanguageType::Markdown => Syntax {
+                prose: true,
+                ..MARKUP
+            },
+            LanguageType::Python => Syntax {
+                doc_quotes: &[("\"\"\"", "\"\"\""), ("'''", "'''")],
+                ..HASH
+            },
+            LanguageType::Ruby => Syntax {
+                block_comments: &[("=begin", "=end")],
+                ..HASH
+            },
+            LanguageType::Sql => Syntax {
+                line_comments: &["--"],
+                ..C_LIKE
+            },
+            _ => C_LIKE,
+        }
+    }
+
+    /// The language of a file by its name, like `Makefile`, or otherwise its extension
+    pub fn from_path<P: AsRef<Path>>(path: P, _config: &Config) -> Option<LanguageType> {
+        let path = path.as_ref();
+        let name = path.file_name()?.to_string_lossy().to_lowercase();
+        let extension = path
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase());
+        LANGUAGES.into_iter().find(|language| {
+            language
+                .extensions()
+                .iter()
+                .any(|known| *known == name || extension.as_deref() == Some(*known))
+        })
+    }
+
+    /// Count the lines of the file at `path`
+    pub fn parse(self, path: PathBuf, config: &Config) -> std::io::Result<Report> {
+        let content = std::fs::read(&path)?;
+        Ok(Report {
+            stats: self.parse_from_str(content, config),
+            name: path,
+        })
+    }
+
+    pub fn parse_from_str<A: AsRef<[u8]>>(self, text: A, _config: &Config) -> CodeStats {
+        let syntax = self.syntax();
+        let text = String::from_utf8_lossy(text.as_ref());
+        let mut stats = CodeStats::default();
+        // the end of the comment the previous line left open
+        let mut open: Option<&str> = None;
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() && open.is_none() {
+                stats.blanks += 1;
+            } else if syntax.prose || !has_code(line, &syntax, &mut open) {
+                stats.comments += 1;
+            } else {
+                stats.code += 1;
+            }
+        }
+        stats
+    }
+}
+
+/// Whether a trimmed line has code outside of comments, updating the comment left `open` at the end of the line
+fn has_code(line: &str, syntax: &Syntax, open: &mut Option<&'static str>) -> bool {
+    let mut rest = line;
+    let mut code = false;
+    loop {
+        if let Some(end) = *open {
+            let Some(index) = rest.find(end) else {
+                return code;
+            };
+            rest = rest[index + end.len()..].trim_start();
+            *open = None;
+        }
+        if rest.is_empty() {
+            return code;
+        }
+        // block comments first, as Lua's `--[[` starts like a line comment
+        let doc_quotes = if code { &[][..] } else { syntax.doc_quotes };
+        let mut starts = syntax.block_comments.iter().chain(doc_quotes);
+        if let Some((start, end)) = starts.find(|(start, _)| rest.starts_with(start)) {
+            rest = &rest[start.len()..];
+            *open = Some(end);
+            continue;
+        }
+        if syntax
+            .line_comments
+            .iter()
+            .any(|comment| rest.starts_with(comment))
+        {
+            return code;
+        }
+        code = true;
+        // a block comment opened after the code, unless it's inside a line comment
+        let line_comment = syntax
+            .line_comments
+            .iter()
+            .filter_map(|comment| find_outside_strings(rest, comment))
+            .min();
+        let Some((index, start, end)) = syntax
+            .block_comments
+            .iter()
+            .filter_map(|(start, end)| Some((find_outside_strings(rest, start)?, start, end)))
+            .min_by_key(|(index, _, _)| *index)
+            .filter(|(index, _, _)| line_comment.is_none_or(|comment| *index < comment))
+        else {
+            return code;
+        };
+        rest = &rest[index + start.len()..];
+        *open = Some(end);
+    }
+}
+
+impl fmt::Display for LanguageType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Index of `pattern` in `code`, leaving out double-quoted strings like `"src/*.rs"`
+fn find_outside_strings(code: &str, pattern: &str) -> Option<usize> {
+    let mut in_string = false;
+    let mut escaped = false;
+    for (index, c) in code.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+        } else if c == '"' {
+            in_string = true;
+        } else if code[index..].starts_with(pattern) {
+            return Some(index);
+        }
+    }
+    None
+}
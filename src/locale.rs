@@ -0,0 +1,108 @@
+use chrono::NaiveDate;
+use num_format::{Locale, ToFormattedString};
+
+use crate::i18n::Lang;
+
+/// Formats numbers and dates according to the CLDR conventions of a locale, and translates the report text
+#[derive(Clone)]
+pub struct ReportLocale {
+    numbers: Locale,
+    dates: chrono::Locale,
+    pub lang: Lang,
+}
+
+impl Default for ReportLocale {
+    fn default() -> Self {
+        ReportLocale {
+            numbers: Locale::en,
+            dates: chrono::Locale::en_US,
+            lang: Lang::En,
+        }
+    }
+}
+
+impl ReportLocale {
+    /// Accepts locales like `de`, `de-CH` or `de_CH`
+    pub fn new(name: &str) -> Result<Self, String> {
+        let name = name.replace('_', "-");
+        let (language, region) = match name.split_once('-') {
+            Some((language, region)) => (language.to_lowercase(), region.to_uppercase()),
+            None => (name.to_lowercase(), name.to_uppercase()),
+        };
+
+        let numbers = Locale::from_name(format!("{}-{}", language, region))
+            .or_else(|_| Locale::from_name(&language))
+            .map_err(|_| format!("Unknown locale: {}", name))?;
+        // chrono only knows locales with a region, default to the language's "own" region
+        let dates = chrono::Locale::try_from(format!("{}_{}", language, region).as_str())
+            .or_else(|_| {
+                chrono::Locale::try_from(
+                    format!("{}_{}", language, language.to_uppercase()).as_str(),
+                )
+            })
+            .or_else(|_| chrono::Locale::try_from(format!("{}_US", language).as_str()))
+            .map_err(|_| format!("Unknown locale: {}", name))?;
+
+        Ok(ReportLocale {
+            numbers,
+            dates,
+            lang: Lang::En,
+        })
+    }
+
+    pub fn translated(self, lang: Lang) -> Self {
+        ReportLocale { lang, ..self }
+    }
+
+    pub fn format_int(&self, n: i64) -> String {
+        n.to_formatted_string(&self.numbers)
+    }
+
+    pub fn format_float(&self, n: f64, decimals: usize) -> String {
+        let formatted = format!("{:.*}", decimals, n.abs());
+        let (int, fraction) = formatted.split_once('.').unwrap_or((&formatted, ""));
+        let int: i64 = int.parse().unwrap_or(0);
+        let sign = if n < 0.0 && formatted.chars().any(|c| c.is_ascii_digit() && c != '0') {
+            self.numbers.minus_sign()
+        } else {
+            ""
+        };
+        match fraction {
+            "" => format!("{}{}", sign, self.format_int(int)),
+            _ => format!(
+                "{}{}{}{}",
+                sign,
+                self.format_int(int),
+                self.numbers.decimal(),
+                fraction
+            ),
+        }
+    }
+
+    /// Format a ratio between 0 and 1 as a percentage
+    pub fn format_percent(&self, ratio: f64, decimals: usize) -> String {
+        format!("{}%", self.format_float(ratio * 100.0, decimals))
+    }
+
+    /// Format a number of bytes with a binary unit, e.g. `1.5 MiB`
+    pub fn format_bytes(&self, bytes: u64) -> String {
+        const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+        let mut value = bytes as f64;
+        let mut unit = 0;
+        while value >= 1024.0 && unit < UNITS.len() - 1 {
+            value /= 1024.0;
+            unit += 1;
+        }
+        let decimals = if unit == 0 { 0 } else { 1 };
+        format!("{} {}", self.format_float(value, decimals), UNITS[unit])
+    }
+
+    pub fn format_date(&self, date: NaiveDate) -> String {
+        date.format_localized("%x", self.dates).to_string()
+    }
+
+    /// Decimal and thousands separators in the format plotly expects
+    pub fn plotly_separators(&self) -> String {
+        format!("{}{}", self.numbers.decimal(), self.numbers.separator())
+    }
+}
@@ -1,186 +1,2373 @@
 use chrono::NaiveDate;
-use clap::Parser;
-use minijinja::{context, Environment, Value};
-use plotly::{
-    common::{Orientation, Title},
-    layout::{BarMode, Margin},
-    Bar, Layout, Plot,
+use clap::{Parser, Subcommand, ValueEnum};
+use minijinja::{context, Value};
+use plotly::Plot;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Component, Path, PathBuf};
+
+mod affiliations;
+mod afterhours;
+mod anomalies;
+mod anonymize;
+mod api;
+mod badges;
+mod blame;
+mod branches;
+mod bundle;
+mod cancel;
+mod checkpoint;
+mod ci;
+mod codeowners;
+mod config;
+mod contributors;
+mod datafiles;
+mod dates;
+mod defects;
+mod diff;
+mod dossier;
+mod embed;
+mod error;
+mod export;
+mod extensions;
+mod fastexport;
+mod forecast;
+mod git;
+mod heat;
+mod highlights;
+mod history;
+mod hosting;
+mod hotfixes;
+mod i18n;
+mod identities;
+mod inequality;
+mod infra;
+mod issues;
+mod languages;
+mod layout;
+#[cfg(not(feature = "tokei"))]
+mod linecount;
+mod locale;
+mod merges;
+mod metrics;
+mod milestones;
+mod newcomers;
+mod output;
+mod pairing;
+mod plots;
+mod plugins;
+mod profile;
+mod publish;
+mod recording;
+mod releases;
+mod remote;
+mod render;
+mod response;
+mod sections;
+mod sensitive;
+mod signatures;
+mod sizes;
+mod sprints;
+mod sqlite;
+mod squash;
+mod streaks;
+mod styles;
+mod submodules;
+mod teams;
+mod tenure;
+mod text;
+mod themes;
+mod timing;
+mod tone;
+
+use affiliations::{get_affiliations, plot_affiliations};
+use afterhours::{get_after_hours, plot_after_hours};
+use anomalies::get_notable_events;
+use anonymize::Anonymizer;
+use api::{get_api_releases, plot_api_changes};
+use badges::{write_badges, BadgeMetrics};
+use blame::{get_blame_ages, get_file_ages, plot_code_freshness, plot_line_age_histogram};
+use branches::{get_divergent_branches, plot_branch_ages};
+use bundle::{read_bundle, write_bundle, Bundle, Manifest};
+use cancel::{install_handler, is_cancelled, unless_cancelled};
+use checkpoint::Checkpoints;
+use ci::{
+    check_thresholds, comment_on_merge_request, get_merge_request_commits, get_range_metrics,
+    recent_activity, report_to_github_actions,
+};
+use codeowners::{get_code_owners, get_ownership_stats};
+use config::{load_config, CONFIG_FILENAME};
+use contributors::{get_contributors, write_contributor_map};
+use datafiles::get_data_files;
+use dates::{normalize_dates, DateKind, Timezone};
+use defects::{compile_fix_patterns, get_defect_stats};
+use diff::{get_divergence, plot_divergence_authors, MAX_LISTED_COMMITS};
+use dossier::get_dossiers;
+use embed::write_embeds;
+use error::Error;
+use export::{check_image_export, export_animation, export_data, export_images, export_json};
+use extensions::get_extensions;
+use fastexport::import_fast_export;
+use forecast::{get_forecasts, plot_forecasts};
+use git::{
+    detect_repository, get_commit_log, get_tags, resolve_revision, run_git, Commit, Repository,
+};
+use heat::plot_heat_frames;
+use highlights::Highlights;
+use history::{append_snapshot, latest_per_head, plot_history, read_history, Snapshot};
+use hosting::{
+    get_github_logins, get_hosting_stats, plot_issue_throughput, plot_pull_request_throughput,
+    Hosting,
+};
+use hotfixes::{compile_release_pattern, get_release_branches, plot_hotfixes};
+use i18n::Lang;
+use identities::unify_noreply_identities;
+use inequality::{get_inequality, plot_lorenz_curve};
+use infra::{get_infra, plot_infra_share};
+use issues::{get_issue_references, plot_issue_references, IssuePatterns};
+use languages::{
+    compile_test_patterns, count_lines_of_code, get_code_ratios, get_largest_files,
+    get_repo_languages, plot_code_treemap, plot_languages_per_author,
 };
-use std::process::Command;
-use std::{cmp::min, hash::Hash};
-use std::{collections::HashMap, path::PathBuf};
-use tokei::{Config, Languages};
+use layout::get_layout;
+use locale::ReportLocale;
+use merges::{get_merge_stats, plot_merge_activity};
+use metrics::METRICS;
+use milestones::{get_milestone_dates, get_milestones, plot_milestone_burn_up};
+use newcomers::{get_newcomers, plot_newcomers};
+use output::write_atomic;
+use pairing::{get_pairing, plot_pairing_sessions};
+use plots::{
+    bundled_plotly_js, count_commits_per_author, get_velocity, plot_commit_count_per_author,
+    plot_commit_history, plot_figure, render_figure, Granularity, PlotStyle,
+};
+use plugins::{run_plugins, PluginOutput};
+use profile::get_profile;
+use publish::{publish as publish_report, PublishFormat, PublishTarget};
+use recording::{record_responses, write_recording, Recording};
+use releases::{get_release_cadence, plot_release_cadence};
+use remote::{clone_remote, is_remote_url};
+use render::{load_template, render_report, render_template};
+use response::{get_first_responses, plot_first_responses};
+use sections::{Section, Sections};
+use sensitive::{compile_sensitive_patterns, get_sensitive_changes, DEFAULT_AUDIT_DAYS};
+use signatures::{get_signature_stats, get_signatures, plot_signatures};
+use sizes::{get_commit_sizes_per_author, plot_commit_size_histogram};
+use sprints::{get_sprints, plot_sprint_velocity};
+use sqlite::export_sqlite;
+use squash::squash_commits;
+use streaks::get_streaks;
+use styles::ReportStyle;
+use submodules::{get_submodule_commits, get_submodule_paths, get_submodule_stats};
+use teams::{get_team_weeks, hour_ranges, plot_team_weeks};
+use tenure::{get_tenures, plot_active_contributors, plot_tenure_timeline};
+use text::{safe, safe_path, set_ascii};
+use themes::BuiltinTheme;
+use timing::Timings;
+use tone::{get_tone, plot_tone};
 
-static TEMPLATE: &str = include_str!("../templates/index.html");
-const PLOT_WIDTH: usize = 1200;
+static SUMMARY_TEMPLATE: &str = include_str!("../templates/summary.md");
+static MERGE_REQUEST_TEMPLATE: &str = include_str!("../templates/merge_request.md");
+static DIFF_TEMPLATE: &str = include_str!("../templates/diff.html");
+/// The report is written to the working directory
+const REPORT_FILENAME: &str = "git-report.html";
+/// Report data written by `publish`, unless --export-json names another file
+const JSON_FILENAME: &str = "git-report.json";
 
 #[derive(Parser)]
-#[command(version, about, long_about = None)]
+#[command(
+    version,
+    about,
+    long_about = None,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
 struct Cli {
-    /// Path to a git repository
-    path: PathBuf,
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    report: Option<ReportArgs>,
 }
 
-#[derive(Eq, Hash, PartialEq, Debug)]
-struct Commit {
-    date: NaiveDate,
-    author: String,
+#[derive(Subcommand)]
+enum Command {
+    /// Render the HTML report from a bundle written with --export-bundle, without access to the repository
+    Import(ImportArgs),
+    /// Compare two revisions, like a release branch and main, by their commits, authors and files
+    Diff(DiffArgs),
+    /// Generate the report and publish it to the target in the [publish] section of the config, like a directory,
+    /// the gh-pages branch or an S3 bucket
+    Publish(Box<PublishArgs>),
 }
 
-fn get_repo_languages(repo_path: &PathBuf) -> Vec<(tokei::LanguageType, tokei::Language)> {
-    let mut languages = Languages::new();
-    let paths = &[repo_path];
+#[derive(clap::Args)]
+struct ImportArgs {
+    /// Bundle to render
+    bundle: PathBuf,
 
-    // Exclude any path that contains any of these strings.
-    let excluded = &[];
+    /// Render the report with this minijinja template instead of the built-in one
+    #[arg(long)]
+    template: Option<PathBuf>,
 
-    // `Config` allows you to configure what is searched and counted.
-    let config = Config::default();
+    /// Built-in report template, each shows a different selection of sections
+    #[arg(long, value_enum, default_value_t = ReportStyle::Full, conflicts_with = "template")]
+    style: ReportStyle,
 
-    languages.get_statistics(paths, excluded, &config);
+    /// Embed plotly.js in the report, so it renders without network access
+    #[arg(long)]
+    offline: bool,
 
-    // sort languages by total number of lines
-    let mut languages = languages.into_iter().collect::<Vec<_>>();
-    languages.sort_by(|a, b| b.1.lines().cmp(&a.1.lines()));
-    languages
+    /// Locale used to format numbers and dates, defaults to the locale of the bundle
+    #[arg(long)]
+    locale: Option<String>,
 }
 
-fn get_commit_log(path: &PathBuf) -> Vec<Commit> {
-    let output = Command::new("git")
-        .arg("log")
-        .arg("--format=%as,%cn")
-        .current_dir(path)
-        .output()
-        .expect("Failed to execute git command");
-    let result = String::from_utf8(output.stdout).expect("Unable to parse git command output");
+#[derive(clap::Args)]
+struct DiffArgs {
+    /// First revision, like a branch, tag or commit
+    a: String,
 
-    // split the results into a vec of tuples
-    result
-        .lines()
-        .map(|line| {
-            let parts: Vec<&str> = line.split(',').collect();
-            Commit {
-                date: NaiveDate::parse_from_str(parts[0], "%Y-%m-%d")
-                    .expect("Could not parse value as a date"),
-                author: parts[1].to_string(),
-            }
-        })
-        .collect()
+    /// Second revision, compared to the first
+    b: String,
+
+    /// Path to the git repository
+    #[arg(long, default_value = ".")]
+    path: PathBuf,
+
+    /// Path to a config file, defaults to git-report.toml in the repository
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// Number of authors and files to show
+    #[arg(long, default_value_t = 10)]
+    top_authors: usize,
+
+    /// Color scheme of the report, overrides the `base` theme of the config
+    #[arg(long, value_enum)]
+    theme: Option<BuiltinTheme>,
+
+    /// Embed plotly.js in the report, so it renders without network access
+    #[arg(long)]
+    offline: bool,
+
+    /// Locale used to format numbers and dates, e.g. "de" or "en-GB", defaults to the language of the report
+    #[arg(long)]
+    locale: Option<String>,
+
+    /// Language of the titles, labels and summaries of the report
+    #[arg(long, value_enum, default_value_t = Lang::En)]
+    lang: Lang,
+
+    /// Restrict terminal output and filenames to plain ASCII, without emoji
+    #[arg(long)]
+    ascii: bool,
+}
+
+#[derive(clap::Args)]
+struct PublishArgs {
+    /// Where to publish the report, overrides the `target` of the config
+    #[arg(long, value_enum)]
+    to: Option<PublishTarget>,
+
+    #[command(flatten)]
+    report: ReportArgs,
 }
 
-fn plot_commit_history(commits: &Vec<Commit>) -> Plot {
-    let mut plot = Plot::new();
+#[derive(clap::Args, Clone)]
+struct ReportArgs {
+    /// Path to a git repository, or the URL of one to clone into a temporary directory
+    #[arg(
+        required_unless_present_any = ["from_fast_export", "replay"],
+        default_value = ".",
+        hide_default_value = true
+    )]
+    path: PathBuf,
+
+    /// Report on a history recorded with `git fast-export`, without access to the repository
+    #[arg(long, value_name = "FILE", conflicts_with = "path")]
+    from_fast_export: Option<PathBuf>,
+
+    /// Record the inputs of the report into this directory: the history, the config, the line counts and the API
+    /// responses, so --replay makes the same report without the repository
+    #[arg(long, value_name = "DIR")]
+    record: Option<PathBuf>,
+
+    /// Make the report from a directory written with --record, without access to the repository or the APIs
+    #[arg(
+        long,
+        value_name = "DIR",
+        conflicts_with_all = ["path", "from_fast_export", "record"]
+    )]
+    replay: Option<PathBuf>,
+
+    /// Clone only the latest commits of a remote repository
+    #[arg(long, value_name = "COMMITS")]
+    clone_depth: Option<u32>,
+
+    /// Clone a remote repository without file contents, git downloads them when they are needed
+    #[arg(long)]
+    partial_clone: bool,
+
+    /// Path to a config file, defaults to git-report.toml in the repository
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// Restrict the report to a directory of the repository, like a package of a monorepo: the history, churn,
+    /// languages and files. Repeat it to report on several directories together
+    #[arg(long, value_name = "DIR")]
+    path_scope: Vec<String>,
+
+    /// Write a report per --path-scope instead, named after the directory like `git-report-services-api.html`
+    #[arg(long, requires = "path_scope")]
+    report_per_scope: bool,
+
+    /// Number of authors to show individually, the rest is grouped as "Other"
+    #[arg(long, default_value_t = 10)]
+    top_authors: usize,
+
+    /// Size of the time buckets in the activity plot
+    #[arg(long, value_enum, default_value_t = Granularity::Month)]
+    granularity: Granularity,
+
+    /// Time zone commits are assigned to days in: utc, local (follows $TZ), author (the time zone each commit was
+    /// made in) or an offset like +02:00
+    #[arg(long, default_value = "utc")]
+    timezone: String,
+
+    /// Timestamp that dates the commits
+    #[arg(long, value_enum, default_value_t = DateKind::Author)]
+    date: DateKind,
+
+    /// Credit given to Co-authored-by trailers, relative to the commit author
+    #[arg(long, default_value_t = 1.0)]
+    co_author_weight: f64,
+
+    /// Glob matching the names of release branches
+    #[arg(long, default_value = "release*")]
+    release_branches: String,
+
+    /// Add a breakdown of commits per email domain, or per organization as mapped in the config
+    #[arg(long)]
+    affiliations: bool,
+
+    /// Chart a typical week of commits per team side by side, by weekday and hour in UTC, to find hours the teams
+    /// share. Teams are the [teams] of the config, or the organizations of the authors without it
+    #[arg(long)]
+    team_weeks: bool,
+
+    /// GitHub repository (owner/repo) to fetch pull request, issue and profile data from, uses $GITHUB_TOKEN
+    #[cfg_attr(
+        feature = "hosting",
+        arg(long, value_name = "OWNER/REPO", conflicts_with = "gitlab")
+    )]
+    #[cfg_attr(not(feature = "hosting"), arg(skip))]
+    github: Option<String>,
+
+    /// GitLab project (group/project) to fetch merge request, issue and avatar data from, uses $GITLAB_TOKEN
+    #[cfg_attr(feature = "hosting", arg(long, value_name = "GROUP/PROJECT"))]
+    #[cfg_attr(not(feature = "hosting"), arg(skip))]
+    gitlab: Option<String>,
+
+    /// Base URL of the GitLab instance
+    #[cfg_attr(feature = "hosting", arg(long, default_value = "https://gitlab.com"))]
+    #[cfg_attr(not(feature = "hosting"), arg(skip = String::from("https://gitlab.com")))]
+    gitlab_url: String,
+
+    /// Also write every chart as PNG and SVG into this directory
+    #[arg(long)]
+    export_images: Option<PathBuf>,
+
+    /// Also write the data behind every chart as a CSV file into this directory
+    #[arg(long, value_name = "DIR")]
+    export_data: Option<PathBuf>,
+
+    /// Also write every author and email with their commits, first and last commit dates and most changed files
+    /// as JSON, for supply-chain reviews
+    #[arg(long, value_name = "PATH")]
+    export_contributors: Option<PathBuf>,
+
+    /// Also write every chart as a standalone page into this directory, with an iframe and a script snippet to
+    /// embed each of them listed in embed.html
+    #[arg(long, value_name = "DIR")]
+    embed_snippet: Option<PathBuf>,
+
+    /// URL the pages of --embed-snippet will be hosted at, for the iframe snippets
+    #[arg(long, value_name = "URL", requires = "embed_snippet")]
+    embed_url: Option<String>,
+
+    /// Also write the commits, authors, file changes and languages into a new SQLite database, for querying with
+    /// SQL, uses sqlite3
+    #[arg(long, value_name = "PATH")]
+    export_sqlite: Option<PathBuf>,
+
+    /// Append a snapshot of the headline metrics to this file on every run, and chart the metrics of all snapshots
+    /// in the report
+    #[arg(long, value_name = "PATH")]
+    history_file: Option<PathBuf>,
+
+    /// Also write SVG badges for the commits, contributors, bus factor, lines of code and main language into this
+    /// directory, for embedding in a README
+    #[arg(long, value_name = "DIR")]
+    badges: Option<PathBuf>,
+
+    /// Also write the report data, including plot figures and section timings, as JSON
+    #[arg(long)]
+    export_json: Option<PathBuf>,
+
+    /// Also write the report as a bundle, which `git-report import` renders without access to the repository
+    #[arg(long, value_name = "PATH")]
+    export_bundle: Option<PathBuf>,
+
+    /// Export an animated GIF or WebM of where in the codebase lines changed over time, uses ffmpeg
+    #[arg(long)]
+    export_animation: Option<PathBuf>,
+
+    /// Scale factor for exported images
+    #[arg(long, default_value_t = 2.0)]
+    scale: f64,
+
+    /// Restrict terminal output and filenames to plain ASCII, without emoji
+    #[arg(long)]
+    ascii: bool,
+
+    /// Run the plugin commands of the config, which is left out by default as the config can come from the
+    /// repository
+    #[arg(long)]
+    allow_plugins: bool,
+
+    /// Replace author names and emails with pseudonyms like "Author 1", to share the report outside the team
+    #[arg(long)]
+    anonymize: bool,
+
+    /// Write the names and emails behind each pseudonym to this JSON file
+    #[arg(long, value_name = "PATH", requires = "anonymize")]
+    anonymize_map: Option<PathBuf>,
+
+    /// Embed plotly.js in the report, so it renders without network access
+    #[arg(long)]
+    offline: bool,
+
+    /// Checkpoint the results of expensive stages in the git directory, so an interrupted run can continue with
+    /// --resume
+    #[arg(long)]
+    checkpoint: bool,
+
+    /// Continue a run interrupted with --checkpoint or --resume, reusing the results of the stages it completed
+    #[arg(long)]
+    resume: bool,
+
+    /// Print the time and memory spent per report section
+    #[arg(short, long)]
+    verbose: bool,
+
+    /// Only compute and render these sections, e.g. "activity,authors,languages"
+    #[arg(long, value_enum, value_delimiter = ',')]
+    sections: Option<Vec<Section>>,
+
+    /// Leave out these sections, e.g. "languages,blame-age"
+    #[arg(long, value_enum, value_delimiter = ',')]
+    skip: Vec<Section>,
+
+    /// Keep the changes made to a file before it was renamed under its old name, instead of its latest name
+    #[arg(long)]
+    no_follow_renames: bool,
+
+    /// Include the history and code of checked out submodules, with a section per submodule
+    #[arg(long)]
+    recurse_submodules: bool,
+
+    /// Blame every file to show how old the surviving lines are, this is slow on large repositories
+    #[arg(long)]
+    blame_age: bool,
+
+    /// Also count commits as logical changes, collapsing stacks of commits by the same author made in quick succession
+    #[arg(long)]
+    squash: bool,
 
-    // do a groupby count per date and author for the commits
-    // count commits per author using plain vec methods
-    let mut count: HashMap<String, HashMap<String, i32>> = HashMap::new();
-    for commit in commits {
-        *count
-            .entry(commit.author.clone())
-            .or_default()
-            .entry(commit.date.clone().format("%Y-%m").to_string())
-            .or_insert(0) += 1;
+    /// Commits of an author less than this many minutes apart are collapsed into one change with --squash
+    #[arg(long, value_name = "MINUTES", default_value_t = 60)]
+    squash_window: u32,
+
+    /// List the changes to sensitive paths since this date, defaults to 90 days before the latest commit
+    #[arg(long, value_name = "DATE")]
+    audit_since: Option<NaiveDate>,
+
+    /// Show the share of commits made on weekends and outside the working hours of the config, per month and author.
+    /// Left out by default, as it shows when people work
+    #[arg(long)]
+    after_hours: bool,
+
+    /// Chart how many commit messages sound frustrated, like "ugh" or "hacky", per quarter and top-level directory.
+    /// Left out by default, it's light-hearted and not a measure of anyone's work
+    #[arg(long)]
+    tone: bool,
+
+    /// Show how many commits are signed with GPG or SSH keys, verifying every signature is slow on large repositories
+    #[arg(long)]
+    signatures: bool,
+
+    /// Count the public items of Rust library crates at every tag, parsing the source of each release
+    #[arg(long)]
+    api_surface: bool,
+
+    /// Write a job summary, annotate threshold violations and output the report path when running in GitHub Actions
+    #[arg(long)]
+    github_actions: bool,
+
+    /// Comment on the current merge request with its metrics compared to the target branch, when running in
+    /// a GitLab merge request pipeline
+    #[cfg_attr(
+        feature = "hosting",
+        arg(long, conflicts_with_all = ["github_actions", "replay"])
+    )]
+    #[cfg_attr(not(feature = "hosting"), arg(skip))]
+    gitlab_ci: bool,
+
+    /// Always run the full analysis, even on repositories exceeding the configured limits
+    #[arg(long)]
+    full: bool,
+
+    /// Set by `publish`, which publishes the report instead of opening it
+    #[arg(skip)]
+    publishing: bool,
+
+    /// Color scheme of the report, overrides the `base` theme of the config
+    #[arg(long, value_enum)]
+    theme: Option<BuiltinTheme>,
+
+    /// Fixed width of full width plots in pixels, narrower plots are scaled along. By default plots resize with
+    /// the page
+    #[arg(long, value_name = "PIXELS")]
+    plot_width: Option<usize>,
+
+    /// Height of plots in pixels, taller plots are scaled along
+    #[arg(long, value_name = "PIXELS")]
+    plot_height: Option<usize>,
+
+    /// Render the report with this minijinja template instead of the built-in one, see --export-json for the
+    /// data available to it
+    #[arg(long)]
+    template: Option<PathBuf>,
+
+    /// Built-in report template, each shows a different selection of sections
+    #[arg(long, value_enum, default_value_t = ReportStyle::Full, conflicts_with = "template")]
+    style: ReportStyle,
+
+    /// Locale used to format numbers and dates, e.g. "de" or "en-GB", defaults to the language of the report
+    #[arg(long)]
+    locale: Option<String>,
+
+    /// Language of the titles, labels and summaries of the report
+    #[arg(long, value_enum, default_value_t = Lang::En)]
+    lang: Lang,
+}
+
+fn write_report(path: &Path, html: String) -> Result<(), Error> {
+    write_atomic(path, html).map_err(|source| Error::Write {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// Open the written report in the browser
+#[cfg(feature = "browser")]
+fn open_report(path: &Path) {
+    if webbrowser::open(&path.to_string_lossy()).is_ok() {
+        println!("Done!");
     }
+}
 
-    // let mut count_vec: Vec<(String, Vec<NaiveDate>)> = count.into_iter().collect();
-    for (author, counts) in count.into_iter() {
-        let x: Vec<String> = counts.clone().keys().map(|x| x.to_string()).collect();
-        let y: Vec<i32> = counts.clone().values().map(|x| x.to_owned()).collect();
-        let trace = Bar::new(x, y).name(author);
-        plot.add_trace(trace);
+#[cfg(not(feature = "browser"))]
+fn open_report(path: &Path) {
+    println!("Done! The report is at {}", path.display());
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let result = match (cli.command, cli.report) {
+        (Some(Command::Import(args)), _) => import(args),
+        (Some(Command::Diff(args)), _) => diff(args),
+        (Some(Command::Publish(args)), _) => publish(args),
+        (None, Some(args)) => report(args),
+        // clap requires the path of the repository when there is no subcommand
+        (None, None) => unreachable!(),
+    };
+    if let Err(err) = result {
+        err.exit();
     }
+}
 
-    let layout = Layout::new()
-        .width(PLOT_WIDTH - 50) // make the legend fit in the containing div
-        .bar_mode(BarMode::Stack)
-        // .x_axis(Axis::new().range(date_range))
-        .title(Title::from("Commit activity per author"));
-    plot.set_layout(layout);
+/// Render the report from a bundle
+fn import(args: ImportArgs) -> Result<(), Error> {
+    install_handler();
+    let bundle = read_bundle(&args.bundle).map_err(|err| {
+        Error::Input(format!(
+            "Could not read bundle {}: {}",
+            safe_path(&args.bundle),
+            err
+        ))
+    })?;
+    let lang = bundle.report["lang"]
+        .as_str()
+        .and_then(|code| Lang::from_str(code, true).ok())
+        .unwrap_or_default();
+    let locale = ReportLocale::new(args.locale.as_ref().unwrap_or(&bundle.manifest.locale))
+        .map_err(Error::Input)?
+        .translated(lang);
+    let highlights = Highlights::new(&bundle.manifest.highlights).map_err(Error::Input)?;
+    set_ascii(bundle.report["ascii"].as_bool().unwrap_or(false));
 
-    plot
+    let template = load_template(args.template.as_ref(), args.style)?;
+    let report_file = Path::new(REPORT_FILENAME);
+    write_report(
+        report_file,
+        render_report(
+            &template,
+            &Value::from_serialize(&bundle.report),
+            &bundle.figures,
+            args.offline,
+            locale,
+            &highlights,
+        )?,
+    )?;
+    open_report(report_file);
+    Ok(())
 }
 
-fn plot_commit_count_per_author(commits: &Vec<Commit>, n: usize) -> Plot {
-    let mut plot = Plot::new();
+/// Generate the report and publish the files of the formats in the config
+fn publish(mut args: Box<PublishArgs>) -> Result<(), Error> {
+    let cli = &mut args.report;
+    let config = load_config(cli.config.as_ref(), &cli.path)?;
+    let target = args.to.unwrap_or(config.publish.target);
+    if cli.report_per_scope && cli.path_scope.len() > 1 {
+        return Err(Error::Input(
+            "Publishing a report per scope isn't supported, publish each --path-scope with its own prefix"
+                .to_string(),
+        ));
+    }
+    if target == PublishTarget::GhPages
+        && (cli.from_fast_export.is_some() || is_remote_url(&cli.path.to_string_lossy()))
+    {
+        return Err(Error::Input(
+            "Publishing to a branch needs the path of a local repository".to_string(),
+        ));
+    }
+    let repository = cli.path.clone();
+    let files: Vec<(PublishFormat, PathBuf)> = config
+        .publish
+        .formats
+        .iter()
+        .map(|format| match format {
+            PublishFormat::Html => (*format, cli.report_file()),
+            PublishFormat::Json => (
+                *format,
+                cli.export_json
+                    .get_or_insert_with(|| PathBuf::from(JSON_FILENAME))
+                    .clone(),
+            ),
+        })
+        .collect();
+    cli.publishing = true;
+
+    report(args.report).map_err(|err| match err {
+        Error::Cancelled(_) => Error::Cancelled(
+            "The run was cancelled, the partial report is not published".to_string(),
+        ),
+        err => err,
+    })?;
+    let location = publish_report(&files, target, &config.publish, &repository)?;
+    println!("Published the report to {}", location);
+    Ok(())
+}
 
-    // count commits per author using plain vec methods
-    let mut count: HashMap<String, u32> = HashMap::new();
-    for commit in commits {
-        *count.entry(commit.author.clone()).or_insert(0) += 1;
+/// Render the comparison of two revisions
+fn diff(args: DiffArgs) -> Result<(), Error> {
+    set_ascii(args.ascii);
+    install_handler();
+    if !args.path.exists() {
+        return Err(Error::Input(format!(
+            "Path does not exist: {}",
+            safe_path(&args.path)
+        )));
     }
+    detect_repository(&args.path)?;
+    let a = resolve_revision(&args.path, &args.a)?;
+    let b = resolve_revision(&args.path, &args.b)?;
 
-    // sort counts
-    let mut count_vec: Vec<(String, u32)> = count.into_iter().collect();
-    count_vec.sort_by_key(|&(_, count)| count);
+    let config = load_config(args.config.as_ref(), &args.path)?;
+    let locale = ReportLocale::new(args.locale.as_deref().unwrap_or(args.lang.locale()))
+        .map_err(Error::Input)?
+        .translated(args.lang);
+    let highlights = Highlights::new(&config.highlights).map_err(Error::Input)?;
+    let theme = args.theme.unwrap_or(config.theme.base);
+    let style = PlotStyle::new(&config.theme, theme, &locale);
 
-    // get top n items
-    let tail: usize = count_vec.len() - min(count_vec.len(), n);
-    let top_n = count_vec[tail..].to_vec();
+    let divergence = get_divergence(&args.path, &a, &b, args.top_authors)?;
+    let only_on = |revision: &str| {
+        args.lang
+            .translate("Only on {name}")
+            .replace("{name}", revision)
+    };
+    let plot = plot_divergence_authors(
+        &divergence,
+        [only_on(&args.a), only_on(&args.b)],
+        args.top_authors,
+    );
+    let figures = serde_json::Map::from_iter([(
+        "divergence_authors".to_string(),
+        plot_figure(&plot, &style),
+    )]);
+
+    let commits = |commits: &[Commit]| {
+        commits
+            .iter()
+            .take(MAX_LISTED_COMMITS)
+            .map(|commit| {
+                context! {
+                    hash => commit.hash,
+                    date => commit.date,
+                    author => commit.author,
+                    subject => commit.message.lines().next().unwrap_or_default(),
+                }
+            })
+            .collect::<Vec<_>>()
+    };
+    let ctx = context! {
+        path => args.path.to_string_lossy(),
+        lang => args.lang.code(),
+        ascii => args.ascii,
+        accent => config.theme.accent(),
+        theme => theme.page_colors(),
+        metrics => BTreeMap::from(METRICS),
+        diff => context! {
+            a => args.a,
+            b => args.b,
+            merge_base => divergence.merge_base,
+            only_a => divergence.only_a.len(),
+            only_b => divergence.only_b.len(),
+            commits_a => commits(&divergence.only_a),
+            commits_b => commits(&divergence.only_b),
+            authors => divergence.authors.iter().map(|author| context! {
+                author => author.author,
+                a => author.a,
+                b => author.b,
+            }).collect::<Vec<_>>(),
+            changed_files => divergence.changed_files,
+            lines_added => divergence.lines_added,
+            lines_deleted => divergence.lines_deleted,
+            files => divergence.files.iter().map(|file| context! {
+                path => file.path,
+                lines_added => file.lines_added,
+                lines_deleted => file.lines_deleted,
+            }).collect::<Vec<_>>(),
+            languages => divergence.languages.iter().map(|language| context! {
+                language => language.language,
+                code => language.code,
+                comments => language.comments,
+                blanks => language.blanks,
+            }).collect::<Vec<_>>(),
+        },
+    };
+
+    let template = ("diff.html".to_string(), DIFF_TEMPLATE.to_string());
+    let report_file = Path::new(REPORT_FILENAME);
+    write_report(
+        report_file,
+        render_report(&template, &ctx, &figures, args.offline, locale, &highlights)?,
+    )?;
+    open_report(report_file);
+    Ok(())
+}
+
+impl ReportArgs {
+    /// The sections of --sections or the style, opt-in sections are turned on by --sections or the style
+    fn sections(&self) -> Sections {
+        Sections::new(
+            self.sections.clone().or_else(|| self.style.sections()),
+            self.sections
+                .clone()
+                .unwrap_or_else(|| self.style.opt_in_sections()),
+            self.skip.clone(),
+        )
+    }
 
-    let y: Vec<String> = top_n
-        .clone()
+    /// The file the report is written to, named after the scope with --report-per-scope
+    fn report_file(&self) -> PathBuf {
+        match (self.report_per_scope, self.path_scope.as_slice()) {
+            (true, [scope]) => PathBuf::from(format!("git-report-{}.html", scope_name(scope))),
+            _ => PathBuf::from(REPORT_FILENAME),
+        }
+    }
+
+    /// The outputs of a report per scope, suffixed with the scope so the reports don't overwrite each other's
+    fn scope_outputs(&mut self, scope: &str) {
+        let name = scope_name(scope);
+        for path in [
+            &mut self.record,
+            &mut self.export_images,
+            &mut self.export_data,
+            &mut self.export_contributors,
+            &mut self.embed_snippet,
+            &mut self.export_sqlite,
+            &mut self.history_file,
+            &mut self.badges,
+            &mut self.export_json,
+            &mut self.export_bundle,
+            &mut self.export_animation,
+            &mut self.anonymize_map,
+        ]
         .into_iter()
-        .map(|(author, _)| author)
+        .flatten()
+        {
+            *path = scoped_path(path, &name);
+        }
+    }
+}
+
+/// A scope as part of a filename, like `services-api` for `services/api`
+fn scope_name(scope: &str) -> String {
+    let name: String = safe(scope)
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
         .collect();
-    let x: Vec<u32> = top_n.clone().into_iter().map(|(_, count)| count).collect();
+    name.trim_matches('-').to_string()
+}
 
-    let trace = Bar::new(x, y).orientation(Orientation::Horizontal);
-    plot.add_trace(trace);
-    let layout = Layout::new()
-        .width(PLOT_WIDTH / 2)
-        .title(Title::from("Commits per author"))
-        .margin(Margin::new().left(200).right(200));
-    plot.set_layout(layout);
+/// The path with the name of a scope added to the file or directory name, before the extension
+fn scoped_path(path: &Path, name: &str) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let filename = match path.extension() {
+        Some(extension) => format!("{}-{}.{}", stem, name, extension.to_string_lossy()),
+        None => format!("{}-{}", stem, name),
+    };
+    path.with_file_name(filename)
+}
 
-    plot
+/// A --path-scope relative to the root of the repository, without `./` and trailing slashes
+fn normalize_scope(scope: &str) -> Result<String, Error> {
+    let normalized = scope.trim_start_matches("./").trim_end_matches('/');
+    let path = Path::new(normalized);
+    if normalized.is_empty()
+        || normalized == "."
+        || path.is_absolute()
+        || path.components().any(|part| part == Component::ParentDir)
+    {
+        return Err(Error::Input(format!(
+            "Invalid --path-scope {:?}, expected a directory relative to the root of the repository",
+            scope
+        )));
+    }
+    Ok(normalized.to_string())
 }
 
-fn render_template(ctx: Value) -> String {
-    let mut env = Environment::new();
-    env.add_template("index.html", TEMPLATE).unwrap();
-    let template = env.get_template("index.html").unwrap();
+/// The result of a section, or `None` with a warning when git failed while reading it
+fn skip_failed<T>(section: &str, result: Result<T, Error>) -> Option<T> {
+    match result {
+        Ok(value) => Some(value),
+        Err(err) => {
+            eprintln!("Warning: Skipping {}: {}", section, err);
+            None
+        }
+    }
+}
 
-    template.render(ctx).unwrap()
+/// Write a report per --path-scope, one after the other
+fn report_per_scope(cli: ReportArgs) -> Result<(), Error> {
+    for scope in &cli.path_scope {
+        let mut scoped = ReportArgs {
+            path_scope: vec![scope.clone()],
+            ..cli.clone()
+        };
+        scoped.scope_outputs(scope);
+        analyse(scoped)?;
+        if is_cancelled() {
+            break;
+        }
+    }
+    Ok(())
 }
 
-fn main() {
-    let cli = Cli::parse();
+/// Write the report, a run cancelled with Ctrl-C fails after writing the partial report
+fn report(cli: ReportArgs) -> Result<(), Error> {
+    analyse(cli)?;
+    if is_cancelled() {
+        return Err(Error::Cancelled(
+            "The run was cancelled, the report is partial".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Analyse the repository and render the report
+fn analyse(mut cli: ReportArgs) -> Result<(), Error> {
+    set_ascii(cli.ascii);
+    cli.path_scope = cli
+        .path_scope
+        .iter()
+        .map(|scope| normalize_scope(scope))
+        .collect::<Result<_, _>>()?;
+    if cli.report_per_scope && cli.path_scope.len() > 1 {
+        return report_per_scope(cli);
+    }
+    // checkpoints are kept in the repository, and temporary clones are removed at the end of the run
+    if (cli.checkpoint || cli.resume)
+        && (cli.replay.is_some()
+            || cli.from_fast_export.is_some()
+            || is_remote_url(&cli.path.to_string_lossy()))
+    {
+        return Err(Error::Input(
+            "--checkpoint and --resume need the path of a local repository, the checkpoints of a temporary clone are removed with it"
+                .to_string(),
+        ));
+    }
+
+    // the signal handler removes the clone when the run is aborted, so install it before cloning
+    install_handler();
+    let recording = cli.replay.as_deref().map(Recording::open).transpose()?;
+    if cli.record.is_some() {
+        record_responses();
+    }
+    let mut clone = match (&recording, &cli.from_fast_export) {
+        (Some(recording), _) => Some(import_fast_export(
+            &recording.history(),
+            Some(recording.manifest.head()),
+        )),
+        (None, Some(file)) => Some(import_fast_export(file, None)),
+        (None, None) => is_remote_url(&cli.path.to_string_lossy()).then(|| {
+            clone_remote(
+                &cli.path.to_string_lossy(),
+                cli.clone_depth,
+                cli.partial_clone,
+            )
+        }),
+    }
+    .transpose()
+    .map_err(Error::Git)?;
+    if let Some(clone) = &clone {
+        cli.path = clone.path.clone();
+    }
+    // a replay reports on the recorded repository, with its hosting and config unless others are passed
+    if let (Some(clone), Some(recording)) = (&mut clone, &recording) {
+        let manifest = &recording.manifest;
+        clone.url = manifest.path.clone();
+        if cli.github.is_none() && cli.gitlab.is_none() {
+            cli.github = manifest.github.clone();
+            cli.gitlab = manifest.gitlab.clone();
+            if let Some(gitlab_url) = &manifest.gitlab_url {
+                cli.gitlab_url = gitlab_url.clone();
+            }
+        }
+        if cli.config.is_none() {
+            cli.config = recording.config();
+        }
+    }
 
     // Check if path exists, if not, error
     if !cli.path.exists() {
-        eprintln!("Error: Path does not exist: {:?}", cli.path);
-        std::process::exit(1);
+        return Err(Error::Input(format!(
+            "Path does not exist: {}",
+            safe_path(&cli.path)
+        )));
+    }
+
+    let repository = detect_repository(&cli.path)?;
+    for scope in &cli.path_scope {
+        if run_git(&cli.path, &["log", "-1", "--format=%H", "--", scope])?.is_empty() {
+            return Err(Error::Input(format!(
+                "No commit changes files in the --path-scope {:?}",
+                scope
+            )));
+        }
+    }
+
+    let config = load_config(cli.config.as_ref(), &cli.path)?;
+    let locale_name = cli.locale.as_deref().unwrap_or(cli.lang.locale());
+    let locale = ReportLocale::new(locale_name)
+        .map_err(Error::Input)?
+        .translated(cli.lang);
+    let theme = cli.theme.unwrap_or(config.theme.base);
+    let style =
+        PlotStyle::new(&config.theme, theme, &locale).sized(cli.plot_width, cli.plot_height);
+    let template = load_template(cli.template.as_ref(), cli.style)?;
+    let layout = get_layout(&config.layout.rows).map_err(Error::Input)?;
+    let highlights = Highlights::new(&config.highlights).map_err(Error::Input)?;
+    let issue_patterns = IssuePatterns::new(&config.issues).map_err(Error::Input)?;
+    let fix_patterns = compile_fix_patterns(&config.fixes.patterns).map_err(Error::Input)?;
+    let test_patterns = compile_test_patterns(&config.tests.patterns).map_err(Error::Input)?;
+    let sensitive_patterns =
+        compile_sensitive_patterns(&config.sensitive.patterns).map_err(Error::Input)?;
+    let release_pattern = compile_release_pattern(&cli.release_branches).map_err(Error::Input)?;
+    let timezone = Timezone::parse(&cli.timezone).map_err(Error::Input)?;
+    let working_hours = &config.working_hours;
+    if working_hours.start >= working_hours.end || working_hours.end > 24 {
+        return Err(Error::Input(format!(
+            "Working hours must start before they end, within 0 to 24, got {} to {}",
+            working_hours.start, working_hours.end
+        )));
+    }
+    if cli.export_images.is_some() {
+        check_image_export()?;
+    }
+
+    // fail early when not running in a merge request pipeline
+    let merge_request = cli
+        .gitlab_ci
+        .then(|| get_merge_request_commits(&cli.path))
+        .transpose()
+        .map_err(Error::Service)?;
+
+    let profile = get_profile(&cli.path, &config.limits, cli.full)?;
+    for reason in &profile.degraded {
+        eprintln!("Warning: Running in degraded mode, {}", reason);
     }
 
-    // check if path is a valid git repository
-    if !cli.path.join(".git").exists() {
+    let sections = cli.sections();
+    if repository == Repository::Bare {
         eprintln!(
-            "Error: Path is not a git repository. Expected a '.git' directory at {:?}/.git",
-            cli.path
+            "Warning: Skipping languages and code owners, the bare repository has no working tree to read them from"
+        );
+    }
+
+    let mut timings = Timings::default();
+    let checkpoints = Checkpoints::new(&cli.path, &cli.path_scope, cli.checkpoint, cli.resume)?;
+    let mut plots: Vec<(&str, Plot)> = vec![];
+
+    let submodules = match cli.recurse_submodules {
+        true => get_submodule_paths(&cli.path)?,
+        false => vec![],
+    };
+
+    let mut commits = timings.time("history", || {
+        let follow_renames = !cli.no_follow_renames;
+        let stage = match (profile.with_files(), follow_renames) {
+            (true, true) => "history-renames",
+            (true, false) => "history-files",
+            (false, _) => "history",
+        };
+        let stage = match cli.recurse_submodules {
+            true => format!("{}-submodules", stage),
+            false => stage.to_string(),
+        };
+        checkpoints.try_stage(&stage, || {
+            let mut commits = get_commit_log(
+                &cli.path,
+                profile.with_files(),
+                follow_renames,
+                &cli.path_scope,
+            )?;
+            if !submodules.is_empty() {
+                commits.extend(get_submodule_commits(
+                    &cli.path,
+                    &submodules,
+                    profile.with_files(),
+                    follow_renames,
+                    &cli.path_scope,
+                )?);
+                // newest first, like the history of a single repository
+                commits.sort_by_key(|commit| std::cmp::Reverse(commit.date));
+            }
+            Ok::<_, Error>(commits)
+        })
+    })?;
+    normalize_dates(&mut commits, cli.date, timezone);
+    // GitHub links the emails of the history to logins, which unifies noreply addresses with emails that don't
+    // contain the login
+    let logins = match &cli.github {
+        Some(repo) if sections.enabled(Section::Hosting) => timings
+            .time("logins", || get_github_logins(repo))
+            .unwrap_or_else(|err| {
+                eprintln!("Warning: Skipping the GitHub login lookup: {}", err);
+                HashMap::new()
+            }),
+        _ => HashMap::new(),
+    };
+    unify_noreply_identities(&mut commits, &logins);
+    let anonymizer = cli
+        .anonymize
+        .then(|| Anonymizer::new(&commits, &config.affiliations));
+    if let Some(anonymizer) = &anonymizer {
+        anonymizer.anonymize_commits(&mut commits);
+        if let Some(path) = &cli.anonymize_map {
+            anonymizer
+                .write_mapping(path)
+                .map_err(|source| Error::Write {
+                    path: path.clone(),
+                    source,
+                })?;
+        }
+    }
+    if commits.is_empty() {
+        checkpoints.clear();
+        println!(
+            "The repository at {} has no commits yet, there is nothing to report.",
+            safe_path(&cli.path)
         );
-        std::process::exit(1);
+        return Ok(());
+    }
+
+    let velocity = sections.run(Section::Activity, || {
+        unless_cancelled(|| {
+            timings.time("activity", || {
+                plots.push((
+                    "activity",
+                    plot_commit_history(
+                        &commits,
+                        cli.top_authors,
+                        cli.co_author_weight,
+                        profile.granularity(cli.granularity),
+                    ),
+                ));
+                get_velocity(&commits)
+            })
+        })
+    });
+
+    let inequality = sections.run(Section::Authors, || {
+        unless_cancelled(|| {
+            timings.time("authors", || {
+                plots.push((
+                    "commits_per_author",
+                    plot_commit_count_per_author(&commits, cli.top_authors, cli.co_author_weight),
+                ));
+                let counts = count_commits_per_author(&commits, cli.co_author_weight);
+                plots.push(("lorenz", plot_lorenz_curve(&counts)));
+                get_inequality(&counts)
+            })
+        })
+    });
+
+    sections.run(Section::AuthorLanguages, || {
+        unless_cancelled(|| {
+            timings.time("author languages", || {
+                if !profile.with_files() {
+                    return;
+                }
+                plots.push((
+                    "author_languages",
+                    plot_languages_per_author(&cli.path, &commits, cli.top_authors),
+                ));
+            })
+        })
+    });
+
+    sections.run(Section::Tenure, || {
+        unless_cancelled(|| {
+            timings.time("tenure", || {
+                let tenures = get_tenures(&commits);
+                plots.push((
+                    "tenure",
+                    plot_tenure_timeline(&commits, &tenures, cli.top_authors),
+                ));
+            })
+        })
+    });
+
+    sections.run(Section::ActiveContributors, || {
+        unless_cancelled(|| {
+            timings.time("active contributors", || {
+                let tenures = get_tenures(&commits);
+                plots.push((
+                    "active_contributors",
+                    plot_active_contributors(&commits, &tenures),
+                ));
+            })
+        })
+    });
+
+    let newcomers = sections.run(Section::NewContributors, || {
+        unless_cancelled(|| {
+            timings.time("new contributors", || {
+                let newcomers = get_newcomers(&commits)?;
+                plots.push(("new_contributors", plot_newcomers(&newcomers)));
+                Some(newcomers)
+            })
+        })
+        .flatten()
+    });
+
+    let forecasts = sections.run(Section::Forecast, || {
+        unless_cancelled(|| {
+            timings.time("forecast", || {
+                if !profile.with_files() {
+                    return None;
+                }
+                let forecasts = get_forecasts(&commits)?;
+                plots.push(("forecast", plot_forecasts(&forecasts, &style.colors())));
+                Some(forecasts)
+            })
+        })
+        .flatten()
+    });
+
+    let streaks = sections.run(Section::Streaks, || {
+        unless_cancelled(|| timings.time("streaks", || get_streaks(&commits))).flatten()
+    });
+
+    let after_hours = sections.run(Section::AfterHours, || {
+        unless_cancelled(|| {
+            timings.time("after hours", || {
+                if !cli.after_hours && !sections.requested(Section::AfterHours) {
+                    return None;
+                }
+                let after_hours =
+                    get_after_hours(&commits, &config.working_hours, cli.top_authors)?;
+                plots.push(("after_hours", plot_after_hours(&after_hours)));
+                Some(after_hours)
+            })
+        })
+        .flatten()
+    });
+
+    let tone = sections.run(Section::Tone, || {
+        unless_cancelled(|| {
+            timings.time("tone", || {
+                if !cli.tone && !sections.requested(Section::Tone) {
+                    return None;
+                }
+                let tone = get_tone(&commits, &config.tone)?;
+                plots.push(("tone", plot_tone(&tone)));
+                Some(tone)
+            })
+        })
+        .flatten()
+    });
+
+    let merge_stats = sections.run(Section::Merges, || {
+        unless_cancelled(|| {
+            timings.time("merges", || {
+                let merge_stats = get_merge_stats(&commits);
+                plots.push(("merge", plot_merge_activity(&merge_stats)));
+                merge_stats
+            })
+        })
+    });
+
+    let branches = sections.run(Section::Branches, || {
+        unless_cancelled(|| {
+            timings.time("branches", || {
+                let branches = skip_failed(
+                    "branches",
+                    get_divergent_branches(&cli.path, &cli.path_scope),
+                )?;
+                if branches.is_empty() {
+                    return None;
+                }
+                plots.push(("branch_age", plot_branch_ages(&branches)));
+                Some(branches)
+            })
+        })
+        .flatten()
+    });
+
+    let notable_events = sections.run(Section::NotableEvents, || {
+        unless_cancelled(|| {
+            timings.time("notable events", || {
+                Some(get_notable_events(
+                    &commits,
+                    profile.with_files(),
+                    &sensitive_patterns,
+                ))
+                .filter(|events| !events.is_empty())
+            })
+        })
+        .flatten()
+    });
+
+    let sensitive_changes = sections.run(Section::SensitiveChanges, || {
+        unless_cancelled(|| {
+            timings.time("sensitive changes", || {
+                if !profile.with_files() {
+                    return None;
+                }
+                let latest = commits.iter().map(|commit| commit.date).max()?;
+                let since = cli
+                    .audit_since
+                    .unwrap_or(latest - chrono::Duration::days(DEFAULT_AUDIT_DAYS));
+                Some((
+                    since,
+                    get_sensitive_changes(&commits, &sensitive_patterns, since),
+                ))
+            })
+        })
+        .flatten()
+    });
+
+    let signatures = sections.run(Section::Signatures, || {
+        unless_cancelled(|| {
+            timings.time("signatures", || {
+                if !cli.signatures && !sections.requested(Section::Signatures) {
+                    return None;
+                }
+                let signatures =
+                    skip_failed("signatures", get_signatures(&cli.path, &cli.path_scope))?;
+                let stats = get_signature_stats(
+                    &commits,
+                    &signatures,
+                    profile.granularity(cli.granularity),
+                    cli.top_authors,
+                )?;
+                plots.push(("signatures", plot_signatures(&stats)));
+                Some(stats)
+            })
+        })
+        .flatten()
+    });
+
+    let squashed = sections.run(Section::Squash, || {
+        unless_cancelled(|| {
+            timings.time("squash", || {
+                if !cli.squash && !sections.requested(Section::Squash) {
+                    return None;
+                }
+                Some(squash_commits(&commits, cli.squash_window, cli.top_authors))
+            })
+        })
+        .flatten()
+    });
+
+    let pairing = sections.run(Section::Pairing, || {
+        unless_cancelled(|| {
+            timings.time("pairing", || {
+                if !profile.with_files() {
+                    return None;
+                }
+                let pairing = get_pairing(&commits, cli.top_authors)?;
+                plots.push(("pairing", plot_pairing_sessions(&pairing)));
+                Some(pairing)
+            })
+        })
+        .flatten()
+    });
+
+    let first_responses = sections.run(Section::FirstResponse, || {
+        unless_cancelled(|| {
+            timings.time("first response", || {
+                if !profile.with_files() {
+                    return None;
+                }
+                let responses = get_first_responses(&commits, cli.co_author_weight)?;
+                plots.push(("first_response", plot_first_responses(&responses)));
+                Some(responses)
+            })
+        })
+        .flatten()
+    });
+
+    let commit_sizes = sections.run(Section::CommitSizes, || {
+        unless_cancelled(|| {
+            timings.time("commit sizes", || {
+                if !profile.with_files() {
+                    return None;
+                }
+                plots.push(("commit_size", plot_commit_size_histogram(&commits)));
+                Some(get_commit_sizes_per_author(&commits, cli.top_authors))
+            })
+        })
+        .flatten()
+    });
+
+    let sprints = sections.run(Section::Sprints, || {
+        unless_cancelled(|| {
+            timings.time("sprints", || {
+                if !config.sprints.is_configured() {
+                    return None;
+                }
+                let sprints = get_sprints(&commits, &config.sprints);
+                plots.push(("sprint", plot_sprint_velocity(&sprints)));
+                Some(sprints)
+            })
+        })
+        .flatten()
+    });
+
+    let ownership = sections.run(Section::CodeOwners, || {
+        unless_cancelled(|| {
+            timings.time("code owners", || {
+                if !profile.with_files() || repository == Repository::Bare {
+                    return None;
+                }
+                let mut code_owners = match get_code_owners(&cli.path)? {
+                    Ok(code_owners) => code_owners,
+                    Err(err) => {
+                        eprintln!("Warning: Skipping code owners: {}", err);
+                        return None;
+                    }
+                };
+                if let Some(anonymizer) = &anonymizer {
+                    code_owners.map_owners(|owner| anonymizer.owner(owner));
+                }
+                let stats = get_ownership_stats(&commits, &code_owners, cli.top_authors);
+                Some((code_owners, stats))
+            })
+        })
+        .flatten()
+    });
+
+    let defects = sections.run(Section::DefectProneFiles, || {
+        unless_cancelled(|| {
+            timings.time("defect-prone files", || {
+                if !profile.with_files() {
+                    return None;
+                }
+                get_defect_stats(&commits, &fix_patterns, cli.top_authors)
+            })
+        })
+        .flatten()
+    });
+
+    let issue_references = sections.run(Section::Issues, || {
+        unless_cancelled(|| {
+            timings.time("issues", || {
+                let references = get_issue_references(&commits, &issue_patterns, cli.top_authors);
+                plots.push(("issue_reference", plot_issue_references(&references)));
+                references
+            })
+        })
+    });
+
+    sections.run(Section::Hotfixes, || {
+        unless_cancelled(|| {
+            timings.time("hotfixes", || {
+                let release_branches = skip_failed(
+                    "hotfixes",
+                    get_release_branches(&cli.path, &release_pattern, &cli.path_scope),
+                );
+                if let Some(release_branches) =
+                    release_branches.filter(|branches| !branches.is_empty())
+                {
+                    plots.push(("hotfix", plot_hotfixes(&release_branches)));
+                }
+            })
+        })
+    });
+
+    let affiliations = sections.run(Section::Affiliations, || {
+        unless_cancelled(|| {
+            timings.time("affiliations", || {
+                if !cli.affiliations
+                    && !sections.requested(Section::Affiliations)
+                    && config.affiliations.is_empty()
+                {
+                    return None;
+                }
+                let affiliations = get_affiliations(&commits, &config.affiliations);
+                plots.push((
+                    "affiliation",
+                    plot_affiliations(&affiliations, cli.top_authors),
+                ));
+                Some(affiliations)
+            })
+        })
+        .flatten()
+    });
+
+    let team_weeks = sections.run(Section::TeamWeeks, || {
+        unless_cancelled(|| {
+            timings.time("team weeks", || {
+                if !cli.team_weeks
+                    && !sections.requested(Section::TeamWeeks)
+                    && config.teams.is_empty()
+                {
+                    return None;
+                }
+                let team_weeks = get_team_weeks(&commits, &config.teams, &config.affiliations)?;
+                plots.push(("team_weeks", plot_team_weeks(&team_weeks)));
+                Some(team_weeks)
+            })
+        })
+        .flatten()
+    });
+
+    let hosting = match (&cli.github, &cli.gitlab) {
+        (Some(repo), _) => Some(Hosting::GitHub { repo: repo.clone() }),
+        (_, Some(project)) => Some(Hosting::GitLab {
+            project: project.clone(),
+            url: cli.gitlab_url.clone(),
+        }),
+        _ => None,
+    };
+    let hosting_stats = sections.run(Section::Hosting, || {
+        unless_cancelled(|| {
+            timings.time("hosting", || {
+                let hosting = hosting.as_ref()?;
+                let mut stats = match get_hosting_stats(hosting, &commits) {
+                    Ok(stats) => stats,
+                    Err(err) => {
+                        eprintln!("Warning: Skipping data from {}: {}", hosting, err);
+                        return None;
+                    }
+                };
+                // logins and avatars would give the pseudonyms away
+                if anonymizer.is_some() {
+                    stats.profiles.clear();
+                }
+                plots.push(("pull_request", plot_pull_request_throughput(&stats)));
+                plots.push(("issue", plot_issue_throughput(&stats)));
+                Some(stats)
+            })
+        })
+        .flatten()
+    });
+
+    let milestones = sections.run(Section::Milestones, || {
+        unless_cancelled(|| {
+            timings.time("milestones", || {
+                let dates = skip_failed(
+                    "milestones",
+                    get_milestone_dates(&cli.path, &config.milestones, hosting_stats.as_ref()),
+                )?;
+                if dates.is_empty() {
+                    return None;
+                }
+                let milestones = get_milestones(&commits, &dates, hosting_stats.as_ref());
+                plots.push(("milestone", plot_milestone_burn_up(&milestones)));
+                Some(milestones)
+            })
+        })
+        .flatten()
+    });
+
+    let release_cadence = sections.run(Section::Releases, || {
+        unless_cancelled(|| {
+            timings.time("releases", || {
+                let cadence = skip_failed(
+                    "releases",
+                    get_tags(&cli.path)
+                        .and_then(|tags| get_release_cadence(&cli.path, &tags, &cli.path_scope)),
+                )??;
+                plots.push(("release_cadence", plot_release_cadence(&cadence)));
+                Some(cadence)
+            })
+        })
+        .flatten()
+    });
+
+    let file_ages = sections.run(Section::BlameAge, || {
+        unless_cancelled(|| {
+            timings.time("blame age", || {
+                if !cli.blame_age && !sections.requested(Section::BlameAge) {
+                    return None;
+                }
+                if profile.is_degraded() {
+                    eprintln!(
+                        "Warning: Skipping --blame-age in degraded mode, run with --full to include it"
+                    );
+                    return None;
+                }
+                let mut blamed = match checkpoints.try_stage("blame", || get_blame_ages(&cli.path, &cli.path_scope)) {
+                    Ok(blamed) => blamed,
+                    Err(err) => {
+                        eprintln!("Warning: Skipping blame age: {}", err);
+                        return None;
+                    }
+                };
+                if let Some(anonymizer) = &anonymizer {
+                    for lines in &mut blamed {
+                        lines.author = anonymizer.name(&lines.author);
+                    }
+                }
+                plots.push(("line_age", plot_line_age_histogram(&blamed)));
+                plots.push((
+                    "code_freshness",
+                    plot_code_freshness(&blamed, cli.top_authors),
+                ));
+                Some(get_file_ages(&blamed, cli.top_authors))
+            })
+        })
+        .flatten()
+    });
+
+    let api_releases = sections.run(Section::ApiSurface, || {
+        unless_cancelled(|| {
+            timings.time("api surface", || {
+                if !cli.api_surface && !sections.requested(Section::ApiSurface) {
+                    return None;
+                }
+                let releases = match get_api_releases(&cli.path, &cli.path_scope) {
+                    Ok(releases) => releases?,
+                    Err(err) => {
+                        eprintln!("Warning: Skipping API surface: {}", err);
+                        return None;
+                    }
+                };
+                plots.push(("api", plot_api_changes(&releases)));
+                Some(releases)
+            })
+        })
+        .flatten()
+    });
+
+    let languages = sections.run(Section::Languages, || {
+        unless_cancelled(|| {
+            timings.time("languages", || {
+                if repository == Repository::Bare {
+                    return None;
+                }
+                // without --recurse-submodules, submodules are left out like they are from the history
+                let (stage, excluded) = match cli.recurse_submodules {
+                    true => ("languages-submodules", vec![]),
+                    false => (
+                        "languages",
+                        skip_failed("languages", get_submodule_paths(&cli.path))?,
+                    ),
+                };
+                let recorded = recording
+                    .as_ref()
+                    .and_then(|recording| recording.languages(&cli.path, &cli.path_scope));
+                let languages = match recorded {
+                    Some(languages) => languages,
+                    None => skip_failed(
+                        "languages",
+                        checkpoints.try_stage(stage, || {
+                            get_repo_languages(&cli.path, &excluded, &cli.path_scope)
+                        }),
+                    )?,
+                };
+                plots.push(("code_treemap", plot_code_treemap(&cli.path, &languages)));
+                Some(languages)
+            })
+        })
+        .flatten()
+    });
+
+    let largest_files = languages
+        .as_ref()
+        .map(|languages| get_largest_files(&cli.path, languages, cli.top_authors))
+        .filter(|files| !files.is_empty());
+
+    let extensions = sections.run(Section::Extensions, || {
+        unless_cancelled(|| {
+            timings.time("extensions", || {
+                skip_failed(
+                    "extensions",
+                    get_extensions(&cli.path, &cli.path_scope, cli.top_authors),
+                )
+            })
+        })
+        .flatten()
+    });
+
+    let submodule_stats = sections.run(Section::Submodules, || {
+        unless_cancelled(|| {
+            timings.time("submodules", || {
+                if submodules.is_empty() {
+                    return None;
+                }
+                Some(get_submodule_stats(
+                    &cli.path,
+                    &submodules,
+                    &commits,
+                    languages.as_deref(),
+                ))
+            })
+        })
+        .flatten()
+    });
+
+    let data_files = sections.run(Section::DataFiles, || {
+        unless_cancelled(|| {
+            timings.time("data files", || {
+                skip_failed(
+                    "data files",
+                    get_data_files(&cli.path, &cli.path_scope, &commits),
+                )?
+            })
+        })
+        .flatten()
+    });
+
+    let infra = sections.run(Section::Infrastructure, || {
+        unless_cancelled(|| {
+            timings.time("infrastructure", || {
+                let infra = skip_failed(
+                    "infrastructure",
+                    get_infra(&cli.path, &commits, profile.granularity(cli.granularity)),
+                )??;
+                if profile.with_files() {
+                    plots.push(("infra", plot_infra_share(&infra)));
+                }
+                Some(infra)
+            })
+        })
+        .flatten()
+    });
+
+    let dossiers = sections.run(Section::FileDossiers, || {
+        unless_cancelled(|| {
+            timings.time("file dossiers", || {
+                if !profile.with_files() {
+                    return None;
+                }
+                // every file listed in a table links to its dossier
+                let mut paths: BTreeSet<String> = BTreeSet::new();
+                paths.extend(file_ages.iter().flatten().map(|age| age.path.clone()));
+                if let Some((_, stats)) = &ownership {
+                    paths.extend(stats.hot_files.iter().map(|hot| hot.path.clone()));
+                }
+                if let Some(files) = &largest_files {
+                    paths.extend(files.iter().map(|file| file.path.clone()));
+                }
+                if let Some(defects) = &defects {
+                    paths.extend(defects.hot_files.iter().map(|file| file.path.clone()));
+                    paths.extend(defects.defect_files.iter().map(|file| file.path.clone()));
+                }
+                if let Some((_, changes)) = &sensitive_changes {
+                    paths.extend(changes.iter().flat_map(|change| change.files.clone()));
+                }
+                let code_owners = ownership.as_ref().map(|(code_owners, _)| code_owners);
+                Some(get_dossiers(&commits, &paths, code_owners))
+                    .filter(|dossiers| !dossiers.is_empty())
+            })
+        })
+        .flatten()
+    });
+
+    let plugins = sections.run(Section::Plugins, || {
+        unless_cancelled(|| {
+            if config.plugins.is_empty() {
+                return None;
+            }
+            if !cli.allow_plugins {
+                eprintln!(
+                    "Warning: Skipping the plugins of the config, run with --allow-plugins to run their commands"
+                );
+                return None;
+            }
+            timings.time("plugins", || {
+                Some(run_plugins(&config.plugins, &cli.path, &commits))
+            })
+        })
+        .flatten()
+    });
+
+    let history = cli
+        .history_file
+        .as_ref()
+        .filter(|_| !is_cancelled())
+        .map(|path| -> Result<_, Error> {
+            let mut snapshots = read_history(path).map_err(Error::Input)?;
+            let counts = count_commits_per_author(&commits, cli.co_author_weight);
+            let snapshot = Snapshot::new(
+                run_git(&cli.path, &["rev-parse", "HEAD"])?
+                    .trim()
+                    .to_string(),
+                &commits,
+                counts.len(),
+                get_inequality(&counts).bus_factor,
+                languages.as_deref().map(count_lines_of_code),
+            );
+            append_snapshot(path, &snapshot).map_err(|source| Error::Write {
+                path: path.clone(),
+                source,
+            })?;
+            snapshots.push(snapshot);
+            plots.push((
+                "metrics_history",
+                plot_history(&latest_per_head(&snapshots)),
+            ));
+            Ok(snapshots)
+        })
+        .transpose()?;
+
+    if let Some(dir) = cli.export_images.as_ref().filter(|_| !is_cancelled()) {
+        timings.time("image export", || {
+            export_images(&plots, &style, dir, cli.scale)
+        })?;
     }
 
-    let commits = get_commit_log(&cli.path);
-    let activity_plot = plot_commit_history(&commits);
-    let commits_per_author_plot = plot_commit_count_per_author(&commits, 10);
+    if let Some(path) = cli.export_contributors.as_ref().filter(|_| !is_cancelled()) {
+        let head = run_git(&cli.path, &["rev-parse", "HEAD"])?;
+        write_contributor_map(path, head.trim(), &get_contributors(&commits)).map_err(
+            |source| Error::Write {
+                path: path.clone(),
+                source,
+            },
+        )?;
+    }
 
-    let languages = get_repo_languages(&cli.path);
+    if let Some(path) = cli.export_sqlite.as_ref().filter(|_| !is_cancelled()) {
+        timings.time("sqlite export", || {
+            export_sqlite(path, &commits, languages.as_deref()).map_err(|err| {
+                Error::Output(format!("Could not write {}: {}", safe_path(path), err))
+            })
+        })?;
+    }
+
+    if let Some(dir) = cli.badges.as_ref().filter(|_| !is_cancelled()) {
+        let counts = count_commits_per_author(&commits, cli.co_author_weight);
+        if languages.is_none() {
+            eprintln!(
+                "Warning: Skipping the lines of code and language badges, languages weren't counted"
+            );
+        }
+        let metrics = BadgeMetrics {
+            commits: commits.len(),
+            contributors: counts.len(),
+            bus_factor: get_inequality(&counts).bus_factor,
+            lines_of_code: languages.as_deref().map(count_lines_of_code),
+            language: languages.as_ref().and_then(|languages| {
+                languages
+                    .iter()
+                    .max_by_key(|(_, language)| language.code)
+                    .map(|(language_type, _)| language_type.to_string())
+            }),
+        };
+        write_badges(dir, &metrics).map_err(Error::Output)?;
+    }
+
+    if let Some(dir) = cli.export_data.as_ref().filter(|_| !is_cancelled()) {
+        timings.time("data export", || export_data(&plots, dir))?;
+    }
+
+    if let Some(path) = cli.export_animation.as_ref().filter(|_| !is_cancelled()) {
+        if profile.with_files() {
+            timings.time("animation export", || {
+                let frames = plot_heat_frames(&commits, profile.granularity(cli.granularity));
+                export_animation(&frames, &style, path, cli.scale)
+            })?;
+        } else {
+            eprintln!(
+                "Warning: Skipping --export-animation in degraded mode, run with --full to include it"
+            );
+        }
+    }
+
+    let violations = check_thresholds(
+        &config.thresholds,
+        ownership.as_ref().map(|(_, stats)| stats),
+        issue_references.as_ref(),
+        commits.len(),
+        hosting_stats.as_ref(),
+    );
 
     let ctx = context! {
-    path => cli.path,
-    activity_plot => activity_plot.to_inline_html(None),
-    commits_per_author_plot => commits_per_author_plot.to_inline_html(None),
+    path => clone.as_ref().map_or(cli.path.to_string_lossy(), |clone| clone.url.as_str().into()),
+    ascii => cli.ascii,
+    path_scope => cli.path_scope,
+    lang => cli.lang.code(),
+    accent => config.theme.accent(),
+    theme => theme.page_colors(),
+    layout => layout,
+    metrics => BTreeMap::from(METRICS),
+    plugins => plugins.as_ref().map(|plugins| plugins.iter().enumerate().map(|(i, plugin)| context! {
+        name => plugin.name,
+        html => match &plugin.output {
+            PluginOutput::Html(html) => html.clone(),
+            PluginOutput::Figure(figure) => render_figure(&format!("plugin{}", i), figure),
+        },
+    }).collect::<Vec<_>>()),
+    dossier_ids => dossiers.iter().flatten().enumerate().map(|(i, dossier)| (dossier.path.clone(), i)).collect::<BTreeMap<_, _>>(),
+    dossiers => dossiers.as_ref().map(|dossiers| dossiers.iter().map(|dossier| context! {
+        path => dossier.path,
+        commits => dossier.commits,
+        lines_added => dossier.lines_added,
+        lines_deleted => dossier.lines_deleted,
+        first_change => dossier.first_change,
+        last_change => dossier.last_change,
+        churn => dossier.churn.iter().map(|churn| context! {
+            quarter => churn.quarter,
+            commits => churn.commits,
+            lines_changed => churn.lines_changed,
+        }).collect::<Vec<_>>(),
+        authors => dossier.authors,
+        owners => dossier.owners,
+        coupled => dossier.coupled.iter().map(|coupled| context! {
+            path => coupled.path,
+            commits => coupled.commits,
+            share => coupled.share,
+        }).collect::<Vec<_>>(),
+    }).collect::<Vec<_>>()),
+    sensitive_changes => sensitive_changes.map(|(since, changes)| context! {
+        since => since,
+        changes => changes.iter().map(|change| context! {
+            hash => change.hash,
+            url => hosting.as_ref().map(|hosting| hosting.commit_url(&change.hash)),
+            date => change.date,
+            author => change.author,
+            lines_added => change.lines_added,
+            lines_deleted => change.lines_deleted,
+            files => change.files,
+        }).collect::<Vec<_>>(),
+    }),
+    signatures => signatures.map(|stats| context! {
+        commits => stats.total.commits,
+        signed => stats.total.signed(),
+        verified => stats.total.verified,
+        bad => stats.total.bad,
+        authors => stats.authors.iter().map(|(author, counts)| context! {
+            author => author,
+            commits => counts.commits,
+            signed => counts.signed_share(),
+            verified => counts.verified as f64 / counts.commits as f64,
+        }).collect::<Vec<_>>(),
+    }),
+    metrics_history => history.as_ref().map(|snapshots| {
+        latest_per_head(snapshots).iter().rev().map(|snapshot| context! {
+            date => snapshot.date,
+            head => snapshot.head,
+            commits => snapshot.commits,
+            contributors => snapshot.contributors,
+            bus_factor => snapshot.bus_factor,
+            lines_of_code => snapshot.lines_of_code,
+            churn => snapshot.churn,
+        }).collect::<Vec<_>>()
+    }),
+    new_contributors => newcomers.map(|newcomers| newcomers.recent.iter().map(|newcomer| context! {
+        author => newcomer.author,
+        hash => newcomer.hash,
+        url => hosting.as_ref().map(|hosting| hosting.commit_url(&newcomer.hash)),
+        date => newcomer.date,
+        subject => newcomer.subject,
+        files => newcomer.files,
+        more_files => newcomer.file_count - newcomer.files.len(),
+        commits => newcomer.commits,
+    }).collect::<Vec<_>>()),
+    forecast => forecasts.map(|forecasts| forecasts.iter().map(|forecast| {
+        let (commits, lower, upper) = forecast.projected_commits();
+        context! {
+            area => forecast.area,
+            recent_commits => forecast.recent_commits(),
+            commits => commits.round(),
+            lower => lower.round(),
+            upper => upper.round(),
+        }
+    }).collect::<Vec<_>>()),
+    notable_events => notable_events.map(|events| events.iter().map(|event| context! {
+        kind => event.kind.label(),
+        date => event.date,
+        description => event.description,
+        commit => event.commit,
+        url => event.commit.as_ref().zip(hosting.as_ref()).map(|(hash, hosting)| hosting.commit_url(hash)),
+    }).collect::<Vec<_>>()),
+    branches => branches.map(|branches| context! {
+        count => branches.len(),
+        at_risk => branches.iter().filter(|branch| branch.at_risk()).count(),
+        branches => branches.iter().take(cli.top_authors).map(|branch| context! {
+            name => branch.name,
+            ahead => branch.ahead,
+            behind => branch.behind,
+            forked => branch.forked,
+            days => branch.days,
+            at_risk => branch.at_risk(),
+            last_commit => branch.last_commit,
+        }).collect::<Vec<_>>(),
+    }),
+    merges => merge_stats.map(|merge_stats| context! {
+        merge_count => merge_stats.merges,
+        direct_count => merge_stats.direct,
+        avg_commits_per_merge => merge_stats.avg_commits_per_merge(),
+    }),
+    sprints => sprints.map(|sprints| context! {
+        churn => profile.with_files(),
+        sprints => sprints.iter().map(|sprint| context! {
+            name => sprint.name,
+            start => sprint.start,
+            end => sprint.end,
+            commits => sprint.commits,
+            contributors => sprint.contributors,
+            lines_added => sprint.lines_added,
+            lines_deleted => sprint.lines_deleted,
+        }).collect::<Vec<_>>(),
+    }),
+    milestones => milestones.map(|milestones| milestones.iter().map(|milestone| context! {
+        name => milestone.name,
+        start => milestone.start,
+        due => milestone.due,
+        commits => milestone.commits,
+        contributors => milestone.contributors,
+        merged_pull_requests => milestone.merged_pull_requests,
+    }).collect::<Vec<_>>()),
+    release_cadence => release_cadence.map(|cadence| context! {
+        releases => cadence.releases.iter().map(|release| context! {
+            tag => release.tag,
+            date => release.date,
+            days => release.days,
+            commits => release.commits,
+        }).collect::<Vec<_>>(),
+        median_days => cadence.median_days,
+        median_commits => cadence.median_commits,
+        recent_median_days => cadence.recent_median_days,
+        earlier_median_days => cadence.earlier_median_days,
+    }),
+    ownership => ownership.as_ref().map(|(code_owners, stats)| {
+        let hot_paths = |paths: &[codeowners::HotPath]| paths.iter().map(|hot| context! {
+            path => hot.path,
+            changes => hot.changes,
+            owners => hot.owners,
+            recent => hot.recent,
+            recent_by_owners => hot.recent_by_owners,
+        }).collect::<Vec<_>>();
+        context! {
+            path => code_owners.path,
+            changed_files => stats.changed_files,
+            unowned_files => stats.unowned_files,
+            hot_files => hot_paths(&stats.hot_files),
+            hot_directories => hot_paths(&stats.hot_directories),
+        }
+    }),
+    defects => defects.map(|defects| {
+        let files = |files: &[defects::DefectFile]| files.iter().map(|file| context! {
+            path => file.path,
+            changes => file.changes,
+            fixes => file.fixes,
+            density => file.density(),
+        }).collect::<Vec<_>>();
+        context! {
+            commits => defects.commits,
+            fix_commits => defects.fix_commits,
+            hot_files => files(&defects.hot_files),
+            defect_files => files(&defects.defect_files),
+        }
+    }),
+    issue_references => issue_references.map(|references| context! {
+        referencing_commits => references.referencing_commits,
+        top_issues => references.top_issues,
+        commit_count => commits.len(),
+    }),
+    file_ages => file_ages.map(|ages| ages.iter().map(|age| context! {
+        path => age.path,
+        lines => age.lines,
+        median_age_days => age.median_age_days,
+    }).collect::<Vec<_>>()),
+    api_releases => api_releases.map(|releases| releases.iter().map(|release| context! {
+        tag => release.tag,
+        date => release.date,
+        items => release.items,
+        added => release.added,
+        removed => release.removed,
+    }).collect::<Vec<_>>()),
+    velocity => velocity.map(|trend| context! {
+        recent => trend.recent,
+        previous => trend.previous,
+        change => trend.change(),
+    }),
+    commit_count => commits.len(),
+    author_count => count_commits_per_author(&commits, cli.co_author_weight).len(),
+    top_contributors => count_commits_per_author(&commits, cli.co_author_weight)
+        .into_iter()
+        .rev()
+        .take(cli.top_authors)
+        .collect::<Vec<_>>(),
+    activity => recent_activity(&commits),
+    inequality => inequality.map(|inequality| context! {
+        gini => inequality.gini,
+        pareto_authors => inequality.pareto_authors,
+        authors => inequality.authors,
+    }),
+    degraded => profile.degraded,
+    partial => is_cancelled(),
+    // only runs saving checkpoints can be completed with --resume
+    resumable => cli.checkpoint || cli.resume,
+    commit_sizes => commit_sizes.map(|sizes| sizes.iter().map(|size| context! {
+        author => size.author,
+        commits => size.commits,
+        median => size.median,
+        p90 => size.p90,
+    }).collect::<Vec<_>>()),
+    after_hours => after_hours.map(|after_hours| context! {
+        start => config.working_hours.start,
+        end => config.working_hours.end,
+        commits => after_hours.total.commits,
+        weekend => after_hours.total.weekend_share(),
+        after_hours => after_hours.total.after_hours_share(),
+        authors => after_hours.authors.iter().map(|(author, off_hours)| context! {
+            author => author,
+            commits => off_hours.commits,
+            weekend => off_hours.weekend_share(),
+            after_hours => off_hours.after_hours_share(),
+        }).collect::<Vec<_>>(),
+    }),
+    tone => tone.map(|tone| context! {
+        commits => tone.total.commits,
+        frustrated => tone.total.frustrated_share(),
+        upbeat => tone.total.upbeat_share(),
+        areas => tone.areas.iter().map(|area| context! {
+            area => area.area,
+            commits => area.total.commits,
+            frustrated => area.total.frustrated_share(),
+            upbeat => area.total.upbeat_share(),
+        }).collect::<Vec<_>>(),
+        markers => tone.markers.iter().map(|(word, commits)| context! {
+            word => word,
+            commits => commits,
+        }).collect::<Vec<_>>(),
+    }),
+    streaks => streaks.map(|streaks| context! {
+        longest_streak => context! {
+            start => streaks.longest_streak.start.to_string(),
+            end => streaks.longest_streak.end.to_string(),
+            days => streaks.longest_streak.days,
+        },
+        longest_gap => streaks.longest_gap.map(|gap| context! {
+            start => gap.start.to_string(),
+            end => gap.end.to_string(),
+            days => gap.days,
+        }),
+        busiest_day => context! {
+            date => streaks.busiest_day.0.to_string(),
+            commits => streaks.busiest_day.1,
+        },
+        busiest_week => context! {
+            start => streaks.busiest_week.0.to_string(),
+            commits => streaks.busiest_week.1,
+        },
+    }),
+    squashed => squashed.map(|squashed| context! {
+        window_minutes => cli.squash_window,
+        commits => squashed.commits,
+        changes => squashed.changes,
+        authors => squashed.authors.iter().map(|author| context! {
+            author => author.author,
+            commits => author.commits,
+            changes => author.changes,
+        }).collect::<Vec<_>>(),
+    }),
+    pairing => pairing.map(|pairing| pairing.pairs.iter().map(|pair| context! {
+        authors => [&pair.authors.0, &pair.authors.1],
+        sessions => pair.sessions,
+        last_session => pair.last_session.to_string(),
+    }).collect::<Vec<_>>()),
+    first_responses => first_responses.map(|responses| context! {
+        maintainers => responses.maintainers,
+        contributions => responses.contributions.len(),
+        answered => responses.answered(),
+        median_days => responses.median_days,
+    }),
+    affiliations => affiliations.map(|affiliations| affiliations.iter().map(|affiliation| context! {
+        name => affiliation.name,
+        commits => affiliation.commits,
+        authors => affiliation.authors,
+    }).collect::<Vec<_>>()),
+    team_weeks => team_weeks.map(|team_weeks| context! {
+        organizations => team_weeks.organizations,
+        shared_hours => hour_ranges(&team_weeks.shared_hours()),
+        teams => team_weeks.teams.iter().map(|team| context! {
+            name => team.team,
+            authors => team.authors,
+            commits => team.commits,
+            active_hours => hour_ranges(&team.active_hours()),
+        }).collect::<Vec<_>>(),
+    }),
+    hosting => hosting_stats.as_ref().map(|stats| context! {
+        pull_requests => stats.pull_requests.len(),
+        merged => stats.pull_requests.iter().filter(|pr| pr.merged.is_some()).count(),
+        median_days_to_merge => stats.median_days_to_merge(),
+        issues => stats.issues.len(),
+        closed => stats.issues.iter().filter(|issue| issue.closed.is_some()).count(),
+        median_days_to_close => stats.median_days_to_close(),
+        contributors => count_commits_per_author(&commits, cli.co_author_weight)
+            .iter()
+            .rev()
+            .take(cli.top_authors)
+            .map(|(author, count)| {
+                let profile = stats.profiles.get(author);
+                context! {
+                    name => author,
+                    commits => count,
+                    login => profile.and_then(|p| p.login.clone()),
+                    url => profile.and_then(|p| p.url.clone()),
+                    avatar_url => profile.and_then(|p| p.avatar_url.clone()),
+                }
+            })
+            .collect::<Vec<_>>(),
+    }),
+    code_ratios => languages.as_ref().map(|languages| {
+        let ratios = get_code_ratios(&cli.path, languages, &test_patterns);
+        context! {
+            code => ratios.code_share(),
+            comments => ratios.comment_share(),
+            blanks => ratios.blank_share(),
+            test_to_code => ratios.test_to_code(),
+        }
+    }),
+    extensions => extensions.map(|extensions| extensions.iter().map(|extension| context! {
+        extension => extension.extension,
+        files => extension.files,
+        bytes => extension.bytes,
+    }).collect::<Vec<_>>()),
+    submodules => submodule_stats.map(|submodules| submodules.iter().map(|submodule| context! {
+        path => submodule.path,
+        commits => submodule.commits,
+        contributors => submodule.contributors,
+        last_commit => submodule.last_commit,
+        lines_of_code => submodule.lines_of_code,
+    }).collect::<Vec<_>>()),
+    data_files => data_files.map(|data_files| context! {
+        churn => profile.with_files(),
+        categories => data_files.iter().map(|data| context! {
+            category => data.category,
+            files => data.files,
+            bytes => data.bytes,
+            commits => data.commits,
+            lines_changed => data.lines_changed,
+        }).collect::<Vec<_>>(),
+    }),
+    largest_files => largest_files.map(|files| files.iter().map(|file| context! {
+        path => file.path,
+        language => file.language.to_string(),
+        code => file.code,
+        comments => file.comments,
+        comment_share => file.comment_share(),
+    }).collect::<Vec<_>>()),
+    infra => infra.map(|infra| context! {
+        churn => profile.with_files(),
+        kinds => infra.kinds.iter().map(|kind| context! {
+            kind => kind.kind,
+            files => kind.files,
+            lines => kind.lines,
+            commits => kind.commits,
+            lines_changed => kind.lines_changed,
+        }).collect::<Vec<_>>(),
+    }),
     languages => languages
     };
 
-    let template = render_template(ctx);
+    let mut figures: serde_json::Map<String, serde_json::Value> = plots
+        .iter()
+        .map(|(name, plot)| (name.to_string(), plot_figure(plot, &style)))
+        .collect();
+    for (i, plugin) in plugins.iter().flatten().enumerate() {
+        if let PluginOutput::Figure(figure) = &plugin.output {
+            figures.insert(format!("plugin{}", i), figure.clone());
+        }
+    }
+    let html = timings.time("render", || {
+        render_report(
+            &template,
+            &ctx,
+            &figures,
+            cli.offline,
+            locale.clone(),
+            &highlights,
+        )
+    })?;
+    let report_file = cli.report_file();
+    write_report(&report_file, html)?;
+
+    if let Some(dir) = &cli.record {
+        let manifest = recording::Manifest {
+            path: clone
+                .as_ref()
+                .map_or(cli.path.to_string_lossy(), |clone| {
+                    clone.url.as_str().into()
+                })
+                .to_string(),
+            branch: None,
+            github: cli.github.clone(),
+            gitlab: cli.gitlab.clone(),
+            gitlab_url: cli.gitlab.as_ref().map(|_| cli.gitlab_url.clone()),
+            path_scope: cli.path_scope.clone(),
+        };
+        let config = cli
+            .config
+            .clone()
+            .or_else(|| Some(cli.path.join(CONFIG_FILENAME)).filter(|config| config.exists()));
+        write_recording(
+            dir,
+            &cli.path,
+            manifest,
+            config.as_deref(),
+            languages.as_deref(),
+        )?;
+        println!("Recorded the inputs of the report in {}", safe_path(dir));
+    }
 
-    // Write to file
-    let filename = "git-report.html";
-    std::fs::write(filename, template).unwrap();
+    if let Some(dir) = &cli.embed_snippet {
+        write_embeds(
+            dir,
+            &figures,
+            &Value::from_serialize(theme.page_colors()),
+            cli.embed_url.as_deref(),
+            cli.offline.then(bundled_plotly_js),
+            &locale,
+        )?;
+    }
 
-    if webbrowser::open(filename).is_ok() {
-        println!("Done!");
+    if let Some(path) = &cli.export_bundle {
+        let bundle = Bundle {
+            manifest: Manifest::new(locale_name, &config.highlights),
+            report: serde_json::to_value(&ctx).unwrap(),
+            figures,
+        };
+        write_bundle(path, &bundle).map_err(|err| {
+            Error::Output(format!("Could not write {}: {}", safe_path(path), err))
+        })?;
+    }
+
+    // keep the checkpoints of a cancelled run, so it can be completed with --resume
+    if !is_cancelled() {
+        checkpoints.clear();
+    }
+
+    if cli.verbose {
+        timings.print();
+    }
+
+    if let Some(path) = &cli.export_json {
+        export_json(path, &ctx, &plots, &style, &timings)?;
+    }
+
+    if cli.github_actions {
+        let summary_ctx = context! {
+            ..context! { violations => violations },
+            ..ctx.clone()
+        };
+        let summary = render_template(
+            "summary.md",
+            SUMMARY_TEMPLATE,
+            summary_ctx,
+            locale,
+            &highlights,
+        )?;
+        report_to_github_actions(&summary, &violations, &report_file).map_err(Error::Service)?;
+        return Ok(());
+    }
+
+    if let Some(merge_request) = &merge_request {
+        let (in_merge_request, in_target): (Vec<&Commit>, Vec<&Commit>) = commits
+            .iter()
+            .partition(|commit| merge_request.contains(&commit.hash));
+        let code_owners = ownership.as_ref().map(|(code_owners, _)| code_owners);
+        let range_metrics = |commits: &[&Commit]| {
+            get_range_metrics(commits, &issue_patterns, code_owners, profile.with_files())
+        };
+        let comment_ctx = context! {
+            ..context! {
+                merge_request => range_metrics(&in_merge_request),
+                target => range_metrics(&in_target),
+            },
+            ..ctx.clone()
+        };
+        let comment = render_template(
+            "merge_request.md",
+            MERGE_REQUEST_TEMPLATE,
+            comment_ctx,
+            locale,
+            &highlights,
+        )?;
+        comment_on_merge_request(&comment).map_err(|err| {
+            Error::Service(format!("Could not comment on the merge request: {}", err))
+        })?;
+        println!("Commented on the merge request");
+        return Ok(());
+    }
+
+    if !cli.publishing {
+        open_report(&report_file);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maintainer_style_keeps_after_hours_and_tone_off() {
+        let cli = Cli::try_parse_from(["git-report", ".", "--style", "maintainer"]).unwrap();
+        let sections = cli.report.unwrap().sections();
+        assert!(sections.requested(Section::BlameAge));
+        assert!(!sections.requested(Section::AfterHours));
+        assert!(!sections.requested(Section::Tone));
     }
 }
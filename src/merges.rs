@@ -0,0 +1,83 @@
+use plotly::{common::Title, layout::BarMode, Bar, Layout, Plot};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::git::Commit;
+use crate::plots::PLOT_WIDTH;
+
+pub struct MergeStats {
+    /// Merge commits on the first-parent history of HEAD
+    pub merges: usize,
+    /// Non-merge commits pushed straight onto the first-parent history of HEAD
+    pub direct: usize,
+    /// Commits that only landed through a merge
+    pub merged_in: usize,
+    /// Per month counts of (merge commits, direct commits)
+    pub per_month: BTreeMap<String, (u32, u32)>,
+}
+
+impl MergeStats {
+    pub fn avg_commits_per_merge(&self) -> f64 {
+        if self.merges == 0 {
+            return 0.0;
+        }
+        self.merged_in as f64 / self.merges as f64
+    }
+}
+
+pub fn get_merge_stats(commits: &[Commit]) -> MergeStats {
+    let by_hash: HashMap<&str, &Commit> = commits.iter().map(|c| (c.hash.as_str(), c)).collect();
+
+    // walk the first-parent chain starting at HEAD, which is the first commit in the log
+    let mut mainline: HashSet<&str> = HashSet::new();
+    let mut current = commits.first();
+    while let Some(commit) = current {
+        mainline.insert(commit.hash.as_str());
+        current = commit
+            .parents
+            .first()
+            .and_then(|parent| by_hash.get(parent.as_str()).copied());
+    }
+
+    let mut stats = MergeStats {
+        merges: 0,
+        direct: 0,
+        merged_in: 0,
+        per_month: BTreeMap::new(),
+    };
+    for commit in commits {
+        if !mainline.contains(commit.hash.as_str()) {
+            stats.merged_in += 1;
+            continue;
+        }
+        let month = stats
+            .per_month
+            .entry(commit.date.format("%Y-%m").to_string())
+            .or_default();
+        if commit.is_merge() {
+            stats.merges += 1;
+            month.0 += 1;
+        } else {
+            stats.direct += 1;
+            month.1 += 1;
+        }
+    }
+    stats
+}
+
+pub fn plot_merge_activity(stats: &MergeStats) -> Plot {
+    let mut plot = Plot::new();
+
+    let x: Vec<String> = stats.per_month.keys().cloned().collect();
+    let merges: Vec<u32> = stats.per_month.values().map(|(m, _)| *m).collect();
+    let direct: Vec<u32> = stats.per_month.values().map(|(_, d)| *d).collect();
+    plot.add_trace(Bar::new(x.clone(), merges).name("Merge commits"));
+    plot.add_trace(Bar::new(x, direct).name("Direct commits"));
+
+    let layout = Layout::new()
+        .width(PLOT_WIDTH - 50)
+        .bar_mode(BarMode::Group)
+        .title(Title::from("Merge commits vs direct commits"));
+    plot.set_layout(layout);
+
+    plot
+}
@@ -0,0 +1,434 @@
+use serde::Serialize;
+
+/// What a metric shows and how it is computed, shown next to the metric in the report
+#[derive(Serialize)]
+pub struct Metric {
+    pub description: &'static str,
+    pub methodology: &'static str,
+}
+
+/// Metrics by the report panel or headline they belong to
+pub const METRICS: [(&str, Metric); 47] = [
+    (
+        "velocity",
+        Metric {
+            description: "Commits in the last 3 months compared to the 3 months before.",
+            methodology: "Periods end at the date of the latest commit, not today, so inactive repositories aren't \
+                          penalized for the time since. Merge commits are counted like any other commit.",
+        },
+    ),
+    (
+        "test-to-code",
+        Metric {
+            description: "Lines of test code per line of production code, and the share of code, comments and blanks.",
+            methodology: "Lines are counted by tokei at HEAD. Files matching the [tests] patterns of the config count \
+                          as test code, tests inside production files like Rust unit tests count as production code.",
+        },
+    ),
+    (
+        "activity",
+        Metric {
+            description: "Commits per period, stacked by the top contributors, with a 3 month moving average.",
+            methodology: "Commits are bucketed by commit date using --granularity. Co-authors named in \
+                          Co-authored-by trailers are credited with --co-author-weight of a commit each.",
+        },
+    ),
+    (
+        "tenure",
+        Metric {
+            description: "How long each contributor has been active, from their first to their last commit.",
+            methodology: "Contributors are identified by author name. Bars are shaded by commits per month, gaps \
+                          within a tenure are not split off.",
+        },
+    ),
+    (
+        "active-contributors",
+        Metric {
+            description: "How many people commit each month, and how many of them commit for the first time.",
+            methodology: "Distinct author names per month of the commit date, co-authors are not counted. A \
+                          contributor is new in the month of their first commit.",
+        },
+    ),
+    (
+        "new-contributors",
+        Metric {
+            description: "How many people make their first commit each month and whether they stay, with the most \
+                          recent first-time contributors and what they changed.",
+            methodology: "A contributor's first commit is their earliest commit by date, contributors are \
+                          told apart by name. A contributor committed again when they have more than one commit in \
+                          the history, co-authored commits are not counted.",
+        },
+    ),
+    (
+        "metrics-history",
+        Metric {
+            description: "Headline metrics of every report run with the same `--history-file`, to follow the health \
+                          of the project over time.",
+            methodology: "Every run appends the number of commits and contributors, the bus factor, the lines of code \
+                          and the lines changed in the 30 days up to the latest commit. Runs are placed at the date \
+                          of their latest commit, of several runs at the same commit the latest one is shown. The bus \
+                          factor is the fewest authors who together made half of the commits.",
+        },
+    ),
+    (
+        "forecast",
+        Metric {
+            description: "Projected commits per month of the busiest top-level directories for the next two \
+                          quarters, as a starting point for staffing discussions rather than a prediction.",
+            methodology: "Fitted to the commits per complete month of the last three years changing files in the \
+                          directory: a linear trend, plus the average deviation from it per calendar month with at \
+                          least two years of history. The band is the 95% prediction interval of the trend, from the \
+                          spread of the months around the fit. Needs a year of history, merge commits are left out.",
+        },
+    ),
+    (
+        "streaks",
+        Metric {
+            description: "The longest run of days with commits, the longest silence, and the busiest day and week.",
+            methodology: "Days are the dates of the commits in the --timezone, UTC by default. A gap is the days \
+                          without any commit between two commits, weeks start on Monday.",
+        },
+    ),
+    (
+        "after-hours",
+        Metric {
+            description: "How much of the work happens on weekends and in the evenings, a sign of crunch or of \
+                          volunteers working in their free time. Only shown with `--after-hours`.",
+            methodology: "Commits are placed by their date in the time zone they were made in. Commits on \
+                          Saturday and Sunday count as weekend work, commits on other days before the start or from \
+                          the end of the working hours in the config as after hours. Merge commits are left out.",
+        },
+    ),
+    (
+        "commit-tone",
+        Metric {
+            description: "How often commit messages sound frustrated or upbeat over time, per top-level directory. \
+                          A light-hearted hint at where work is painful, not a measure of anyone's work. Only shown \
+                          with `--tone`.",
+            methodology: "A message sounds frustrated when it contains a word like \"hack\", \"ugh\" or \"wtf\", \
+                          and upbeat with a word like \"cleanup\" or \"nice\", matched as whole words ignoring \
+                          case. The words can be changed in the [tone] section of the config. A commit counts \
+                          towards every top-level directory it changes. Merge commits are left out.",
+        },
+    ),
+    (
+        "merges",
+        Metric {
+            description: "How changes reach the main line: through merge commits or pushed directly.",
+            methodology: "Only the first-parent history of HEAD is considered. Commits reachable only through the \
+                          second parent of a merge count as merged.",
+        },
+    ),
+    (
+        "branches",
+        Metric {
+            description: "Local and remote branches with commits that aren't on HEAD, and how long ago they forked.",
+            methodology: "The fork point is the merge base with HEAD, its age is counted up to the latest commit on \
+                          HEAD. Branches that forked more than 90 days ago are flagged as drifting, they are likely \
+                          to conflict when they are integrated. Remote branches at the same commit as a local branch \
+                          are left out.",
+        },
+    ),
+    (
+        "notable-events",
+        Metric {
+            description: "Statistically unusual events: spikes in activity, large changes to sensitive directories by \
+                          new authors and mass deletions.",
+            methodology: "Values are compared to the rest of the history with the modified z-score, the distance from \
+                          the median in median absolute deviations, and flagged above 3.5. Spikes compare the commits \
+                          per week, weeks without commits included, and need at least 10 commits. Changes by new \
+                          authors are the first 10 commits of an author changing an unusual number of lines, \
+                          including files matching the sensitive path patterns of the config. Mass \
+                          deletions delete an unusual number of lines, at least 90% of the lines they change. Merge \
+                          commits are left out.",
+        },
+    ),
+    (
+        "sensitive-changes",
+        Metric {
+            description: "Every commit changing files that match the sensitive path patterns of the config, for a \
+                          lightweight security review.",
+            methodology: "Commits are listed from `--audit-since`, by default 90 days before the latest commit. The \
+                          lines added and deleted only count the sensitive files. Merge commits are left out, the \
+                          commits they merge are listed instead.",
+        },
+    ),
+    (
+        "signatures",
+        Metric {
+            description: "How many commits are signed with a GPG or SSH key, to follow the adoption of commit \
+                          signing. Only shown with `--signatures`.",
+            methodology: "Signatures are checked by git with the keys known on the machine making the report. A \
+                          signature is verified when it is good and its key isn't expired or revoked, signatures \
+                          by unknown keys count as signed but not verified.",
+        },
+    ),
+    (
+        "commit-sizes",
+        Metric {
+            description: "Lines changed per commit, overall and per author.",
+            methodology: "Lines added plus lines deleted as reported by git, binary files count as zero lines. Merge \
+                          commits are left out, their changes are counted in the commits they merge. Percentiles \
+                          use the nearest-rank method.",
+        },
+    ),
+    (
+        "squashed-commits",
+        Metric {
+            description: "Commits counted as logical changes, as if stacks of small commits had been squashed.",
+            methodology: "Commits of an author less than --squash-window minutes after their previous commit \
+                          belong to the same change, by commit date. This evens out teams that squash before \
+                          merging and teams that don't. Merge commits are left out.",
+        },
+    ),
+    (
+        "pairing",
+        Metric {
+            description: "Likely pairing or mobbing sessions, detected from authors taking turns on the same files.",
+            methodology: "A handoff is a commit to a file by another author than the previous commit to it, at most \
+                          2 hours later. A session is a run of at least 2 handoffs between the same two authors, \
+                          each within 2 hours of the last. This is a heuristic: it misses pairs who commit from \
+                          one machine and can mistake quick review fixes for pairing.",
+        },
+    ),
+    (
+        "first-response",
+        Metric {
+            description: "How long first-time contributors wait for a maintainer to respond, without hosting data.",
+            methodology: "Maintainers are the fewest authors who together made 80% of the commits, everyone else \
+                          is a contributor. The response time is from a contributor's first commit to the next \
+                          commit by a maintainer changing one of the same files. This is a proxy for review: the \
+                          maintainer may have changed the file for another reason.",
+        },
+    ),
+    (
+        "sprints",
+        Metric {
+            description: "Commits, contributors and churn per sprint of the [sprints] config.",
+            methodology: "Churn is the lines added and deleted by the commits dated within the sprint.",
+        },
+    ),
+    (
+        "milestones",
+        Metric {
+            description: "Work delivered towards each milestone, with a burn-up chart.",
+            methodology: "A milestone spans from the day after the previous milestone, or the first commit, until its \
+                          due date or the date of its tag. Merged pull requests are only counted with --github or \
+                          --gitlab.",
+        },
+    ),
+    (
+        "release-cadence",
+        Metric {
+            description: "Days and commits between consecutive tags, and whether releases are getting more or less \
+                          frequent.",
+            methodology: "Every tag counts as a release, dated by the tag for annotated tags and by the commit \
+                          otherwise. Commits are those reachable from the tag but not from the previous one. The \
+                          trend compares the median days between the last 5 releases to the median before them, it \
+                          needs at least 7 releases.",
+        },
+    ),
+    (
+        "code-age",
+        Metric {
+            description: "How long ago the lines that survive at HEAD were last changed.",
+            methodology: "Every text file is blamed at HEAD, ages are the days between the commit that last changed \
+                          a line and HEAD. Moved or reformatted lines count as changed.",
+        },
+    ),
+    (
+        "public-api",
+        Metric {
+            description: "Public items of the Rust library crates at every release.",
+            methodology: "Crates with a src/lib.rs are parsed at each tag. Items count when reachable through public \
+                          modules or re-exported with pub use. Removed items break code that uses them.",
+        },
+    ),
+    (
+        "code-owners",
+        Metric {
+            description: "Owners of the most changed files and directories, and how many changed files are unowned.",
+            methodology: "The last matching CODEOWNERS rule wins. Changes within 90 days of the latest commit are \
+                          recent. Owners are matched to committers by email or login, team owners can't be resolved.",
+        },
+    ),
+    (
+        "defect-prone-files",
+        Metric {
+            description: "Files changed by the most bug fixes, next to the most changed files. Files that are both \
+                          changed often and fixed often are the first candidates for refactoring.",
+            methodology: "A commit is a fix when its subject line matches one of the [fixes] patterns of the config, \
+                          by default Conventional Commit `fix:` types and words like fix, bug or regression. The \
+                          defect density is the share of the commits changing the file that are fixes. Merge commits \
+                          are left out.",
+        },
+    ),
+    (
+        "issues",
+        Metric {
+            description: "Share of commits that reference an issue, and the most referenced issues.",
+            methodology: "Commit messages are matched against the [issues] patterns of the config, a commit \
+                          referencing several issues counts once.",
+        },
+    ),
+    (
+        "hotfixes",
+        Metric {
+            description: "Commits on release branches that never made it back into the main line.",
+            methodology: "Commits on the branches matching --release-branches that aren't reachable from HEAD, by \
+                          month of their commit date. Cherry-picks into the main line have a different hash and \
+                          still count.",
+        },
+    ),
+    (
+        "pull-requests",
+        Metric {
+            description: "Pull requests and issues opened and closed over time, with the time it took.",
+            methodology: "Read from the --github or --gitlab API. Medians are in whole days, open pull requests and \
+                          issues are left out of them.",
+        },
+    ),
+    (
+        "contributors",
+        Metric {
+            description: "Everyone who authored or co-authored a commit.",
+            methodology: "Contributors are identified by author name. Co-authors are credited with \
+                          --co-author-weight of a commit each.",
+        },
+    ),
+    (
+        "affiliations",
+        Metric {
+            description: "Commits and authors per organization.",
+            methodology: "Organizations are derived from the email domain, or from the [affiliations] config which \
+                          also matches subdomains. Authors are counted by email address.",
+        },
+    ),
+    (
+        "team-weeks",
+        Metric {
+            description: "When each team commits during the week, to plan meetings in the hours the teams share. \
+                          Shown when [teams] are configured or with `--team-weeks`.",
+            methodology: "Commits are placed by their weekday and hour in UTC, so teams in different time zones \
+                          line up. Cells are the share of the team's commits. A team is active in the weekday hours \
+                          with at least half of the commits of its busiest weekday hour, shared hours are the ones \
+                          all charted teams are active in. Members of the [teams] config are matched by email or \
+                          name, without it teams are the organizations of the affiliations. Merge commits are left \
+                          out.",
+        },
+    ),
+    (
+        "top-contributors",
+        Metric {
+            description: "The contributors with the most commits.",
+            methodology: "Commits over the whole history, co-authors are credited with --co-author-weight of a \
+                          commit each. This counts commits, not their size or impact.",
+        },
+    ),
+    (
+        "inequality",
+        Metric {
+            description: "How evenly the commits are spread over the authors. A Gini coefficient of 0 means everyone \
+                          made as many commits, 1 means a single author made all of them.",
+            methodology: "Computed from the commits per author, with co-authors credited with --co-author-weight of a \
+                          commit each. The Lorenz curve plots the share of commits of the least active authors, the \
+                          further it sags below the diagonal the more unequal the contributions.",
+        },
+    ),
+    (
+        "languages",
+        Metric {
+            description: "Files and lines per language at HEAD.",
+            methodology: "Counted by tokei, which also counts code embedded in other languages, like code blocks in \
+                          Markdown. Only files tracked by git are counted.",
+        },
+    ),
+    (
+        "author-languages",
+        Metric {
+            description: "Which languages the contributors who changed the most code work in.",
+            methodology: "Lines added plus lines deleted per file, with the language recognised from the file name or \
+                          extension like tokei does. Files in other formats and merge commits are left out.",
+        },
+    ),
+    (
+        "file-types",
+        Metric {
+            description: "All files at HEAD by extension, including binary files and assets.",
+            methodology: "Sizes are the blob sizes in git, not the size on disk after checkout filters.",
+        },
+    ),
+    (
+        "submodules",
+        Metric {
+            description: "Activity and size of each checked out submodule.",
+            methodology: "Only included with --recurse-submodules. Uninitialized submodules are left out.",
+        },
+    ),
+    (
+        "data-files",
+        Metric {
+            description: "Notebooks, data files and model weights, which tokei doesn't count as code.",
+            methodology: "Recognised by extension. Churn is the lines changed as reported by git, which is zero for \
+                          binary files.",
+        },
+    ),
+    (
+        "infrastructure",
+        Metric {
+            description: "Terraform, Kubernetes manifests, Helm charts and Dockerfiles, and their share of all lines.",
+            methodology: "Kubernetes manifests are YAML files with both an apiVersion and a kind, Helm charts are the \
+                          directories with a Chart.yaml.",
+        },
+    ),
+    (
+        "code-size",
+        Metric {
+            description: "Lines of code per language and directory at HEAD.",
+            methodology: "Counted by tokei. Directories deeper than 3 levels are counted towards their ancestor.",
+        },
+    ),
+    (
+        "largest-files",
+        Metric {
+            description: "The files with the most lines of code at HEAD, often the ones that are hardest to change.",
+            methodology: "Counted by tokei, code embedded in other languages counts towards the file. The comment \
+                          ratio is the share of comments in the lines that aren't blank.",
+        },
+    ),
+    (
+        "file-dossiers",
+        Metric {
+            description: "Everything the history tells about the files listed in the tables of the report: how \
+                          often they changed, who changed them, who owns them and which files change with them.",
+            methodology: "Churn counts the commits and lines added and deleted per quarter, merge commits are left \
+                          out. Owners come from the CODEOWNERS file. Coupled files were changed in at least two of the \
+                          same commits, commits changing more than 50 files are left out of the coupling.",
+        },
+    ),
+    (
+        "divergence",
+        Metric {
+            description: "Commits made on one revision since it diverged from the other, by author.",
+            methodology: "Commits reachable from one revision but not from the other, like `git log b..a`. Commits \
+                          picked onto both sides are counted on both.",
+        },
+    ),
+    (
+        "tree-churn",
+        Metric {
+            description: "Lines added and deleted to get from the files of the first revision to the second.",
+            methodology: "Compares the trees of both revisions directly, like `git diff a b`, with renames detected. \
+                          Binary files count as changed files without lines.",
+        },
+    ),
+    (
+        "language-delta",
+        Metric {
+            description: "Change in lines of code, comments and blanks per language from the first revision to the \
+                          second.",
+            methodology: "Counted by tokei in the files that differ between the trees, by the language of their \
+                          path.",
+        },
+    ),
+];
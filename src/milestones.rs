@@ -0,0 +1,154 @@
+use chrono::NaiveDate;
+use plotly::{
+    common::{Mode, Title},
+    layout::Axis,
+    Layout, Plot, Scatter,
+};
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+use crate::config;
+use crate::error::Error;
+use crate::git::{run_git, Commit};
+use crate::hosting::HostingStats;
+use crate::plots::PLOT_WIDTH;
+
+pub struct Milestone {
+    pub name: String,
+    pub due: NaiveDate,
+    /// Day after the previous milestone, or the first commit
+    pub start: NaiveDate,
+    pub commits: usize,
+    pub contributors: usize,
+    /// `None` without GitHub or GitLab data
+    pub merged_pull_requests: Option<usize>,
+    /// Cumulative commits per day with commits, counted from `start`
+    burn_up: BTreeMap<NaiveDate, usize>,
+}
+
+/// Milestones from the config, resolving tags to the date of their commit, or from GitHub or GitLab
+pub fn get_milestone_dates(
+    path: &Path,
+    milestones: &[config::Milestone],
+    hosting: Option<&HostingStats>,
+) -> Result<Vec<(String, NaiveDate)>, Error> {
+    if milestones.is_empty() {
+        return Ok(hosting
+            .map(|stats| {
+                stats
+                    .milestones
+                    .iter()
+                    .map(|milestone| (milestone.title.clone(), milestone.due))
+                    .collect()
+            })
+            .unwrap_or_default());
+    }
+
+    let mut dates = vec![];
+    for milestone in milestones {
+        let date = match (&milestone.date, &milestone.tag) {
+            (Some(date), _) => Some(*date),
+            (None, Some(tag)) => {
+                let date = run_git(path, &["log", "-1", "--format=%as", tag, "--"])?;
+                NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").ok()
+            }
+            (None, None) => None,
+        };
+        match date {
+            Some(date) => dates.push((milestone.name.clone(), date)),
+            None => eprintln!(
+                "Warning: Skipping milestone {}, it needs a date or an existing tag",
+                milestone.name
+            ),
+        }
+    }
+    Ok(dates)
+}
+
+pub fn get_milestones(
+    commits: &[Commit],
+    dates: &[(String, NaiveDate)],
+    hosting: Option<&HostingStats>,
+) -> Vec<Milestone> {
+    let first = commits
+        .iter()
+        .map(|commit| commit.date)
+        .min()
+        .unwrap_or_default();
+    let mut dates = dates.to_vec();
+    dates.sort_by_key(|(_, due)| *due);
+
+    let mut start = first;
+    let mut milestones = vec![];
+    for (name, due) in dates {
+        let in_milestone: Vec<&Commit> = commits
+            .iter()
+            .filter(|commit| start <= commit.date && commit.date <= due)
+            .collect();
+        let contributors: HashSet<&str> = in_milestone
+            .iter()
+            .map(|commit| commit.author.as_str())
+            .collect();
+        let mut per_day: BTreeMap<NaiveDate, usize> = BTreeMap::new();
+        for commit in &in_milestone {
+            *per_day.entry(commit.date).or_insert(0) += 1;
+        }
+        let mut total = 0;
+        let burn_up = per_day
+            .into_iter()
+            .map(|(day, count)| {
+                total += count;
+                (day, total)
+            })
+            .collect();
+
+        milestones.push(Milestone {
+            merged_pull_requests: hosting.map(|stats| {
+                stats
+                    .pull_requests
+                    .iter()
+                    .filter(|pr| {
+                        pr.merged
+                            .is_some_and(|merged| start <= merged && merged <= due)
+                    })
+                    .count()
+            }),
+            name,
+            due,
+            start,
+            commits: in_milestone.len(),
+            contributors: contributors.len(),
+            burn_up,
+        });
+        start = due.succ_opt().unwrap();
+    }
+    milestones
+}
+
+/// Cumulative commits towards every milestone, aligned on the due date so milestones can be compared
+pub fn plot_milestone_burn_up(milestones: &[Milestone]) -> Plot {
+    let mut plot = Plot::new();
+
+    for milestone in milestones {
+        let x: Vec<i64> = milestone
+            .burn_up
+            .keys()
+            .map(|day| (*day - milestone.due).num_days())
+            .collect();
+        let y: Vec<usize> = milestone.burn_up.values().copied().collect();
+        plot.add_trace(
+            Scatter::new(x, y)
+                .mode(Mode::LinesMarkers)
+                .name(&milestone.name),
+        );
+    }
+
+    let layout = Layout::new()
+        .width(PLOT_WIDTH - 50)
+        .title(Title::from("Commits towards each milestone"))
+        .x_axis(Axis::new().title(Title::from("Days until the due date")))
+        .y_axis(Axis::new().title(Title::from("Commits")));
+    plot.set_layout(layout);
+
+    plot
+}
@@ -0,0 +1,137 @@
+use chrono::NaiveDate;
+use plotly::{
+    common::Title,
+    layout::{Axis, BarMode},
+    Bar, Layout, Plot,
+};
+use std::collections::HashMap;
+
+use crate::git::Commit;
+use crate::plots::{Granularity, PLOT_WIDTH};
+
+/// First-time contributors listed, the most recent ones
+const RECENT_NEWCOMERS: usize = 15;
+/// Files of the first commit listed per contributor
+const FIRST_FILES: usize = 3;
+
+/// A contributor and their first commit
+pub struct Newcomer {
+    pub author: String,
+    pub hash: String,
+    pub date: NaiveDate,
+    /// Subject line of the first commit
+    pub subject: String,
+    /// Files changed in the first commit, and how many there were in total
+    pub files: Vec<String>,
+    pub file_count: usize,
+    /// Commits in the whole history
+    pub commits: usize,
+}
+
+pub struct Newcomers {
+    /// Contributors making their first commit per month, and how many of them committed again later
+    pub months: Vec<(NaiveDate, usize, usize)>,
+    /// Most recent first
+    pub recent: Vec<Newcomer>,
+}
+
+/// The first commit of every author, counted per month. `None` for a history by a single author
+pub fn get_newcomers(commits: &[Commit]) -> Option<Newcomers> {
+    let mut first_commits: HashMap<&str, (&Commit, usize)> = HashMap::new();
+    for commit in commits {
+        let (first, count) = first_commits.entry(&commit.author).or_insert((commit, 0));
+        if commit.time < first.time {
+            *first = commit;
+        }
+        *count += 1;
+    }
+    if first_commits.len() < 2 {
+        return None;
+    }
+
+    let mut newcomers: Vec<Newcomer> = first_commits
+        .into_iter()
+        .map(|(author, (first, commits))| Newcomer {
+            author: author.to_string(),
+            hash: first.hash.clone(),
+            date: first.date,
+            subject: first.message.lines().next().unwrap_or_default().to_string(),
+            files: first
+                .files
+                .iter()
+                .take(FIRST_FILES)
+                .map(|file| file.path.clone())
+                .collect(),
+            file_count: first.files.len(),
+            commits,
+        })
+        .collect();
+    newcomers.sort_by(|a, b| b.date.cmp(&a.date).then(a.author.cmp(&b.author)));
+
+    let first = newcomers.iter().map(|newcomer| newcomer.date).min()?;
+    let latest = commits.iter().map(|commit| commit.date).max()?;
+    let mut months: HashMap<NaiveDate, (usize, usize)> = HashMap::new();
+    for newcomer in &newcomers {
+        let month = months
+            .entry(Granularity::Month.bucket(newcomer.date))
+            .or_default();
+        month.0 += 1;
+        month.1 += (newcomer.commits > 1) as usize;
+    }
+    let months = Granularity::Month
+        .periods(first, latest)
+        .into_iter()
+        .map(|month| {
+            let (new, returning) = months.get(&month).copied().unwrap_or_default();
+            (month, new, returning)
+        })
+        .collect();
+
+    newcomers.truncate(RECENT_NEWCOMERS);
+    Some(Newcomers {
+        months,
+        recent: newcomers,
+    })
+}
+
+/// First-time contributors per month, split by whether they committed again
+pub fn plot_newcomers(newcomers: &Newcomers) -> Plot {
+    let mut plot = Plot::new();
+
+    let x: Vec<String> = newcomers
+        .months
+        .iter()
+        .map(|(month, _, _)| month.to_string())
+        .collect();
+    plot.add_trace(
+        Bar::new(
+            x.clone(),
+            newcomers
+                .months
+                .iter()
+                .map(|(_, _, returning)| *returning)
+                .collect(),
+        )
+        .name("Committed again"),
+    );
+    plot.add_trace(
+        Bar::new(
+            x,
+            newcomers
+                .months
+                .iter()
+                .map(|(_, new, returning)| new - returning)
+                .collect(),
+        )
+        .name("Single commit"),
+    );
+
+    let layout = Layout::new()
+        .width(PLOT_WIDTH - 50)
+        .bar_mode(BarMode::Stack)
+        .title(Title::from("First-time contributors per month"))
+        .y_axis(Axis::new().title(Title::from("Contributors")));
+    plot.set_layout(layout);
+
+    plot
+}
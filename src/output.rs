@@ -0,0 +1,26 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Temporary file next to `path`, so renaming it into place stays on the same filesystem
+pub fn temp_path(path: &Path) -> PathBuf {
+    let filename = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{}.{}.tmp", filename, std::process::id()))
+}
+
+/// Write to a temporary file and rename it into place, so readers never see a half-written file
+pub fn write_atomic(path: &Path, content: impl AsRef<[u8]>) -> std::io::Result<()> {
+    let temp = temp_path(path);
+    let result = std::fs::File::create(&temp)
+        .and_then(|mut file| {
+            file.write_all(content.as_ref())?;
+            file.sync_all()
+        })
+        .and_then(|_| std::fs::rename(&temp, path));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    result
+}
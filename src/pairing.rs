@@ -0,0 +1,124 @@
+use chrono::NaiveDate;
+use plotly::{common::Title, Bar, Layout, Plot};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::git::Commit;
+use crate::plots::{Granularity, PLOT_WIDTH};
+
+/// Maximum time between two commits of a pair on the same file, and between the handoffs of a session
+const HANDOFF_WINDOW: i64 = 2 * 60 * 60;
+/// Handoffs needed for a session, so a single review fix by a colleague doesn't count as pairing
+const MIN_HANDOFFS: usize = 2;
+
+/// Two authors who took turns committing to the same files
+pub struct Pair {
+    pub authors: (String, String),
+    pub sessions: usize,
+    pub last_session: NaiveDate,
+}
+
+pub struct Pairing {
+    /// Sessions per week, by the first day of the week
+    pub sessions_per_week: BTreeMap<NaiveDate, usize>,
+    /// Pairs with the most sessions first
+    pub pairs: Vec<Pair>,
+}
+
+/// Detect pairing and mobbing sessions from authors taking turns on the same files within hours of each other,
+/// `None` if there are none. A handoff is a commit to a file by another author than the previous commit to it,
+/// made at most two hours later, and a session is a run of handoffs between the same two authors
+pub fn get_pairing(commits: &[Commit], n: usize) -> Option<Pairing> {
+    let mut per_file: HashMap<&str, Vec<&Commit>> = HashMap::new();
+    for commit in commits.iter().filter(|commit| !commit.is_merge()) {
+        for file in &commit.files {
+            per_file.entry(&file.path).or_default().push(commit);
+        }
+    }
+
+    // a commit changing several files of the previous commit is a single handoff
+    let mut handoffs: HashSet<((&str, &str), &str)> = HashSet::new();
+    let mut times: HashMap<(&str, &str), Vec<(i64, NaiveDate)>> = HashMap::new();
+    for file_commits in per_file.values_mut() {
+        file_commits.sort_by_key(|commit| commit.time);
+        for pair in file_commits.windows(2) {
+            let (previous, commit) = (pair[0], pair[1]);
+            if previous.author == commit.author || commit.time - previous.time > HANDOFF_WINDOW {
+                continue;
+            }
+            let authors = match previous.author < commit.author {
+                true => (previous.author.as_str(), commit.author.as_str()),
+                false => (commit.author.as_str(), previous.author.as_str()),
+            };
+            if handoffs.insert((authors, &commit.hash)) {
+                times
+                    .entry(authors)
+                    .or_default()
+                    .push((commit.time, commit.date));
+            }
+        }
+    }
+
+    let mut sessions_per_week: BTreeMap<NaiveDate, usize> = BTreeMap::new();
+    let mut pairs = vec![];
+    for (authors, mut times) in times {
+        times.sort();
+        let mut sessions = vec![];
+        let mut start = 0;
+        for i in 1..=times.len() {
+            if i == times.len() || times[i].0 - times[i - 1].0 > HANDOFF_WINDOW {
+                if i - start >= MIN_HANDOFFS {
+                    sessions.push(times[start].1);
+                }
+                start = i;
+            }
+        }
+        let Some(last_session) = sessions.last().copied() else {
+            continue;
+        };
+        for date in &sessions {
+            *sessions_per_week
+                .entry(Granularity::Week.bucket(*date))
+                .or_insert(0) += 1;
+        }
+        pairs.push(Pair {
+            authors: (authors.0.to_string(), authors.1.to_string()),
+            sessions: sessions.len(),
+            last_session,
+        });
+    }
+    if pairs.is_empty() {
+        return None;
+    }
+    pairs.sort_by(|a, b| b.sessions.cmp(&a.sessions).then(a.authors.cmp(&b.authors)));
+    pairs.truncate(n);
+
+    Some(Pairing {
+        sessions_per_week,
+        pairs,
+    })
+}
+
+pub fn plot_pairing_sessions(pairing: &Pairing) -> Plot {
+    let mut plot = Plot::new();
+
+    // weeks without sessions are shown as well, so the bars are spaced by time
+    let mut x = vec![];
+    let mut y = vec![];
+    if let (Some(first), Some(last)) = (
+        pairing.sessions_per_week.keys().next(),
+        pairing.sessions_per_week.keys().last(),
+    ) {
+        for week in Granularity::Week.periods(*first, *last) {
+            x.push(week.to_string());
+            y.push(pairing.sessions_per_week.get(&week).copied().unwrap_or(0));
+        }
+    }
+    plot.add_trace(Bar::new(x, y));
+
+    let layout = Layout::new()
+        .width(PLOT_WIDTH / 2)
+        .title(Title::from("Pairing sessions per week"));
+    plot.set_layout(layout);
+
+    plot
+}
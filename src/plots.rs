@@ -0,0 +1,557 @@
+use chrono::{Datelike, Days, Months, NaiveDate};
+use clap::ValueEnum;
+use plotly::{
+    common::{Line, LineShape, Mode, Orientation, Title},
+    layout::{
+        Axis, AxisType, BarMode, Margin, RangeSelector, RangeSlider, SelectorButton, SelectorStep,
+        StepMode, Template,
+    },
+    Bar, Layout, Plot, Scatter, Trace,
+};
+use serde::Serialize;
+use std::cmp::min;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+use crate::config::Theme;
+use crate::git::Commit;
+use crate::i18n::Lang;
+use crate::locale::ReportLocale;
+use crate::themes::BuiltinTheme;
+
+pub const PLOT_WIDTH: usize = 1200;
+/// Height of plots that don't set one, the plotly.js default
+pub const PLOT_HEIGHT: usize = 450;
+const OTHER_AUTHORS: &str = "Other";
+/// Trace colors of plotly.js and its built-in templates
+const PLOTLY_COLORS: [&str; 10] = [
+    "#636efa", "#EF553B", "#00cc96", "#ab63fa", "#FFA15A", "#19d3f3", "#FF6692", "#B6E880",
+    "#FF97FF", "#FECB52",
+];
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Granularity {
+    Day,
+    Week,
+    Month,
+    Quarter,
+    Year,
+}
+
+impl Granularity {
+    /// First day of the period containing `date`, weeks start on Monday
+    pub fn bucket(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Granularity::Day => date,
+            Granularity::Week => date.week(chrono::Weekday::Mon).first_day(),
+            Granularity::Month => date.with_day(1).unwrap(),
+            Granularity::Quarter => {
+                NaiveDate::from_ymd_opt(date.year(), (date.month0() / 3) * 3 + 1, 1).unwrap()
+            }
+            Granularity::Year => NaiveDate::from_ymd_opt(date.year(), 1, 1).unwrap(),
+        }
+    }
+
+    /// First day of the period after the one starting at `bucket`
+    pub fn next(&self, bucket: NaiveDate) -> NaiveDate {
+        match self {
+            Granularity::Day => bucket + Days::new(1),
+            Granularity::Week => bucket + Days::new(7),
+            Granularity::Month => bucket + Months::new(1),
+            Granularity::Quarter => bucket + Months::new(3),
+            Granularity::Year => bucket + Months::new(12),
+        }
+    }
+
+    /// Every period from the one containing `first` to the one containing `last`, including those without commits
+    pub fn periods(&self, first: NaiveDate, last: NaiveDate) -> Vec<NaiveDate> {
+        let mut periods = vec![];
+        let mut period = self.bucket(first);
+        while period <= last {
+            periods.push(period);
+            period = self.next(period);
+        }
+        periods
+    }
+
+    /// Periods covering about three months, the window of the moving average
+    fn periods_per_three_months(&self) -> usize {
+        match self {
+            Granularity::Day => 91,
+            Granularity::Week => 13,
+            Granularity::Month => 3,
+            Granularity::Quarter | Granularity::Year => 1,
+        }
+    }
+}
+
+/// Commits in the three months up to the latest commit and in the three months before
+pub struct Velocity {
+    pub recent: usize,
+    pub previous: usize,
+}
+
+impl Velocity {
+    /// Relative change from the previous period, `None` without commits in the previous period
+    pub fn change(&self) -> Option<f64> {
+        (self.previous > 0).then(|| self.recent as f64 / self.previous as f64 - 1.0)
+    }
+}
+
+pub fn get_velocity(commits: &[Commit]) -> Velocity {
+    let latest = commits
+        .iter()
+        .map(|commit| commit.date)
+        .max()
+        .unwrap_or_default();
+    let recent_since = latest - Months::new(3);
+    let previous_since = recent_since - Months::new(3);
+    Velocity {
+        recent: commits
+            .iter()
+            .filter(|commit| commit.date > recent_since)
+            .count(),
+        previous: commits
+            .iter()
+            .filter(|commit| commit.date > previous_since && commit.date <= recent_since)
+            .count(),
+    }
+}
+
+/// Deeper directories are counted towards their ancestor at this depth, to keep treemaps responsive
+const TREEMAP_DEPTH: usize = 3;
+
+/// Treemap trace, which the plotly crate doesn't provide
+#[derive(Clone, Serialize, Default)]
+pub struct Treemap {
+    r#type: &'static str,
+    ids: Vec<String>,
+    labels: Vec<String>,
+    parents: Vec<String>,
+    values: Vec<usize>,
+    /// Values of parents include their children
+    branchvalues: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    marker: Option<TreemapMarker>,
+}
+
+#[derive(Clone, Serialize)]
+struct TreemapMarker {
+    colors: Vec<usize>,
+    colorscale: &'static str,
+}
+
+impl Treemap {
+    pub fn new() -> Box<Self> {
+        Box::new(Treemap {
+            r#type: "treemap",
+            branchvalues: "total",
+            ..Default::default()
+        })
+    }
+
+    /// Add a node, the root has an empty `parent`
+    pub fn push(&mut self, id: String, label: String, parent: String, value: usize) {
+        self.ids.push(id);
+        self.labels.push(label);
+        self.parents.push(parent);
+        self.values.push(value);
+    }
+
+    /// Add the directories containing `files` below the node `parent`, sized by the values of their files
+    pub fn push_directories<'a>(
+        &mut self,
+        parent: &str,
+        files: impl IntoIterator<Item = (&'a Path, usize)>,
+    ) {
+        // values per directory, including subdirectories
+        let mut directories: BTreeMap<String, usize> = BTreeMap::new();
+        for (path, value) in files {
+            let parts: Vec<String> = path
+                .parent()
+                .into_iter()
+                .flat_map(|parent| parent.components())
+                .take(TREEMAP_DEPTH)
+                .map(|component| component.as_os_str().to_string_lossy().to_string())
+                .collect();
+            for depth in 1..=parts.len() {
+                *directories.entry(parts[..depth].join("/")).or_insert(0) += value;
+            }
+        }
+
+        // sorted by path, so parents are added before their children
+        for (directory, value) in directories {
+            let (directory_parent, label) = match directory.rsplit_once('/') {
+                Some((directory_parent, label)) => {
+                    (format!("{}/{}", parent, directory_parent), label)
+                }
+                None => (parent.to_string(), directory.as_str()),
+            };
+            self.push(
+                format!("{}/{}", parent, directory),
+                label.to_string(),
+                directory_parent,
+                value,
+            );
+        }
+    }
+
+    /// Color the nodes by their value, from yellow to red
+    pub fn heat(&mut self) {
+        self.marker = Some(TreemapMarker {
+            colors: self.values.clone(),
+            colorscale: "YlOrRd",
+        });
+    }
+}
+
+impl Trace for Treemap {
+    fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+}
+
+/// Report wide styling applied to every plot before rendering
+pub struct PlotStyle {
+    pub palette: Vec<String>,
+    pub separators: String,
+    pub template: Option<&'static Template>,
+    /// Fixed width of full width plots and height of plots of the default height, other plots are scaled along.
+    /// Without a width, plots resize with the page
+    pub width: Option<usize>,
+    pub height: Option<usize>,
+    /// Language the titles and trace names are translated to
+    pub lang: Lang,
+}
+
+impl PlotStyle {
+    pub fn new(theme: &Theme, base: BuiltinTheme, locale: &ReportLocale) -> Self {
+        PlotStyle {
+            palette: theme.palette.clone(),
+            separators: locale.plotly_separators(),
+            template: base.plot_template(),
+            width: None,
+            height: None,
+            lang: locale.lang,
+        }
+    }
+
+    /// Trace colors, for plots whose traces share colors
+    pub fn colors(&self) -> Vec<String> {
+        match self.palette.is_empty() {
+            true => PLOTLY_COLORS
+                .iter()
+                .map(|color| color.to_string())
+                .collect(),
+            false => self.palette.clone(),
+        }
+    }
+
+    pub fn sized(self, width: Option<usize>, height: Option<usize>) -> Self {
+        PlotStyle {
+            width,
+            height,
+            ..self
+        }
+    }
+}
+
+/// Apply the report style to a copy of the plot
+pub fn styled(plot: &Plot, style: &PlotStyle) -> Plot {
+    let mut plot = plot.clone();
+    let mut layout = plot.layout().clone().separators(&style.separators);
+    if !style.palette.is_empty() {
+        layout = layout.colorway(style.palette.clone());
+    }
+    if let Some(template) = style.template {
+        layout = layout.template(template);
+    }
+    plot.set_layout(layout);
+    plot
+}
+
+/// The plotly.js bundle shipped with the plotly crate, for reports that have to render without network access
+pub fn bundled_plotly_js() -> String {
+    let mut plot = Plot::new();
+    plot.use_local_plotly();
+    let html = plot.to_html();
+
+    let start_tag = "<script type=\"text/javascript\">";
+    let start = html
+        .find(start_tag)
+        .expect("plotly.js is not bundled with the plotly crate")
+        + start_tag.len();
+    let end = start + html[start..].find("</script>").unwrap();
+    html[start..end].to_string()
+}
+
+/// Translate the title, axis titles and trace names of a figure. Names that aren't in the catalog, like those of
+/// authors and directories, are kept
+fn translate_figure(figure: &mut serde_json::Value, lang: Lang) {
+    let translate = |text: Option<&mut serde_json::Value>| {
+        if let Some(text) = text {
+            if let Some(message) = text.as_str() {
+                *text = lang.translate(message).into();
+            }
+        }
+    };
+    if let Some(layout) = figure["layout"].as_object_mut() {
+        for (key, value) in layout.iter_mut() {
+            if key == "title" {
+                translate(value.get_mut("text"));
+            } else if key.starts_with("xaxis") || key.starts_with("yaxis") {
+                translate(value.pointer_mut("/title/text"));
+            }
+        }
+    }
+    if let Some(traces) = figure["data"].as_array_mut() {
+        for trace in traces {
+            translate(trace.get_mut("name"));
+        }
+    }
+}
+
+/// The styled plot as a plotly figure, with `data` and `layout`. The figure fills the width of its container,
+/// unless the style has a fixed width
+pub fn plot_figure(plot: &Plot, style: &PlotStyle) -> serde_json::Value {
+    let mut figure: serde_json::Value =
+        serde_json::from_str(&styled(plot, style).to_json()).unwrap();
+    translate_figure(&mut figure, style.lang);
+    let layout = figure["layout"].as_object_mut().unwrap();
+    let width = layout.remove("width").and_then(|width| width.as_u64());
+    match style.width {
+        Some(fixed) => {
+            let width = width.unwrap_or(PLOT_WIDTH as u64) * fixed as u64 / PLOT_WIDTH as u64;
+            layout.insert("width".to_string(), width.into());
+        }
+        None => {
+            layout.insert("autosize".to_string(), true.into());
+            figure["config"]["responsive"] = true.into();
+        }
+    }
+    if let Some(fixed) = style.height {
+        let layout = figure["layout"].as_object_mut().unwrap();
+        let height = layout.get("height").and_then(|height| height.as_u64());
+        let height = height.unwrap_or(PLOT_HEIGHT as u64) * fixed as u64 / PLOT_HEIGHT as u64;
+        layout.insert("height".to_string(), height.into());
+    }
+    figure
+}
+
+/// The figure as JSON that can't end the script it is in, with `<`, `>` and `&` escaped like plotly's `json` filter
+/// does. File paths, commit messages and author names end up in the figures
+fn script_json(figure: &serde_json::Value) -> String {
+    figure
+        .to_string()
+        .replace('<', "\\u003c")
+        .replace('>', "\\u003e")
+        .replace('&', "\\u0026")
+}
+
+/// Render a plotly figure as an embeddable div, like `Plot::to_inline_html` does
+pub fn render_figure(name: &str, figure: &serde_json::Value) -> String {
+    format!(
+        "<div id=\"plot-{name}\" class=\"plotly-graph-div\" style=\"height:100%; width:100%;\"></div>\n\
+         <script type=\"text/javascript\">\n    Plotly.newPlot(\"plot-{name}\", {figure});\n</script>",
+        name = name,
+        figure = script_json(figure),
+    )
+}
+
+pub fn count_commits_per_author(commits: &[Commit], co_author_weight: f64) -> Vec<(String, f64)> {
+    // count commits per author using plain vec methods
+    let mut count: HashMap<String, f64> = HashMap::new();
+    for commit in commits {
+        for (author, credit) in commit.credits(co_author_weight) {
+            *count.entry(author.to_string()).or_insert(0.0) += credit;
+        }
+    }
+
+    // sort counts
+    let mut count_vec: Vec<(String, f64)> = count.into_iter().collect();
+    count_vec.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+    count_vec
+}
+
+pub fn plot_commit_history(
+    commits: &[Commit],
+    n: usize,
+    co_author_weight: f64,
+    granularity: Granularity,
+) -> Plot {
+    let mut plot = Plot::new();
+
+    // authors outside of the top n are collapsed into a single "Other" trace
+    let count_vec = count_commits_per_author(commits, co_author_weight);
+    let tail: usize = count_vec.len() - min(count_vec.len(), n);
+    let top_n: HashSet<&str> = count_vec[tail..]
+        .iter()
+        .map(|(author, _)| author.as_str())
+        .collect();
+
+    // do a groupby count per period and author for the commits
+    let mut count: HashMap<&str, HashMap<NaiveDate, f64>> = HashMap::new();
+    for commit in commits {
+        for (author, credit) in commit.credits(co_author_weight) {
+            let author = if top_n.contains(author) {
+                author
+            } else {
+                OTHER_AUTHORS
+            };
+            *count
+                .entry(author)
+                .or_default()
+                .entry(granularity.bucket(commit.date))
+                .or_insert(0.0) += credit;
+        }
+    }
+
+    // every author gets a bar for every period, so periods without commits take up space on the date axis
+    let first = commits
+        .iter()
+        .map(|commit| commit.date)
+        .min()
+        .unwrap_or_default();
+    let last = commits
+        .iter()
+        .map(|commit| commit.date)
+        .max()
+        .unwrap_or_default();
+    let periods = granularity.periods(first, last);
+    // most active authors first, "Other" last
+    let authors = count_vec[tail..]
+        .iter()
+        .rev()
+        .map(|(author, _)| author.as_str())
+        .chain([OTHER_AUTHORS]);
+    for author in authors {
+        let Some(counts) = count.get(author) else {
+            continue;
+        };
+        let x: Vec<String> = periods.iter().map(|period| period.to_string()).collect();
+        let y: Vec<f64> = periods
+            .iter()
+            .map(|period| counts.get(period).copied().unwrap_or(0.0))
+            .collect();
+        plot.add_trace(Bar::new(x, y).name(author));
+    }
+    plot.add_trace(moving_average_trace(commits, co_author_weight, granularity));
+
+    let range_buttons = vec![
+        SelectorButton::new()
+            .count(6)
+            .label("6M")
+            .step(SelectorStep::Month)
+            .step_mode(StepMode::Backward),
+        SelectorButton::new()
+            .count(1)
+            .label("1Y")
+            .step(SelectorStep::Year)
+            .step_mode(StepMode::Backward),
+        SelectorButton::new().label("All").step(SelectorStep::All),
+    ];
+    let layout = Layout::new()
+        .width(PLOT_WIDTH - 50) // make the legend fit in the containing div
+        .bar_mode(BarMode::Stack)
+        .x_axis(
+            Axis::new()
+                .type_(AxisType::Date)
+                .range_selector(RangeSelector::new().buttons(range_buttons))
+                .range_slider(RangeSlider::new().visible(true)),
+        )
+        .title(Title::from("Commit activity per author"));
+    plot.set_layout(layout);
+
+    plot
+}
+
+/// Commits per period averaged over the last three months, to show the trend behind the noisy bars
+fn moving_average_trace(
+    commits: &[Commit],
+    co_author_weight: f64,
+    granularity: Granularity,
+) -> Box<Scatter<String, f64>> {
+    let mut totals: BTreeMap<NaiveDate, f64> = BTreeMap::new();
+    for commit in commits {
+        let credits: f64 = commit
+            .credits(co_author_weight)
+            .iter()
+            .map(|(_, credit)| credit)
+            .sum();
+        *totals.entry(granularity.bucket(commit.date)).or_insert(0.0) += credits;
+    }
+
+    // periods without commits count as zero
+    let mut periods: Vec<(NaiveDate, f64)> = vec![];
+    if let (Some(first), Some(last)) = (totals.keys().next(), totals.keys().last()) {
+        for period in granularity.periods(*first, *last) {
+            periods.push((period, totals.get(&period).copied().unwrap_or(0.0)));
+        }
+    }
+    let window = granularity.periods_per_three_months();
+    let (x, y): (Vec<String>, Vec<f64>) = periods
+        .iter()
+        .enumerate()
+        .map(|(i, (period, _))| {
+            let start = (i + 1).saturating_sub(window);
+            let sum: f64 = periods[start..=i].iter().map(|(_, total)| total).sum();
+            (period.to_string(), sum / (i + 1 - start) as f64)
+        })
+        .unzip();
+
+    Scatter::new(x, y)
+        .mode(Mode::Lines)
+        .line(Line::new().shape(LineShape::Spline).width(3.0))
+        .name("3 month average")
+}
+
+pub fn plot_commit_count_per_author(commits: &[Commit], n: usize, co_author_weight: f64) -> Plot {
+    let mut plot = Plot::new();
+
+    let count_vec = count_commits_per_author(commits, co_author_weight);
+
+    // get top n items
+    let tail: usize = count_vec.len() - min(count_vec.len(), n);
+    let top_n = count_vec[tail..].to_vec();
+
+    let y: Vec<String> = top_n
+        .clone()
+        .into_iter()
+        .map(|(author, _)| author)
+        .collect();
+    let x: Vec<f64> = top_n.clone().into_iter().map(|(_, count)| count).collect();
+
+    let trace = Bar::new(x, y).orientation(Orientation::Horizontal);
+    plot.add_trace(trace);
+    let layout = Layout::new()
+        .width(PLOT_WIDTH / 2)
+        .title(Title::from("Commits per author"))
+        .margin(Margin::new().left(200).right(200));
+    plot.set_layout(layout);
+
+    plot
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn figure_cannot_close_its_script() {
+        let figure = serde_json::json!({
+            "data": [{"labels": ["</script><svg onload=alert(1)>", "a & b"]}],
+        });
+        let html = render_figure("tree", &figure);
+        assert_eq!(html.matches("</script>").count(), 1);
+        assert!(!html.contains("<svg"));
+        assert!(html.contains("\\u003c/script\\u003e\\u003csvg onload=alert(1)\\u003e"));
+        assert!(html.contains("a \\u0026 b"));
+
+        let (_, json) = html.split_once("Plotly.newPlot(\"plot-tree\", ").unwrap();
+        let json = json.strip_suffix(");\n</script>").unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(json).unwrap(),
+            figure
+        );
+    }
+}
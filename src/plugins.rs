@@ -0,0 +1,92 @@
+use serde::Serialize;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::config::Plugin;
+use crate::git::Commit;
+
+/// What a plugin printed, a Plotly figure if it is a JSON object with `data`, HTML otherwise
+pub enum PluginOutput {
+    Html(String),
+    Figure(serde_json::Value),
+}
+
+pub struct PluginSection {
+    pub name: String,
+    pub output: PluginOutput,
+}
+
+/// Data passed to every plugin on stdin
+#[derive(Serialize)]
+struct PluginInput<'a> {
+    generator: String,
+    repository: &'a Path,
+    commits: &'a [Commit],
+}
+
+fn run_plugin(plugin: &Plugin, repo_path: &Path, input: Vec<u8>) -> Result<PluginOutput, String> {
+    let (program, args) = plugin
+        .command
+        .split_first()
+        .ok_or_else(|| "The command is empty".to_string())?;
+    let mut child = Command::new(program)
+        .args(args)
+        .current_dir(repo_path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("Failed to execute {}: {}", program, err))?;
+    // written from another thread, so a plugin printing before it read all of its input can't block
+    let mut stdin = child.stdin.take().unwrap();
+    let writer = std::thread::spawn(move || {
+        // plugins may exit without reading their input
+        let _ = stdin.write_all(&input);
+    });
+    let output = child
+        .wait_with_output()
+        .map_err(|err| format!("Failed to execute {}: {}", program, err))?;
+    let _ = writer.join();
+    if !output.status.success() {
+        return Err(format!(
+            "{} exited with {}: {}",
+            program,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    match serde_json::from_str::<serde_json::Value>(&stdout) {
+        Ok(figure) if figure["data"].is_array() => Ok(PluginOutput::Figure(figure)),
+        _ => Ok(PluginOutput::Html(stdout)),
+    }
+}
+
+/// Run every plugin command in the repository with the commits as JSON on stdin, plugins that fail are skipped with
+/// a warning
+pub fn run_plugins(plugins: &[Plugin], repo_path: &Path, commits: &[Commit]) -> Vec<PluginSection> {
+    let input = serde_json::to_vec(&PluginInput {
+        generator: format!("git-report {}", env!("CARGO_PKG_VERSION")),
+        repository: repo_path,
+        commits,
+    })
+    .unwrap();
+
+    plugins
+        .iter()
+        .filter_map(
+            |plugin| match run_plugin(plugin, repo_path, input.clone()) {
+                Ok(output) => Some(PluginSection {
+                    name: plugin.name.clone(),
+                    output,
+                }),
+                Err(err) => {
+                    eprintln!("Warning: Skipping plugin {:?}: {}", plugin.name, err);
+                    None
+                }
+            },
+        )
+        .collect()
+}
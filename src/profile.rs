@@ -0,0 +1,62 @@
+use std::path::Path;
+
+use crate::config::Limits;
+use crate::error::Error;
+use crate::git::run_git;
+use crate::plots::Granularity;
+
+/// Which analyses to run, scaled down for repositories that would otherwise take hours
+pub struct Profile {
+    /// Reasons the repository is considered too large, empty when running a full analysis
+    pub degraded: Vec<String>,
+}
+
+impl Profile {
+    pub fn is_degraded(&self) -> bool {
+        !self.degraded.is_empty()
+    }
+
+    /// Per file change statistics are the most expensive part of reading the history
+    pub fn with_files(&self) -> bool {
+        !self.is_degraded()
+    }
+
+    pub fn granularity(&self, granularity: Granularity) -> Granularity {
+        if self.is_degraded() {
+            granularity.max(Granularity::Quarter)
+        } else {
+            granularity
+        }
+    }
+}
+
+/// Quickly count commits and tracked files to decide whether the full analysis is feasible
+pub fn get_profile(path: &Path, limits: &Limits, full: bool) -> Result<Profile, Error> {
+    let mut degraded = vec![];
+    if full {
+        return Ok(Profile { degraded });
+    }
+
+    let commits: usize = run_git(path, &["rev-list", "--count", "HEAD"])?
+        .trim()
+        .parse()
+        .unwrap_or(0);
+    if commits > limits.max_commits {
+        degraded.push(format!(
+            "{} commits exceeds the limit of {}",
+            commits, limits.max_commits
+        ));
+    }
+
+    let files = run_git(path, &["ls-tree", "-r", "--name-only", "HEAD"])?
+        .lines()
+        .count();
+    if files > limits.max_files {
+        degraded.push(format!(
+            "{} files exceeds the limit of {}",
+            files, limits.max_files
+        ));
+    }
+
+    Ok(Profile { degraded })
+}
@@ -0,0 +1,275 @@
+use chrono::Local;
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::config::Publish;
+use crate::error::Error;
+use crate::output::write_atomic;
+use crate::text::safe_path;
+
+/// Where `git-report publish` puts the report
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum PublishTarget {
+    /// A local directory, like one served by a web server
+    #[default]
+    Directory,
+    /// A branch of the repository, committed without touching the working tree and pushed
+    GhPages,
+    /// A bucket of S3 or an S3-compatible store, uses curl
+    S3,
+}
+
+/// Files `git-report publish` puts in the target
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum PublishFormat {
+    /// The HTML report, as `index.html`
+    Html,
+    /// The report data like `--export-json` writes it, as `report.json`
+    Json,
+}
+
+impl PublishFormat {
+    pub fn filename(&self) -> &'static str {
+        match self {
+            PublishFormat::Html => "index.html",
+            PublishFormat::Json => "report.json",
+        }
+    }
+
+    fn content_type(&self) -> &'static str {
+        match self {
+            PublishFormat::Html => "text/html; charset=utf-8",
+            PublishFormat::Json => "application/json",
+        }
+    }
+}
+
+/// Publish the written files of the report, returns where they were published to
+pub fn publish(
+    files: &[(PublishFormat, PathBuf)],
+    target: PublishTarget,
+    config: &Publish,
+    repository: &Path,
+) -> Result<String, Error> {
+    // `{date}` in the prefix keeps the report of every day, like `reports/{date}`
+    let prefix = config
+        .prefix
+        .replace("{date}", &Local::now().date_naive().to_string());
+    let prefix = prefix.trim_matches('/');
+    let key = |format: &PublishFormat| match prefix {
+        "" => format.filename().to_string(),
+        _ => format!("{}/{}", prefix, format.filename()),
+    };
+    match target {
+        PublishTarget::Directory => {
+            for (format, file) in files {
+                let path = config.directory.join(key(format));
+                copy(file, &path)?;
+            }
+            Ok(safe_path(&config.directory.join(prefix)))
+        }
+        PublishTarget::GhPages => {
+            let files: Vec<(String, &Path)> = files
+                .iter()
+                .map(|(format, file)| (key(format), file.as_path()))
+                .collect();
+            commit_to_branch(repository, &files, &config.branch, &config.remote)
+        }
+        PublishTarget::S3 => {
+            let bucket = config.bucket.as_deref().ok_or_else(|| {
+                Error::Input(
+                    "Publishing to S3 needs a bucket in the [publish] section of the config"
+                        .to_string(),
+                )
+            })?;
+            let base = format!("{}/{}", config.endpoint.trim_end_matches('/'), bucket);
+            for (format, file) in files {
+                upload(
+                    file,
+                    &format!("{}/{}", base, key(format)),
+                    format.content_type(),
+                    &config.region,
+                )?;
+            }
+            Ok(format!("{}/{}", base, prefix))
+        }
+    }
+}
+
+fn copy(from: &Path, to: &Path) -> Result<(), Error> {
+    let write_error = |source| Error::Write {
+        path: to.to_path_buf(),
+        source,
+    };
+    if let Some(dir) = to.parent() {
+        std::fs::create_dir_all(dir).map_err(write_error)?;
+    }
+    let content = std::fs::read(from)
+        .map_err(|err| Error::Output(format!("Could not read {}: {}", safe_path(from), err)))?;
+    write_atomic(to, content).map_err(write_error)
+}
+
+/// Run git with its own index, so the working tree and the index of the repository are left alone
+fn git_with_index(
+    repository: &Path,
+    index: &Path,
+    envs: &[(&str, &str)],
+    args: &[&str],
+) -> Result<String, Error> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(repository)
+        .env("GIT_INDEX_FILE", index)
+        .envs(envs.iter().copied())
+        .output()
+        .map_err(Error::GitNotFound)?;
+    if !output.status.success() {
+        return Err(Error::Git(format!(
+            "git {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Commit the files on top of the branch, keeping the other files on it, and push it unless `remote` is empty
+fn commit_to_branch(
+    repository: &Path,
+    files: &[(String, &Path)],
+    branch: &str,
+    remote: &str,
+) -> Result<String, Error> {
+    let index = std::env::temp_dir().join(format!("git-report-{}.index", std::process::id()));
+    let result = commit_files(repository, &index, files, branch);
+    let _ = std::fs::remove_file(&index);
+    result?;
+    if remote.is_empty() {
+        return Ok(format!("branch {}", branch));
+    }
+    git_with_index(
+        repository,
+        &index,
+        &[],
+        &["push", "--quiet", remote, &format!("refs/heads/{}", branch)],
+    )?;
+    Ok(format!("branch {} of {}", branch, remote))
+}
+
+fn commit_files(
+    repository: &Path,
+    index: &Path,
+    files: &[(String, &Path)],
+    branch: &str,
+) -> Result<(), Error> {
+    let git = |args: &[&str]| git_with_index(repository, index, &[], args);
+    let branch_ref = format!("refs/heads/{}", branch);
+    let parent = git(&["rev-parse", "--verify", "--quiet", &branch_ref]).ok();
+    match &parent {
+        Some(parent) => git(&["read-tree", parent])?,
+        None => git(&["read-tree", "--empty"])?,
+    };
+    for (key, file) in files {
+        let file = file
+            .canonicalize()
+            .map_err(|err| Error::Output(format!("Could not read {}: {}", safe_path(file), err)))?;
+        let hash = git(&["hash-object", "-w", &file.to_string_lossy()])?;
+        let entry = format!("100644,{},{}", hash, key);
+        git(&["update-index", "--add", "--cacheinfo", &entry])?;
+    }
+    let tree = git(&["write-tree"])?;
+    if let Some(parent) = &parent {
+        if git(&["rev-parse", &format!("{}^{{tree}}", parent)])? == tree {
+            return Ok(());
+        }
+    }
+
+    let head = git(&["rev-parse", "--short", "HEAD"])?;
+    let message = format!("Publish the git-report of {}", head);
+    let mut args = vec!["commit-tree", tree.as_str(), "-m", message.as_str()];
+    if let Some(parent) = &parent {
+        args.extend(["-p", parent.as_str()]);
+    }
+    // commit as git-report where no identity is configured, like on a fresh CI runner
+    let identity = match git(&["var", "GIT_COMMITTER_IDENT"]) {
+        Ok(_) => vec![],
+        Err(_) => vec![
+            ("GIT_AUTHOR_NAME", "git-report"),
+            ("GIT_AUTHOR_EMAIL", "git-report@localhost"),
+            ("GIT_COMMITTER_NAME", "git-report"),
+            ("GIT_COMMITTER_EMAIL", "git-report@localhost"),
+        ],
+    };
+    let commit = git_with_index(repository, index, &identity, &args)?;
+    git(&[
+        "update-ref",
+        "-m",
+        "git-report publish",
+        &branch_ref,
+        &commit,
+        parent.as_deref().unwrap_or_default(),
+    ])?;
+    Ok(())
+}
+
+/// Upload a file with a request signed with the credentials in $AWS_ACCESS_KEY_ID and $AWS_SECRET_ACCESS_KEY
+fn upload(file: &Path, url: &str, content_type: &str, region: &str) -> Result<(), Error> {
+    let credential = |name: &str| {
+        std::env::var(name).map_err(|_| {
+            Error::Input(format!(
+                "Publishing to S3 needs the credentials in ${}",
+                name
+            ))
+        })
+    };
+    let key_id = credential("AWS_ACCESS_KEY_ID")?;
+    let secret = credential("AWS_SECRET_ACCESS_KEY")?;
+
+    let mut command = Command::new("curl");
+    command
+        .args(["--silent", "--show-error", "--fail"])
+        .arg("--aws-sigv4")
+        .arg(format!("aws:amz:{}:s3", region))
+        .arg("--header")
+        .arg(format!("Content-Type: {}", content_type));
+    // the credentials and session token go to curl as config on stdin, so they don't show up in the process list
+    let mut child = command
+        .args(["--config", "-", "--upload-file"])
+        .arg(file)
+        .arg(url)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| {
+            Error::Service(format!(
+                "Failed to execute curl, is it installed? ({})",
+                err
+            ))
+        })?;
+    let mut stdin = child.stdin.take().unwrap();
+    let mut config = format!("user = \"{}:{}\"\n", key_id, secret);
+    if let Ok(token) = std::env::var("AWS_SESSION_TOKEN") {
+        config.push_str(&format!("header = \"x-amz-security-token: {}\"\n", token));
+    }
+    stdin
+        .write_all(config.as_bytes())
+        .map_err(|err| Error::Service(format!("Failed to upload to {}: {}", url, err)))?;
+    drop(stdin);
+    let output = child
+        .wait_with_output()
+        .map_err(|err| Error::Service(format!("Failed to execute curl: {}", err)))?;
+    if !output.status.success() {
+        return Err(Error::Service(format!(
+            "Upload to {} failed: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(())
+}
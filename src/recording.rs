@@ -0,0 +1,207 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+
+use crate::config::CONFIG_FILENAME;
+use crate::error::Error;
+use crate::git::run_git;
+use crate::languages::{rebase_languages, Language, LanguageType};
+use crate::output::write_atomic;
+use crate::text::safe_path;
+
+const MANIFEST_FILENAME: &str = "recording.json";
+const HISTORY_FILENAME: &str = "history.fi";
+const LANGUAGES_FILENAME: &str = "languages.json";
+const RESPONSES_FILENAME: &str = "responses.json";
+/// Ref the stream keeps HEAD under, for recordings made on a detached HEAD
+const HEAD_REF: &str = "refs/git-report/head";
+
+/// What a recording was made of, so it replays without passing the same options again
+#[derive(Serialize, Deserialize)]
+pub struct Manifest {
+    /// Path or URL of the recorded repository, as shown in the report
+    pub path: String,
+    /// Branch HEAD pointed to, `None` for a detached HEAD
+    pub branch: Option<String>,
+    pub github: Option<String>,
+    pub gitlab: Option<String>,
+    pub gitlab_url: Option<String>,
+    /// The line counts are only recorded for these directories
+    pub path_scope: Vec<String>,
+}
+
+impl Manifest {
+    /// The ref to check out when replaying
+    pub fn head(&self) -> &str {
+        self.branch.as_deref().unwrap_or(HEAD_REF)
+    }
+}
+
+/// API responses by URL, kept with `--record` or answered from with `--replay`
+enum Responses {
+    Record(BTreeMap<String, Value>),
+    Replay(BTreeMap<String, Value>),
+}
+
+static RESPONSES: Mutex<Option<Responses>> = Mutex::new(None);
+
+/// Keep the responses of the API requests of this run, to write them into the recording
+pub fn record_responses() {
+    *RESPONSES.lock().unwrap() = Some(Responses::Record(BTreeMap::new()));
+}
+
+/// Keep a response of an API request when recording
+pub fn record_response(url: &str, response: &Value) {
+    if let Some(Responses::Record(responses)) = RESPONSES.lock().unwrap().as_mut() {
+        responses.insert(url.to_string(), response.clone());
+    }
+}
+
+/// The recorded response to an API request when replaying, the request fails when it wasn't recorded
+pub fn replayed_response(url: &str) -> Option<Result<Value, String>> {
+    match RESPONSES.lock().unwrap().as_ref() {
+        Some(Responses::Replay(responses)) => Some(
+            responses
+                .get(url)
+                .cloned()
+                .ok_or_else(|| format!("The recording has no response to {}", url)),
+        ),
+        _ => None,
+    }
+}
+
+fn read_json<T: DeserializeOwned>(path: &Path) -> Result<T, Error> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|err| Error::Input(format!("Could not read {}: {}", safe_path(path), err)))?;
+    serde_json::from_str(&content)
+        .map_err(|err| Error::Input(format!("Invalid recording {}: {}", safe_path(path), err)))
+}
+
+fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), Error> {
+    let json = serde_json::to_string_pretty(value).expect("recordings serialize to JSON");
+    write_atomic(path, json).map_err(|source| Error::Write {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// Record the inputs of the report into `dir`: the history with `git fast-export`, the config, the line counts
+/// and the API responses, so `--replay` makes the same report without the repository
+pub fn write_recording(
+    dir: &Path,
+    repo_path: &Path,
+    mut manifest: Manifest,
+    config: Option<&Path>,
+    languages: Option<&[(LanguageType, Language)]>,
+) -> Result<(), Error> {
+    std::fs::create_dir_all(dir).map_err(|source| Error::Write {
+        path: dir.to_path_buf(),
+        source,
+    })?;
+
+    let branch = run_git(repo_path, &["symbolic-ref", "--quiet", "HEAD"])?;
+    manifest.branch = Some(branch.trim().to_string()).filter(|branch| !branch.is_empty());
+    let history = dir.join(HISTORY_FILENAME);
+    let stream = File::create(&history).map_err(|source| Error::Write {
+        path: history.clone(),
+        source,
+    })?;
+    // signatures can't be imported without the keys, and the stream is always UTF-8
+    let output = Command::new("git")
+        .args([
+            "fast-export",
+            "--all",
+            "--signed-tags=strip",
+            "--reencode=yes",
+            "--refspec",
+            &format!("HEAD:{}", HEAD_REF),
+            "HEAD",
+        ])
+        .current_dir(repo_path)
+        .stdout(stream)
+        .output()
+        .map_err(Error::GitNotFound)?;
+    if !output.status.success() {
+        return Err(Error::Git(format!(
+            "git fast-export failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    if let Some(config) = config {
+        let target = dir.join(CONFIG_FILENAME);
+        std::fs::copy(config, &target).map_err(|source| Error::Write {
+            path: target,
+            source,
+        })?;
+    }
+    // file names relative to the repository, the replay checks it out elsewhere
+    if let Some(languages) = languages {
+        let mut languages = languages.to_vec();
+        rebase_languages(&mut languages, repo_path, Path::new(""));
+        write_json(&dir.join(LANGUAGES_FILENAME), &languages)?;
+    }
+    if let Some(Responses::Record(responses)) = RESPONSES.lock().unwrap().as_ref() {
+        write_json(&dir.join(RESPONSES_FILENAME), responses)?;
+    }
+    write_json(&dir.join(MANIFEST_FILENAME), &manifest)
+}
+
+/// A recording made with `--record`
+pub struct Recording {
+    dir: PathBuf,
+    pub manifest: Manifest,
+}
+
+impl Recording {
+    /// Open the recording in `dir`, later API requests are answered from its responses
+    pub fn open(dir: &Path) -> Result<Recording, Error> {
+        let manifest = read_json(&dir.join(MANIFEST_FILENAME))?;
+        let responses_path = dir.join(RESPONSES_FILENAME);
+        let responses = match responses_path.exists() {
+            true => read_json(&responses_path)?,
+            false => BTreeMap::new(),
+        };
+        *RESPONSES.lock().unwrap() = Some(Responses::Replay(responses));
+        Ok(Recording {
+            dir: dir.to_path_buf(),
+            manifest,
+        })
+    }
+
+    pub fn history(&self) -> PathBuf {
+        self.dir.join(HISTORY_FILENAME)
+    }
+
+    /// The recorded config, if the recorded run had one
+    pub fn config(&self) -> Option<PathBuf> {
+        Some(self.dir.join(CONFIG_FILENAME)).filter(|path| path.exists())
+    }
+
+    /// The recorded line counts with the files under `repo_path`, `None` if the recorded run didn't count them or
+    /// counted them for other directories
+    pub fn languages(
+        &self,
+        repo_path: &Path,
+        scopes: &[String],
+    ) -> Option<Vec<(LanguageType, Language)>> {
+        let path = self.dir.join(LANGUAGES_FILENAME);
+        if !path.exists() || self.manifest.path_scope != scopes {
+            return None;
+        }
+        match read_json::<Vec<(LanguageType, Language)>>(&path) {
+            Ok(mut languages) => {
+                rebase_languages(&mut languages, Path::new(""), repo_path);
+                Some(languages)
+            }
+            Err(err) => {
+                eprintln!("Warning: Counting the lines again, {}", err);
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use chrono::NaiveDate;
+use plotly::{
+    common::{AxisSide, Mode, Title},
+    layout::Axis,
+    Bar, Layout, Plot, Scatter,
+};
+use std::path::Path;
+
+use crate::error::Error;
+use crate::git::{run_git, Tag};
+use crate::plots::PLOT_WIDTH;
+use crate::sizes::percentile;
+
+/// Number of latest releases whose intervals are compared to the ones before them for the trend
+const RECENT_RELEASES: usize = 5;
+
+pub struct Release {
+    pub tag: String,
+    pub date: NaiveDate,
+    /// Days since the previous release, `None` for the first one
+    pub days: Option<u32>,
+    /// Commits since the previous release, or since the start for the first one
+    pub commits: u32,
+}
+
+pub struct ReleaseCadence {
+    pub releases: Vec<Release>,
+    pub median_days: u32,
+    pub median_commits: u32,
+    /// Median days between the latest releases and between the releases before them, `None` without enough
+    /// releases to compare
+    pub recent_median_days: Option<u32>,
+    pub earlier_median_days: Option<u32>,
+}
+
+fn median(values: impl Iterator<Item = u32>) -> Option<u32> {
+    let mut values: Vec<u32> = values.collect();
+    if values.is_empty() {
+        return None;
+    }
+    values.sort();
+    Some(percentile(&values, 50))
+}
+
+/// Time and commits between consecutive tags, `None` with fewer than two tags. Non-empty `scopes` only count the
+/// commits changing files under them
+pub fn get_release_cadence(
+    path: &Path,
+    tags: &[Tag],
+    scopes: &[String],
+) -> Result<Option<ReleaseCadence>, Error> {
+    if tags.len() < 2 {
+        return Ok(None);
+    }
+    let mut releases: Vec<Release> = vec![];
+    let mut previous: Option<&Tag> = None;
+    for tag in tags {
+        let range = match previous {
+            Some(previous) => format!("{}..{}", previous.commit, tag.commit),
+            None => tag.commit.clone(),
+        };
+        let mut args = vec!["rev-list", "--count", &range, "--"];
+        args.extend(scopes.iter().map(String::as_str));
+        let commits = run_git(path, &args)?.trim().parse().unwrap_or(0);
+        releases.push(Release {
+            tag: tag.name.clone(),
+            date: tag.date,
+            days: previous.map(|previous| (tag.date - previous.date).num_days().max(0) as u32),
+            commits,
+        });
+        previous = Some(tag);
+    }
+
+    let intervals: Vec<u32> = releases.iter().filter_map(|release| release.days).collect();
+    // the trend needs at least two intervals on either side to mean anything
+    let (recent_median_days, earlier_median_days) = match intervals.len() >= RECENT_RELEASES + 2 {
+        true => {
+            let (earlier, recent) = intervals.split_at(intervals.len() - RECENT_RELEASES);
+            (
+                median(recent.iter().copied()),
+                median(earlier.iter().copied()),
+            )
+        }
+        false => (None, None),
+    };
+    let (Some(median_days), Some(median_commits)) = (
+        median(intervals.iter().copied()),
+        // the first release contains all the history before it
+        median(releases.iter().skip(1).map(|release| release.commits)),
+    ) else {
+        return Ok(None);
+    };
+    Ok(Some(ReleaseCadence {
+        median_days,
+        median_commits,
+        recent_median_days,
+        earlier_median_days,
+        releases,
+    }))
+}
+
+/// Commits per release as bars, with the days since the previous release on a second axis
+pub fn plot_release_cadence(cadence: &ReleaseCadence) -> Plot {
+    let mut plot = Plot::new();
+
+    let releases = &cadence.releases[1..];
+    let x: Vec<String> = releases.iter().map(|release| release.tag.clone()).collect();
+    let commits: Vec<u32> = releases.iter().map(|release| release.commits).collect();
+    let days: Vec<u32> = releases
+        .iter()
+        .map(|release| release.days.unwrap_or(0))
+        .collect();
+    plot.add_trace(Bar::new(x.clone(), commits).name("Commits"));
+    plot.add_trace(
+        Scatter::new(x, days)
+            .mode(Mode::LinesMarkers)
+            .name("Days since previous release")
+            .y_axis("y2"),
+    );
+
+    let layout = Layout::new()
+        .width(PLOT_WIDTH - 50)
+        .title(Title::from("Commits and days between releases"))
+        .y_axis(Axis::new().title(Title::from("Commits")))
+        .y_axis2(
+            Axis::new()
+                .title(Title::from("Days"))
+                .overlaying("y")
+                .side(AxisSide::Right),
+        );
+    plot.set_layout(layout);
+
+    plot
+}
@@ -0,0 +1,87 @@
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::cancel::{forget_temp_dir, remove_on_exit};
+
+/// A clone of a remote repository in a temporary directory, which is removed when it's dropped
+pub struct TempClone {
+    /// Shown in place of the path, the URL or the imported fast-export file
+    pub url: String,
+    pub path: PathBuf,
+}
+
+impl Drop for TempClone {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+        forget_temp_dir(&self.path);
+    }
+}
+
+/// Whether the argument is a URL like `https://github.com/foo/bar` or `git@github.com:foo/bar.git` rather than a
+/// local path
+pub fn is_remote_url(arg: &str) -> bool {
+    if Path::new(arg).exists() {
+        return false;
+    }
+    if arg.contains("://") {
+        return true;
+    }
+    // scp-like syntax, a colon before the first slash. Single letters are drive letters on Windows
+    match arg.split_once(':') {
+        Some((host, _)) => host.len() > 1 && !host.contains('/'),
+        None => false,
+    }
+}
+
+/// The URL without a user name or token, for showing it in messages and in the report
+fn without_credentials(url: &str) -> String {
+    let Some((scheme, rest)) = url.split_once("://") else {
+        return url.to_string();
+    };
+    let authority = rest.split('/').next().unwrap_or_default();
+    match authority.rsplit_once('@') {
+        Some((_, host)) => format!("{}://{}{}", scheme, host, &rest[authority.len()..]),
+        None => url.to_string(),
+    }
+}
+
+/// Clone `url` into a temporary directory, `depth` makes a shallow clone and `partial` leaves out file contents,
+/// which git downloads when they are needed
+pub fn clone_remote(url: &str, depth: Option<u32>, partial: bool) -> Result<TempClone, String> {
+    let name = url
+        .trim_end_matches('/')
+        .trim_end_matches(".git")
+        .rsplit(['/', ':'])
+        .next()
+        .unwrap_or_default();
+    let path = std::env::temp_dir().join(format!("git-report-{}-{}", std::process::id(), name));
+    // registered before cloning, so an aborted clone is removed as well
+    remove_on_exit(&path);
+    eprintln!(
+        "Cloning {} into a temporary directory",
+        without_credentials(url)
+    );
+    let clone = TempClone {
+        url: without_credentials(url),
+        path,
+    };
+
+    let mut command = Command::new("git");
+    command.arg("clone");
+    if let Some(depth) = depth {
+        command.arg(format!("--depth={}", depth));
+    }
+    if partial {
+        command.arg("--filter=blob:none");
+    }
+    let status = command
+        .args(["--", url])
+        .arg(&clone.path)
+        .stdout(Stdio::null())
+        .status()
+        .map_err(|err| format!("Failed to execute git clone: {}", err))?;
+    if !status.success() {
+        return Err(format!("Could not clone {}", without_credentials(url)));
+    }
+    Ok(clone)
+}
@@ -0,0 +1,147 @@
+use chrono::NaiveDate;
+use minijinja::{context, value::Kwargs, AutoEscape, Environment, HtmlEscape, State, Value};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::error::Error;
+use crate::highlights::Highlights;
+use crate::locale::ReportLocale;
+use crate::plots::{bundled_plotly_js, render_figure};
+use crate::styles::ReportStyle;
+use crate::text::safe_path;
+
+pub static TEMPLATE: &str = include_str!("../templates/index.html");
+
+/// Render a template, which can extend the built-in report template as `index.html`
+pub fn render_template(
+    name: &str,
+    source: &str,
+    ctx: Value,
+    locale: ReportLocale,
+    highlights: &Highlights,
+) -> Result<String, Error> {
+    let mut env = Environment::new();
+    add_locale_filters(&mut env, locale);
+    let highlights = highlights.clone();
+    // color of a highlighted table cell, none if its value is within the configured limits
+    env.add_function("highlight", move |column: String, value: f64| {
+        highlights.color(&column, value)
+    });
+    env.add_template("index.html", TEMPLATE)
+        .and_then(|_| env.add_template(name, source))
+        .and_then(|_| env.get_template(name)?.render(ctx))
+        .map_err(|err| Error::Render {
+            name: name.to_string(),
+            source: err,
+        })
+}
+
+/// Register the `number`, `percent`, `bytes` and `date` filters, formatting values for the report locale, and the
+/// `t` function and `translate` filter translating text to the report language
+fn add_locale_filters(env: &mut Environment, locale: ReportLocale) {
+    let locale = Arc::new(locale);
+
+    // text of the template, which may contain markup, with `{name}` replaced by the keyword argument `name`
+    let lang = locale.lang;
+    env.add_function(
+        "t",
+        move |state: &State, message: String, args: Kwargs| -> Result<Value, minijinja::Error> {
+            let mut text = lang.translate(&message).to_string();
+            for name in args.args() {
+                let value: Value = args.get(name)?;
+                let value = match state.auto_escape() {
+                    AutoEscape::Html if !value.is_safe() => {
+                        HtmlEscape(&value.to_string()).to_string()
+                    }
+                    _ => value.to_string(),
+                };
+                text = text.replace(&format!("{{{}}}", name), &value);
+            }
+            Ok(Value::from_safe_string(text))
+        },
+    );
+    // text from the report data, like the kind of a notable event, escaped like any other value
+    env.add_filter("translate", move |message: String| {
+        lang.translate(&message).to_string()
+    });
+
+    let numbers = locale.clone();
+    env.add_filter(
+        "number",
+        move |value: Value, decimals: Option<usize>| match (value.as_i64(), decimals) {
+            (Some(n), None) => numbers.format_int(n),
+            _ => numbers.format_float(
+                f64::try_from(value).unwrap_or(f64::NAN),
+                decimals.unwrap_or(1),
+            ),
+        },
+    );
+
+    let percentages = locale.clone();
+    env.add_filter("percent", move |value: f64, decimals: Option<usize>| {
+        percentages.format_percent(value, decimals.unwrap_or(0))
+    });
+
+    let sizes = locale.clone();
+    env.add_filter("bytes", move |value: u64| sizes.format_bytes(value));
+
+    env.add_filter(
+        "date",
+        move |value: String| match NaiveDate::parse_from_str(&value, "%Y-%m-%d") {
+            Ok(date) => locale.format_date(date),
+            Err(_) => value,
+        },
+    );
+}
+
+/// Name and source of the custom template, or of the template of the built-in style
+pub fn load_template(
+    template: Option<&PathBuf>,
+    style: ReportStyle,
+) -> Result<(String, String), Error> {
+    match template {
+        Some(path) => Ok((
+            path.file_name().map_or("template".into(), |name| {
+                name.to_string_lossy().into_owned()
+            }),
+            std::fs::read_to_string(path).map_err(|err| {
+                Error::Input(format!(
+                    "Could not read template {}: {}",
+                    safe_path(path),
+                    err
+                ))
+            })?,
+        )),
+        None => {
+            let (name, source) = style.template();
+            Ok((name.to_string(), source.to_string()))
+        }
+    }
+}
+
+/// Render the report page from its data and the plotly figures of its plots
+pub fn render_report(
+    (template_name, template_source): &(String, String),
+    ctx: &Value,
+    figures: &serde_json::Map<String, serde_json::Value>,
+    offline: bool,
+    locale: ReportLocale,
+    highlights: &Highlights,
+) -> Result<String, Error> {
+    let report_ctx = context! {
+        ..context! { plotly_js => offline.then(bundled_plotly_js) },
+        ..Value::from_iter(figures.iter().map(|(name, figure)| {
+            (format!("{}_plot", name), render_figure(name, figure))
+        })),
+        // the plotly figures behind the rendered plots, for custom templates
+        ..context! { figures => Value::from_serialize(figures) },
+        ..ctx.clone()
+    };
+    render_template(
+        template_name,
+        template_source,
+        report_ctx,
+        locale,
+        highlights,
+    )
+}
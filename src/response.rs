@@ -0,0 +1,175 @@
+use chrono::NaiveDate;
+use plotly::{
+    common::{Mode, Title},
+    layout::Axis,
+    Layout, Plot, Scatter,
+};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::git::Commit;
+use crate::inequality::get_inequality;
+use crate::plots::{count_commits_per_author, Granularity, PLOT_WIDTH};
+use crate::sizes::percentile;
+
+const SECONDS_PER_DAY: f64 = 24.0 * 60.0 * 60.0;
+
+/// The first commit of a contributor outside the maintainers
+pub struct FirstContribution {
+    pub author: String,
+    pub date: NaiveDate,
+    /// Days until a maintainer committed to one of the same files, `None` if none did
+    pub days: Option<f64>,
+}
+
+pub struct FirstResponses {
+    /// Authors who together made 80% of the commits
+    pub maintainers: usize,
+    /// Oldest first
+    pub contributions: Vec<FirstContribution>,
+    pub median_days: Option<f64>,
+}
+
+impl FirstResponses {
+    pub fn answered(&self) -> usize {
+        self.contributions
+            .iter()
+            .filter(|contribution| contribution.days.is_some())
+            .count()
+    }
+}
+
+fn median_days(days: impl Iterator<Item = f64>) -> Option<f64> {
+    // whole minutes are precise enough and can be sorted without caring about NaN
+    let mut minutes: Vec<u32> = days.map(|days| (days * 24.0 * 60.0) as u32).collect();
+    if minutes.is_empty() {
+        return None;
+    }
+    minutes.sort();
+    Some(percentile(&minutes, 50) as f64 / 24.0 / 60.0)
+}
+
+/// Time from the first commit of every contributor outside the maintainers to the next commit by a maintainer
+/// touching one of the same files, as a proxy for the time to a first review without hosting data. `None` if
+/// there are no such contributors
+pub fn get_first_responses(commits: &[Commit], co_author_weight: f64) -> Option<FirstResponses> {
+    let counts = count_commits_per_author(commits, co_author_weight);
+    let core = get_inequality(&counts).pareto_authors;
+    let maintainers: HashSet<&str> = counts
+        .iter()
+        .rev()
+        .take(core)
+        .map(|(author, _)| author.as_str())
+        .collect();
+
+    let mut first_commits: HashMap<&str, &Commit> = HashMap::new();
+    // times of the maintainers' commits per file, sorted
+    let mut maintained: HashMap<&str, Vec<i64>> = HashMap::new();
+    for commit in commits.iter().filter(|commit| !commit.is_merge()) {
+        if maintainers.contains(commit.author.as_str()) {
+            for file in &commit.files {
+                maintained.entry(&file.path).or_default().push(commit.time);
+            }
+            continue;
+        }
+        let first = first_commits.entry(&commit.author).or_insert(commit);
+        if commit.time < first.time {
+            *first = commit;
+        }
+    }
+    if first_commits.is_empty() {
+        return None;
+    }
+    for times in maintained.values_mut() {
+        times.sort();
+    }
+
+    let mut contributions: Vec<FirstContribution> = first_commits
+        .into_iter()
+        .map(|(author, commit)| {
+            let response = commit
+                .files
+                .iter()
+                .filter_map(|file| {
+                    let times = maintained.get(file.path.as_str())?;
+                    times
+                        .get(times.partition_point(|time| *time <= commit.time))
+                        .copied()
+                })
+                .min();
+            FirstContribution {
+                author: author.to_string(),
+                date: commit.date,
+                days: response.map(|time| (time - commit.time) as f64 / SECONDS_PER_DAY),
+            }
+        })
+        .collect();
+    contributions.sort_by(|a, b| a.date.cmp(&b.date).then(a.author.cmp(&b.author)));
+
+    Some(FirstResponses {
+        maintainers: core,
+        median_days: median_days(
+            contributions
+                .iter()
+                .filter_map(|contribution| contribution.days),
+        ),
+        contributions,
+    })
+}
+
+/// Days to the first response for every first contribution, with the median per quarter as the trend
+pub fn plot_first_responses(responses: &FirstResponses) -> Plot {
+    let mut plot = Plot::new();
+
+    let answered: Vec<&FirstContribution> = responses
+        .contributions
+        .iter()
+        .filter(|contribution| contribution.days.is_some())
+        .collect();
+    plot.add_trace(
+        Scatter::new(
+            answered
+                .iter()
+                .map(|contribution| contribution.date.to_string())
+                .collect(),
+            answered
+                .iter()
+                .filter_map(|contribution| contribution.days)
+                .collect(),
+        )
+        .mode(Mode::Markers)
+        .text_array(
+            answered
+                .iter()
+                .map(|contribution| contribution.author.clone())
+                .collect(),
+        )
+        .name("First contribution"),
+    );
+
+    let mut quarters: BTreeMap<NaiveDate, Vec<f64>> = BTreeMap::new();
+    for contribution in &answered {
+        quarters
+            .entry(Granularity::Quarter.bucket(contribution.date))
+            .or_default()
+            .extend(contribution.days);
+    }
+    let (x, y): (Vec<String>, Vec<f64>) = quarters
+        .into_iter()
+        .filter_map(|(quarter, days)| Some((quarter.to_string(), median_days(days.into_iter())?)))
+        .unzip();
+    plot.add_trace(
+        Scatter::new(x, y)
+            .mode(Mode::LinesMarkers)
+            .name("Median per quarter"),
+    );
+
+    let layout = Layout::new()
+        .width(PLOT_WIDTH - 50)
+        .title(Title::from(
+            "Days from a first contribution to a maintainer's response",
+        ))
+        .y_axis(Axis::new().title(Title::from("Days")));
+    plot.set_layout(layout);
+
+    plot
+}
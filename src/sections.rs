@@ -0,0 +1,88 @@
+use clap::ValueEnum;
+
+/// Report sections that can be selected with `--sections` or left out with `--skip`
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Section {
+    Activity,
+    Authors,
+    AuthorLanguages,
+    Tenure,
+    ActiveContributors,
+    NewContributors,
+    Forecast,
+    Streaks,
+    AfterHours,
+    Tone,
+    Merges,
+    Branches,
+    NotableEvents,
+    SensitiveChanges,
+    Signatures,
+    CommitSizes,
+    Squash,
+    Pairing,
+    FirstResponse,
+    Sprints,
+    CodeOwners,
+    DefectProneFiles,
+    Issues,
+    Hotfixes,
+    Affiliations,
+    TeamWeeks,
+    Hosting,
+    Milestones,
+    Releases,
+    BlameAge,
+    ApiSurface,
+    Languages,
+    Extensions,
+    DataFiles,
+    Submodules,
+    Infrastructure,
+    FileDossiers,
+    Plugins,
+}
+
+pub struct Sections {
+    /// `None` selects every section
+    selected: Option<Vec<Section>>,
+    /// Opt-in sections that are turned on, by `--sections` or the style
+    requested: Vec<Section>,
+    skipped: Vec<Section>,
+}
+
+impl Sections {
+    pub fn new(
+        selected: Option<Vec<Section>>,
+        requested: Vec<Section>,
+        skipped: Vec<Section>,
+    ) -> Self {
+        Sections {
+            selected,
+            requested,
+            skipped,
+        }
+    }
+
+    pub fn enabled(&self, section: Section) -> bool {
+        let selected = self
+            .selected
+            .as_ref()
+            .is_none_or(|selected| selected.contains(&section));
+        selected && !self.skipped.contains(&section)
+    }
+
+    /// Whether an opt-in section is turned on
+    pub fn requested(&self, section: Section) -> bool {
+        self.requested.contains(&section) && !self.skipped.contains(&section)
+    }
+
+    /// Compute a section only if it is enabled, so skipped sections don't cost any time
+    pub fn run<T>(&self, section: Section, f: impl FnOnce() -> Option<T>) -> Option<T> {
+        if self.enabled(section) {
+            f()
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use chrono::NaiveDate;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+
+use crate::git::Commit;
+
+/// Days before the latest commit audited when no start date is given
+pub const DEFAULT_AUDIT_DAYS: i64 = 90;
+
+/// A commit changing files that match the sensitive path patterns
+pub struct SensitiveChange {
+    pub hash: String,
+    pub date: NaiveDate,
+    pub author: String,
+    /// Lines added and deleted in the sensitive files
+    pub lines_added: u32,
+    pub lines_deleted: u32,
+    /// The sensitive files it changed
+    pub files: Vec<String>,
+}
+
+pub fn compile_sensitive_patterns(patterns: &[String]) -> Result<GlobSet, String> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(
+            Glob::new(pattern)
+                .map_err(|err| format!("Invalid sensitive path pattern {:?}: {}", pattern, err))?,
+        );
+    }
+    builder
+        .build()
+        .map_err(|err| format!("Invalid sensitive path patterns: {}", err))
+}
+
+/// Every commit since `since` changing sensitive files, newest first. Merge commits are left out, their changes are
+/// listed with the commits they merge
+pub fn get_sensitive_changes(
+    commits: &[Commit],
+    patterns: &GlobSet,
+    since: NaiveDate,
+) -> Vec<SensitiveChange> {
+    let mut changes: Vec<SensitiveChange> = commits
+        .iter()
+        .filter(|commit| commit.date >= since && !commit.is_merge())
+        .filter_map(|commit| {
+            let files: Vec<_> = commit
+                .files
+                .iter()
+                .filter(|file| patterns.is_match(&file.path))
+                .collect();
+            if files.is_empty() {
+                return None;
+            }
+            Some(SensitiveChange {
+                hash: commit.hash.clone(),
+                date: commit.date,
+                author: commit.author.clone(),
+                lines_added: files.iter().filter_map(|file| file.added).sum(),
+                lines_deleted: files.iter().filter_map(|file| file.deleted).sum(),
+                files: files.iter().map(|file| file.path.clone()).collect(),
+            })
+        })
+        .collect();
+    changes.sort_by_key(|change| std::cmp::Reverse(change.date));
+    changes
+}
@@ -0,0 +1,150 @@
+use chrono::NaiveDate;
+use plotly::{
+    common::Title,
+    layout::{Axis, BarMode},
+    Bar, Layout, Plot,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use crate::error::Error;
+use crate::git::{run_git, Commit};
+use crate::plots::{Granularity, PLOT_WIDTH};
+
+#[derive(Clone, Copy)]
+pub enum Signature {
+    /// A good signature by a trusted or at least known key
+    Verified,
+    /// Signed, but the key is missing, expired or revoked
+    Unverified,
+    /// The signature doesn't match the commit
+    Bad,
+    Unsigned,
+}
+
+impl Signature {
+    /// The signature status of `git log --format=%G?`
+    fn parse(status: &str) -> Signature {
+        match status {
+            "G" | "U" => Signature::Verified,
+            "B" => Signature::Bad,
+            "N" | "" => Signature::Unsigned,
+            _ => Signature::Unverified,
+        }
+    }
+}
+
+/// Signature of every commit of HEAD changing files under non-empty `scopes` by hash. This verifies every signature
+/// with gpg or ssh-keygen, which is slow on repositories with many signed commits
+pub fn get_signatures(path: &Path, scopes: &[String]) -> Result<HashMap<String, Signature>, Error> {
+    let mut args = vec!["log", "--format=%H %G?", "HEAD", "--"];
+    args.extend(scopes.iter().map(String::as_str));
+    Ok(run_git(path, &args)?
+        .lines()
+        .filter_map(|line| {
+            let (hash, status) = line.split_once(' ')?;
+            Some((hash.to_string(), Signature::parse(status)))
+        })
+        .collect())
+}
+
+#[derive(Default, Clone, Copy)]
+pub struct SignatureCounts {
+    pub commits: usize,
+    pub verified: usize,
+    pub unverified: usize,
+    pub bad: usize,
+}
+
+impl SignatureCounts {
+    fn add(&mut self, signature: Signature) {
+        self.commits += 1;
+        match signature {
+            Signature::Verified => self.verified += 1,
+            Signature::Unverified => self.unverified += 1,
+            Signature::Bad => self.bad += 1,
+            Signature::Unsigned => {}
+        }
+    }
+
+    pub fn signed(&self) -> usize {
+        self.verified + self.unverified + self.bad
+    }
+
+    pub fn signed_share(&self) -> f64 {
+        self.signed() as f64 / self.commits.max(1) as f64
+    }
+}
+
+pub struct SignatureStats {
+    pub total: SignatureCounts,
+    pub periods: BTreeMap<NaiveDate, SignatureCounts>,
+    /// Authors with the most commits, most commits first
+    pub authors: Vec<(String, SignatureCounts)>,
+}
+
+/// Signed and unsigned commits per period and per author, `None` if no commit is signed
+pub fn get_signature_stats(
+    commits: &[Commit],
+    signatures: &HashMap<String, Signature>,
+    granularity: Granularity,
+    top_authors: usize,
+) -> Option<SignatureStats> {
+    let mut total = SignatureCounts::default();
+    let mut periods: BTreeMap<NaiveDate, SignatureCounts> = BTreeMap::new();
+    let mut authors: HashMap<&str, SignatureCounts> = HashMap::new();
+    for commit in commits {
+        // commits of submodules aren't on HEAD
+        let Some(signature) = signatures.get(&commit.hash) else {
+            continue;
+        };
+        total.add(*signature);
+        periods
+            .entry(granularity.bucket(commit.date))
+            .or_default()
+            .add(*signature);
+        authors.entry(&commit.author).or_default().add(*signature);
+    }
+    if total.signed() == 0 {
+        return None;
+    }
+
+    let mut authors: Vec<(String, SignatureCounts)> = authors
+        .into_iter()
+        .map(|(author, counts)| (author.to_string(), counts))
+        .collect();
+    authors.sort_by(|a, b| b.1.commits.cmp(&a.1.commits).then(a.0.cmp(&b.0)));
+    authors.truncate(top_authors);
+    Some(SignatureStats {
+        total,
+        periods,
+        authors,
+    })
+}
+
+/// Verified, otherwise signed and unsigned commits per period, stacked
+pub fn plot_signatures(stats: &SignatureStats) -> Plot {
+    let mut plot = Plot::new();
+
+    let x: Vec<String> = stats
+        .periods
+        .keys()
+        .map(|period| period.to_string())
+        .collect();
+    let counts = |count: fn(&SignatureCounts) -> usize| stats.periods.values().map(count).collect();
+    plot.add_trace(Bar::new(x.clone(), counts(|counts| counts.verified)).name("Verified"));
+    plot.add_trace(
+        Bar::new(x.clone(), counts(|counts| counts.unverified + counts.bad))
+            .name("Signed, not verified"),
+    );
+    plot.add_trace(Bar::new(x, counts(|counts| counts.commits - counts.signed())).name("Unsigned"));
+
+    let layout = Layout::new()
+        .width(PLOT_WIDTH - 50)
+        .bar_mode(BarMode::Stack)
+        .title(Title::from("Signed and unsigned commits"))
+        .y_axis(Axis::new().title(Title::from("Commits")));
+    plot.set_layout(layout);
+
+    plot
+}
@@ -0,0 +1,85 @@
+use plotly::{common::Title, Bar, Layout, Plot};
+use std::collections::HashMap;
+
+use crate::git::Commit;
+use crate::plots::PLOT_WIDTH;
+
+/// Upper bounds (inclusive) of the commit size buckets
+const BUCKETS: [u32; 7] = [10, 50, 100, 250, 500, 1000, 5000];
+
+#[derive(Debug)]
+pub struct AuthorCommitSize {
+    pub author: String,
+    pub commits: usize,
+    pub median: u32,
+    pub p90: u32,
+}
+
+/// Value at the given percentile (0-100) using the nearest-rank method, `sorted` must be sorted
+pub fn percentile(sorted: &[u32], percentile: usize) -> u32 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (percentile * sorted.len()).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+/// Merge commits are skipped, as their changes are already counted in the commits they merge
+fn commit_sizes(commits: &[Commit]) -> impl Iterator<Item = (&Commit, u32)> {
+    commits
+        .iter()
+        .filter(|commit| !commit.is_merge())
+        .map(|commit| (commit, commit.lines_changed()))
+}
+
+pub fn get_commit_sizes_per_author(commits: &[Commit], n: usize) -> Vec<AuthorCommitSize> {
+    let mut sizes: HashMap<&str, Vec<u32>> = HashMap::new();
+    for (commit, size) in commit_sizes(commits) {
+        sizes.entry(&commit.author).or_default().push(size);
+    }
+
+    let mut sizes: Vec<AuthorCommitSize> = sizes
+        .into_iter()
+        .map(|(author, mut sizes)| {
+            sizes.sort();
+            AuthorCommitSize {
+                author: author.to_string(),
+                commits: sizes.len(),
+                median: percentile(&sizes, 50),
+                p90: percentile(&sizes, 90),
+            }
+        })
+        .collect();
+    sizes.sort_by_key(|size| std::cmp::Reverse(size.commits));
+    sizes.truncate(n);
+    sizes
+}
+
+pub fn plot_commit_size_histogram(commits: &[Commit]) -> Plot {
+    let mut plot = Plot::new();
+
+    let mut labels: Vec<String> = vec![];
+    let mut lower = 0;
+    for upper in BUCKETS {
+        labels.push(format!("{}-{}", lower, upper));
+        lower = upper + 1;
+    }
+    labels.push(format!(">{}", BUCKETS[BUCKETS.len() - 1]));
+
+    let mut counts = vec![0; labels.len()];
+    for (_, size) in commit_sizes(commits) {
+        let bucket = BUCKETS
+            .iter()
+            .position(|upper| size <= *upper)
+            .unwrap_or(BUCKETS.len());
+        counts[bucket] += 1;
+    }
+
+    plot.add_trace(Bar::new(labels, counts));
+    let layout = Layout::new()
+        .width(PLOT_WIDTH / 2)
+        .title(Title::from("Lines changed per commit"));
+    plot.set_layout(layout);
+
+    plot
+}
@@ -0,0 +1,117 @@
+use chrono::{Duration, NaiveDate};
+use plotly::{common::Title, layout::BarMode, Bar, Layout, Plot};
+use std::collections::HashSet;
+
+use crate::config::Sprints;
+use crate::git::Commit;
+use crate::plots::PLOT_WIDTH;
+
+pub struct Sprint {
+    pub name: String,
+    pub start: NaiveDate,
+    /// Last day of the sprint
+    pub end: NaiveDate,
+    pub commits: usize,
+    pub contributors: usize,
+    pub lines_added: u32,
+    pub lines_deleted: u32,
+}
+
+/// Start, end (exclusive) and name of every sprint up to the latest commit
+fn sprint_boundaries(config: &Sprints, latest: NaiveDate) -> Vec<(NaiveDate, NaiveDate, String)> {
+    let length = Duration::days(config.length_days.max(1) as i64);
+
+    if !config.schedule.is_empty() {
+        let mut schedule: Vec<_> = config.schedule.iter().collect();
+        schedule.sort_by_key(|sprint| sprint.start);
+        return schedule
+            .iter()
+            .enumerate()
+            .map(|(i, sprint)| {
+                let end = schedule
+                    .get(i + 1)
+                    .map_or(sprint.start + length, |next| next.start);
+                let name = sprint
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| format!("Sprint {}", i + 1));
+                (sprint.start, end, name)
+            })
+            .collect();
+    }
+
+    let mut boundaries = vec![];
+    let Some(mut start) = config.start else {
+        return boundaries;
+    };
+    while start <= latest {
+        boundaries.push((
+            start,
+            start + length,
+            format!("Sprint {}", boundaries.len() + 1),
+        ));
+        start += length;
+    }
+    boundaries
+}
+
+/// Activity per sprint, commits outside of all sprints are left out
+pub fn get_sprints(commits: &[Commit], config: &Sprints) -> Vec<Sprint> {
+    let latest = commits
+        .iter()
+        .map(|commit| commit.date)
+        .max()
+        .unwrap_or_default();
+
+    sprint_boundaries(config, latest)
+        .into_iter()
+        .map(|(start, end, name)| {
+            let in_sprint: Vec<&Commit> = commits
+                .iter()
+                .filter(|commit| start <= commit.date && commit.date < end)
+                .collect();
+            let contributors: HashSet<&str> = in_sprint
+                .iter()
+                .map(|commit| commit.author.as_str())
+                .collect();
+            // merge commits are skipped, as their changes are already counted in the commits they merge
+            let files = in_sprint
+                .iter()
+                .filter(|commit| !commit.is_merge())
+                .flat_map(|commit| &commit.files);
+            let (lines_added, lines_deleted) = files.fold((0, 0), |(added, deleted), file| {
+                (
+                    added + file.added.unwrap_or(0),
+                    deleted + file.deleted.unwrap_or(0),
+                )
+            });
+            Sprint {
+                name,
+                start,
+                end: end - Duration::days(1),
+                commits: in_sprint.len(),
+                contributors: contributors.len(),
+                lines_added,
+                lines_deleted,
+            }
+        })
+        .collect()
+}
+
+pub fn plot_sprint_velocity(sprints: &[Sprint]) -> Plot {
+    let mut plot = Plot::new();
+
+    let x: Vec<String> = sprints.iter().map(|sprint| sprint.name.clone()).collect();
+    let commits: Vec<usize> = sprints.iter().map(|sprint| sprint.commits).collect();
+    let contributors: Vec<usize> = sprints.iter().map(|sprint| sprint.contributors).collect();
+    plot.add_trace(Bar::new(x.clone(), commits).name("Commits"));
+    plot.add_trace(Bar::new(x, contributors).name("Contributors"));
+
+    let layout = Layout::new()
+        .width(PLOT_WIDTH - 50)
+        .bar_mode(BarMode::Group)
+        .title(Title::from("Velocity per sprint"));
+    plot.set_layout(layout);
+
+    plot
+}
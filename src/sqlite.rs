@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::git::Commit;
+use crate::languages::{Language, LanguageType};
+use crate::output::temp_path;
+
+const SCHEMA: &str = "
+CREATE TABLE authors (id INTEGER PRIMARY KEY, name TEXT NOT NULL, email TEXT NOT NULL, UNIQUE (name, email));
+CREATE TABLE commits (
+    hash TEXT PRIMARY KEY,
+    author_id INTEGER NOT NULL REFERENCES authors (id),
+    date TEXT NOT NULL,
+    time INTEGER NOT NULL,
+    message TEXT NOT NULL,
+    submodule TEXT
+);
+CREATE TABLE commit_parents (
+    commit_hash TEXT NOT NULL REFERENCES commits (hash),
+    parent_hash TEXT NOT NULL,
+    position INTEGER NOT NULL
+);
+CREATE TABLE co_authors (commit_hash TEXT NOT NULL REFERENCES commits (hash), name TEXT NOT NULL);
+CREATE TABLE file_changes (
+    commit_hash TEXT NOT NULL REFERENCES commits (hash),
+    path TEXT NOT NULL,
+    renamed_from TEXT,
+    added INTEGER,
+    deleted INTEGER
+);
+CREATE TABLE languages (
+    language TEXT PRIMARY KEY,
+    files INTEGER NOT NULL,
+    code INTEGER NOT NULL,
+    comments INTEGER NOT NULL,
+    blanks INTEGER NOT NULL
+);
+CREATE INDEX file_changes_path ON file_changes (path);
+CREATE INDEX commits_author ON commits (author_id);
+";
+
+/// SQL literal of a string
+fn text(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map_or("NULL".to_string(), |value| value.to_string())
+}
+
+/// SQL script creating the tables and inserting the commits and languages
+fn script(commits: &[Commit], languages: Option<&[(LanguageType, Language)]>) -> String {
+    let mut sql = String::from("BEGIN;\n");
+    sql.push_str(SCHEMA);
+
+    let mut authors: HashMap<(&str, &str), usize> = HashMap::new();
+    for commit in commits {
+        let next = authors.len() + 1;
+        let id = *authors
+            .entry((&commit.author, &commit.email))
+            .or_insert_with(|| {
+                let _ = writeln!(
+                    sql,
+                    "INSERT INTO authors VALUES ({}, {}, {});",
+                    next,
+                    text(&commit.author),
+                    text(&commit.email)
+                );
+                next
+            });
+        let _ = writeln!(
+            sql,
+            "INSERT INTO commits VALUES ({}, {}, {}, {}, {}, {});",
+            text(&commit.hash),
+            id,
+            text(&commit.date.to_string()),
+            commit.time,
+            text(&commit.message),
+            optional(commit.submodule.as_deref().map(text))
+        );
+        for (position, parent) in commit.parents.iter().enumerate() {
+            let _ = writeln!(
+                sql,
+                "INSERT INTO commit_parents VALUES ({}, {}, {});",
+                text(&commit.hash),
+                text(parent),
+                position
+            );
+        }
+        for co_author in &commit.co_authors {
+            let _ = writeln!(
+                sql,
+                "INSERT INTO co_authors VALUES ({}, {});",
+                text(&commit.hash),
+                text(co_author)
+            );
+        }
+        for file in &commit.files {
+            let _ = writeln!(
+                sql,
+                "INSERT INTO file_changes VALUES ({}, {}, {}, {}, {});",
+                text(&commit.hash),
+                text(&file.path),
+                optional(file.renamed_from.as_deref().map(text)),
+                optional(file.added),
+                optional(file.deleted)
+            );
+        }
+    }
+
+    for (language_type, language) in languages.unwrap_or_default() {
+        let _ = writeln!(
+            sql,
+            "INSERT INTO languages VALUES ({}, {}, {}, {}, {});",
+            text(&language_type.to_string()),
+            language.reports.len(),
+            language.code,
+            language.comments,
+            language.blanks
+        );
+    }
+    sql.push_str("COMMIT;\n");
+    sql
+}
+
+/// Write the commits, their authors and file changes, and the languages at HEAD into a new SQLite database at `path`,
+/// with the sqlite3 command line tool. The languages table is empty when they weren't counted
+pub fn export_sqlite(
+    path: &Path,
+    commits: &[Commit],
+    languages: Option<&[(LanguageType, Language)]>,
+) -> Result<(), String> {
+    let temp = temp_path(path);
+    let _ = std::fs::remove_file(&temp);
+    let mut child = Command::new("sqlite3")
+        .arg("-bail")
+        .arg(&temp)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("Failed to execute sqlite3, is it installed? ({})", err))?;
+    // a failed write means sqlite3 stopped at an error, which it reports on stderr
+    let _ = child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(script(commits, languages).as_bytes());
+    let output = child
+        .wait_with_output()
+        .map_err(|err| format!("Failed to execute sqlite3: {}", err))?;
+    if !output.status.success() {
+        let _ = std::fs::remove_file(&temp);
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    std::fs::rename(&temp, path).map_err(|err| err.to_string())
+}
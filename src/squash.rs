@@ -0,0 +1,53 @@
+use std::collections::HashMap;
+
+use crate::git::Commit;
+
+/// Commits of an author, and the logical changes they collapse into
+pub struct AuthorChanges {
+    pub author: String,
+    pub commits: usize,
+    pub changes: usize,
+}
+
+pub struct SquashedCommits {
+    pub commits: usize,
+    pub changes: usize,
+    /// The authors with the most commits, most commits first
+    pub authors: Vec<AuthorChanges>,
+}
+
+/// Collapse consecutive commits of the same author into one logical change when each follows the previous one
+/// within `window_minutes`, as if the stack had been squashed before merging. Merge commits are left out
+pub fn squash_commits(commits: &[Commit], window_minutes: u32, n: usize) -> SquashedCommits {
+    let mut times: HashMap<&str, Vec<i64>> = HashMap::new();
+    for commit in commits.iter().filter(|commit| !commit.is_merge()) {
+        times.entry(&commit.author).or_default().push(commit.time);
+    }
+
+    let window = i64::from(window_minutes) * 60;
+    let mut authors: Vec<AuthorChanges> = times
+        .into_iter()
+        .map(|(author, mut times)| {
+            times.sort();
+            let changes = 1 + times
+                .windows(2)
+                .filter(|pair| pair[1] - pair[0] > window)
+                .count();
+            AuthorChanges {
+                author: author.to_string(),
+                commits: times.len(),
+                changes,
+            }
+        })
+        .collect();
+    authors.sort_by(|a, b| b.commits.cmp(&a.commits).then(a.author.cmp(&b.author)));
+
+    let commits = authors.iter().map(|author| author.commits).sum();
+    let changes = authors.iter().map(|author| author.changes).sum();
+    authors.truncate(n);
+    SquashedCommits {
+        commits,
+        changes,
+        authors,
+    }
+}
@@ -0,0 +1,85 @@
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+
+use crate::git::Commit;
+use crate::plots::Granularity;
+
+/// Consecutive days from `start` to `end`, both included
+pub struct Span {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub days: i64,
+}
+
+pub struct Streaks {
+    /// Longest run of days with at least one commit
+    pub longest_streak: Span,
+    /// Longest run of days without commits between two commits, `None` if there were commits every day
+    pub longest_gap: Option<Span>,
+    pub busiest_day: (NaiveDate, usize),
+    /// First day of the week with the most commits
+    pub busiest_week: (NaiveDate, usize),
+}
+
+/// The earliest period with the most commits
+fn busiest(counts: &BTreeMap<NaiveDate, usize>) -> (NaiveDate, usize) {
+    counts
+        .iter()
+        .max_by(|(a_date, a), (b_date, b)| a.cmp(b).then(b_date.cmp(a_date)))
+        .map(|(date, count)| (*date, *count))
+        .unwrap_or_default()
+}
+
+/// Streaks, gaps and the busiest periods of the daily commit series, `None` if there are no commits
+pub fn get_streaks(commits: &[Commit]) -> Option<Streaks> {
+    let mut days: BTreeMap<NaiveDate, usize> = BTreeMap::new();
+    let mut weeks: BTreeMap<NaiveDate, usize> = BTreeMap::new();
+    for commit in commits {
+        *days.entry(commit.date).or_insert(0) += 1;
+        *weeks
+            .entry(Granularity::Week.bucket(commit.date))
+            .or_insert(0) += 1;
+    }
+    let first = *days.keys().next()?;
+
+    let mut longest_streak = Span {
+        start: first,
+        end: first,
+        days: 1,
+    };
+    let mut longest_gap: Option<Span> = None;
+    let mut streak_start = first;
+    let mut previous = first;
+    for &day in days.keys().skip(1) {
+        let gap = (day - previous).num_days() - 1;
+        if gap > 0 {
+            streak_start = day;
+            if longest_gap
+                .as_ref()
+                .is_none_or(|longest| gap > longest.days)
+            {
+                longest_gap = Some(Span {
+                    start: previous.succ_opt().unwrap(),
+                    end: day.pred_opt().unwrap(),
+                    days: gap,
+                });
+            }
+        }
+        let streak = (day - streak_start).num_days() + 1;
+        if streak > longest_streak.days {
+            longest_streak = Span {
+                start: streak_start,
+                end: day,
+                days: streak,
+            };
+        }
+        previous = day;
+    }
+
+    Some(Streaks {
+        longest_streak,
+        longest_gap,
+        busiest_day: busiest(&days),
+        busiest_week: busiest(&weeks),
+    })
+}
@@ -0,0 +1,89 @@
+use clap::ValueEnum;
+
+use crate::render::TEMPLATE;
+use crate::sections::Section;
+
+/// Built-in report templates, each showing the sections relevant to its audience
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReportStyle {
+    /// Every section that applies to the repository
+    #[default]
+    Full,
+    /// Headline numbers and delivery for managers
+    Executive,
+    /// Every section, including the slow opt-in ones like blame age and the public API
+    Maintainer,
+    /// Contributors, affiliations, issues and ownership
+    Community,
+    /// Activity, contributors and languages without decoration
+    Minimal,
+}
+
+impl ReportStyle {
+    /// Opt-in sections the style turns on. The ones showing when people work and how they feel are left to
+    /// `--after-hours` and `--tone`
+    pub fn opt_in_sections(&self) -> Vec<Section> {
+        match self {
+            ReportStyle::Maintainer => Section::value_variants()
+                .iter()
+                .copied()
+                .filter(|section| !matches!(section, Section::AfterHours | Section::Tone))
+                .collect(),
+            ReportStyle::Community => vec![Section::Affiliations],
+            _ => vec![],
+        }
+    }
+
+    /// Sections the style shows, `None` for the default selection
+    pub fn sections(&self) -> Option<Vec<Section>> {
+        match self {
+            ReportStyle::Full => None,
+            ReportStyle::Executive => Some(vec![
+                Section::Activity,
+                Section::Authors,
+                Section::Merges,
+                Section::Sprints,
+                Section::Hosting,
+                Section::Milestones,
+                Section::Releases,
+                Section::Languages,
+            ]),
+            ReportStyle::Maintainer => Some(Section::value_variants().to_vec()),
+            ReportStyle::Community => Some(vec![
+                Section::Activity,
+                Section::Authors,
+                Section::Tenure,
+                Section::Streaks,
+                Section::FirstResponse,
+                Section::CodeOwners,
+                Section::Issues,
+                Section::Affiliations,
+                Section::Hosting,
+            ]),
+            ReportStyle::Minimal => Some(vec![
+                Section::Activity,
+                Section::Authors,
+                Section::Languages,
+            ]),
+        }
+    }
+
+    /// Name and source of the template, the styles extend the full report template `index.html`
+    pub fn template(&self) -> (&'static str, &'static str) {
+        match self {
+            ReportStyle::Full | ReportStyle::Maintainer => ("index.html", TEMPLATE),
+            ReportStyle::Executive => (
+                "executive.html",
+                include_str!("../templates/styles/executive.html"),
+            ),
+            ReportStyle::Community => (
+                "community.html",
+                include_str!("../templates/styles/community.html"),
+            ),
+            ReportStyle::Minimal => (
+                "minimal.html",
+                include_str!("../templates/styles/minimal.html"),
+            ),
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use chrono::NaiveDate;
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::error::Error;
+use crate::git::{get_commit_log, run_git, Commit};
+use crate::languages::{Language, LanguageType};
+
+pub struct Submodule {
+    pub path: String,
+    pub commits: usize,
+    pub contributors: usize,
+    pub last_commit: Option<NaiveDate>,
+    pub lines_of_code: usize,
+}
+
+/// Paths of the checked out submodules relative to the repository, including nested submodules
+pub fn get_submodule_paths(path: &Path) -> Result<Vec<String>, Error> {
+    let status = run_git(
+        path,
+        &[
+            "-c",
+            "core.quotePath=false",
+            "submodule",
+            "status",
+            "--recursive",
+        ],
+    )?;
+    // lines look like `<status><commit> <path> (<describe>)`, where a `-` status means not checked out and the
+    // description is left out when there is none
+    Ok(status
+        .lines()
+        .filter_map(|line| {
+            let (status, rest) = line.split_at_checked(1)?;
+            let (_, path) = rest.split_once(' ')?;
+            let path = match path.strip_suffix(')').and_then(|path| path.rsplit_once(" (")) {
+                Some((path, _)) => path,
+                None => path,
+            };
+            if status == "-" {
+                eprintln!(
+                    "Warning: Skipping submodule {}, it is not checked out. Run `git submodule update --init --recursive` to include it",
+                    path
+                );
+                return None;
+            }
+            Some(path.to_string())
+        })
+        .collect())
+}
+
+/// The scopes relative to a submodule, empty for all of it, `None` if the submodule is outside of the scopes
+fn submodule_scopes(submodule: &str, scopes: &[String]) -> Option<Vec<String>> {
+    if scopes.is_empty() {
+        return Some(vec![]);
+    }
+    let mut inner = vec![];
+    for scope in scopes {
+        if Path::new(submodule).starts_with(scope) {
+            return Some(vec![]);
+        }
+        if let Ok(path) = Path::new(scope).strip_prefix(submodule) {
+            inner.push(path.to_string_lossy().into_owned());
+        }
+    }
+    (!inner.is_empty()).then_some(inner)
+}
+
+/// Read the history of every submodule under the `scopes`, with the changed files relative to the repository
+pub fn get_submodule_commits(
+    path: &Path,
+    submodules: &[String],
+    with_files: bool,
+    follow_renames: bool,
+    scopes: &[String],
+) -> Result<Vec<Commit>, Error> {
+    let mut commits = vec![];
+    for submodule in submodules {
+        let Some(scopes) = submodule_scopes(submodule, scopes) else {
+            continue;
+        };
+        let log = get_commit_log(&path.join(submodule), with_files, follow_renames, &scopes)
+            .map_err(|err| match err {
+                Error::Git(message) => {
+                    Error::Git(format!("{} (in submodule {})", message, submodule))
+                }
+                err => err,
+            })?;
+        for mut commit in log {
+            for file in &mut commit.files {
+                file.path = format!("{}/{}", submodule, file.path);
+                if let Some(renamed_from) = &mut file.renamed_from {
+                    *renamed_from = format!("{}/{}", submodule, renamed_from);
+                }
+            }
+            commit.submodule = Some(submodule.clone());
+            commits.push(commit);
+        }
+    }
+    Ok(commits)
+}
+
+/// Activity and size of every submodule, in the order of `submodules`
+pub fn get_submodule_stats(
+    repo_path: &Path,
+    submodules: &[String],
+    commits: &[Commit],
+    languages: Option<&[(LanguageType, Language)]>,
+) -> Vec<Submodule> {
+    submodules
+        .iter()
+        .map(|submodule| {
+            let in_submodule: Vec<&Commit> = commits
+                .iter()
+                .filter(|commit| commit.submodule.as_ref() == Some(submodule))
+                .collect();
+            let root = repo_path.join(submodule);
+            let lines_of_code = languages
+                .unwrap_or_default()
+                .iter()
+                .flat_map(|(_, language)| &language.reports)
+                .filter(|report| report.name.starts_with(&root))
+                .map(|report| report.stats.summarise().code)
+                .sum();
+            Submodule {
+                path: submodule.clone(),
+                commits: in_submodule.len(),
+                contributors: in_submodule
+                    .iter()
+                    .map(|commit| &commit.author)
+                    .collect::<HashSet<_>>()
+                    .len(),
+                last_commit: in_submodule.iter().map(|commit| commit.date).max(),
+                lines_of_code,
+            }
+        })
+        .collect()
+}
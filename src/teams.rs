@@ -0,0 +1,209 @@
+use chrono::{DateTime, Datelike, Timelike};
+use plotly::{
+    common::{ColorScale, ColorScalePalette, Title},
+    layout::{Axis, GridPattern, LayoutGrid},
+    HeatMap, Layout, Plot,
+};
+use std::collections::{HashMap, HashSet};
+
+use crate::affiliations::organization;
+use crate::git::Commit;
+use crate::plots::PLOT_WIDTH;
+
+/// Teams charted side by side, the ones with the most commits
+const TOP_TEAMS: usize = 4;
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+pub struct TeamWeek {
+    pub team: String,
+    pub authors: usize,
+    pub commits: usize,
+    /// Commits by weekday, Monday first, and hour of the day in UTC
+    pub hours: [[usize; 24]; 7],
+}
+
+impl TeamWeek {
+    /// Weekday hours with at least half of the commits of the team's busiest weekday hour
+    pub fn active_hours(&self) -> Vec<u32> {
+        let per_hour: Vec<usize> = (0..24)
+            .map(|hour| self.hours[..5].iter().map(|day| day[hour]).sum())
+            .collect();
+        let busiest = per_hour.iter().copied().max().unwrap_or(0);
+        (0..24)
+            .filter(|hour| busiest > 0 && 2 * per_hour[*hour as usize] >= busiest)
+            .collect()
+    }
+}
+
+pub struct TeamWeeks {
+    /// Teams with the most commits, most commits first
+    pub teams: Vec<TeamWeek>,
+    /// Whether the teams are the organizations of the authors' email domains, as no [teams] are configured
+    pub organizations: bool,
+}
+
+impl TeamWeeks {
+    /// Weekday hours in UTC all teams are active in, the candidates for meetings
+    pub fn shared_hours(&self) -> Vec<u32> {
+        let mut teams = self.teams.iter().map(TeamWeek::active_hours);
+        let first: Vec<u32> = teams.next().unwrap_or_default();
+        teams.fold(first, |shared, hours| {
+            shared
+                .into_iter()
+                .filter(|hour| hours.contains(hour))
+                .collect()
+        })
+    }
+}
+
+/// Hours like `[9, 10, 11, 15]` as ranges like `09:00–12:00, 15:00–16:00`
+pub fn hour_ranges(hours: &[u32]) -> String {
+    let mut ranges: Vec<(u32, u32)> = vec![];
+    for &hour in hours {
+        match ranges.last_mut() {
+            Some((_, end)) if *end == hour => *end = hour + 1,
+            _ => ranges.push((hour, hour + 1)),
+        }
+    }
+    ranges
+        .iter()
+        .map(|(start, end)| format!("{:02}:00–{:02}:00", start, end))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// The team of every member, matched by email or name ignoring case
+fn members(teams: &HashMap<String, Vec<String>>) -> HashMap<String, &str> {
+    teams
+        .iter()
+        .flat_map(|(team, members)| {
+            members
+                .iter()
+                .map(move |member| (member.to_lowercase(), team.as_str()))
+        })
+        .collect()
+}
+
+/// Commits per team by weekday and hour in UTC, so the weeks of teams in different time zones line up. Teams come
+/// from the `[teams]` of the config, authors not in a team are left out, or are the organizations of the authors'
+/// email domains without it. Merge commits are left out, as they are often made by tools
+pub fn get_team_weeks(
+    commits: &[Commit],
+    teams: &HashMap<String, Vec<String>>,
+    affiliations: &HashMap<String, String>,
+) -> Option<TeamWeeks> {
+    let organizations = teams.is_empty();
+    let members = members(teams);
+    let mut weeks: HashMap<&str, ([[usize; 24]; 7], HashSet<&str>)> = HashMap::new();
+    for commit in commits.iter().filter(|commit| !commit.is_merge()) {
+        let team = if organizations {
+            organization(commit.email_domain(), affiliations)
+        } else {
+            let member = members
+                .get(&commit.email.to_lowercase())
+                .or_else(|| members.get(&commit.author.to_lowercase()));
+            let Some(team) = member else {
+                continue;
+            };
+            team
+        };
+        let Some(time) = DateTime::from_timestamp(commit.time, 0) else {
+            continue;
+        };
+        let (hours, authors) = weeks.entry(team).or_insert(([[0; 24]; 7], HashSet::new()));
+        hours[time.weekday().num_days_from_monday() as usize][time.hour() as usize] += 1;
+        authors.insert(&commit.email);
+    }
+
+    let mut teams: Vec<TeamWeek> = weeks
+        .into_iter()
+        .map(|(team, (hours, authors))| TeamWeek {
+            team: team.to_string(),
+            authors: authors.len(),
+            commits: hours.iter().flatten().sum(),
+            hours,
+        })
+        .collect();
+    if teams.is_empty() {
+        return None;
+    }
+    teams.sort_by(|a, b| b.commits.cmp(&a.commits).then(a.team.cmp(&b.team)));
+    teams.truncate(TOP_TEAMS);
+    Some(TeamWeeks {
+        teams,
+        organizations,
+    })
+}
+
+/// A heatmap of the typical week per team side by side, with the hours of the day from top to bottom. Cells are
+/// the share of the team's commits, so small and large teams use the same colors
+pub fn plot_team_weeks(weeks: &TeamWeeks) -> Plot {
+    let mut plot = Plot::new();
+
+    // the first hour at the top
+    let y: Vec<String> = (0..24)
+        .rev()
+        .map(|hour| format!("{:02}:00", hour))
+        .collect();
+    let shares: Vec<Vec<Vec<f64>>> = weeks
+        .teams
+        .iter()
+        .map(|team| {
+            (0..24)
+                .rev()
+                .map(|hour| {
+                    team.hours
+                        .iter()
+                        .map(|day| 100.0 * day[hour] as f64 / team.commits.max(1) as f64)
+                        .collect()
+                })
+                .collect()
+        })
+        .collect();
+    let max = shares
+        .iter()
+        .flatten()
+        .flatten()
+        .copied()
+        .fold(0.0, f64::max);
+
+    let mut layout = Layout::new()
+        .width(PLOT_WIDTH - 50)
+        .height(650)
+        .title(Title::from("Typical week per team (UTC)"))
+        .grid(
+            LayoutGrid::new()
+                .rows(1)
+                .columns(weeks.teams.len())
+                .pattern(GridPattern::Independent),
+        );
+    for (i, (team, z)) in weeks.teams.iter().zip(shares).enumerate() {
+        let axis = match i {
+            0 => String::new(),
+            _ => (i + 1).to_string(),
+        };
+        let trace = HeatMap::new(WEEKDAYS.to_vec(), y.clone(), z)
+            .name(&team.team)
+            .x_axis(format!("x{}", axis))
+            .y_axis(format!("y{}", axis))
+            .zmin(0.0)
+            .zmax(max)
+            .color_scale(ColorScale::Palette(ColorScalePalette::Blues))
+            .reverse_scale(true)
+            .show_scale(i + 1 == weeks.teams.len())
+            .hover_template("%{x} %{y} UTC: %{z:.1f}% of commits<extra>%{fullData.name}</extra>");
+        plot.add_trace(trace);
+
+        let x_axis = Axis::new().title(Title::from(team.team.as_str()));
+        let y_axis = Axis::new().show_tick_labels(i == 0);
+        layout = match i {
+            0 => layout.x_axis(x_axis).y_axis(y_axis),
+            1 => layout.x_axis2(x_axis).y_axis2(y_axis),
+            2 => layout.x_axis3(x_axis).y_axis3(y_axis),
+            _ => layout.x_axis4(x_axis).y_axis4(y_axis),
+        };
+    }
+    plot.set_layout(layout);
+
+    plot
+}
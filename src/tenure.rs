@@ -0,0 +1,175 @@
+use chrono::{Datelike, Months, NaiveDate};
+use plotly::{
+    common::{ColorScale, ColorScalePalette, Mode, Title},
+    layout::Margin,
+    HeatMap, Layout, Plot, Scatter,
+};
+use std::collections::{HashMap, HashSet};
+
+use crate::git::Commit;
+use crate::plots::PLOT_WIDTH;
+
+pub struct Tenure {
+    pub author: String,
+    pub first: NaiveDate,
+    pub last: NaiveDate,
+    pub commits: u32,
+}
+
+pub fn get_tenures(commits: &[Commit]) -> Vec<Tenure> {
+    let mut tenures: HashMap<&str, Tenure> = HashMap::new();
+    for commit in commits {
+        let tenure = tenures.entry(&commit.author).or_insert(Tenure {
+            author: commit.author.clone(),
+            first: commit.date,
+            last: commit.date,
+            commits: 0,
+        });
+        tenure.first = tenure.first.min(commit.date);
+        tenure.last = tenure.last.max(commit.date);
+        tenure.commits += 1;
+    }
+
+    // longest standing contributors first
+    let mut tenures: Vec<Tenure> = tenures.into_values().collect();
+    tenures.sort_by_key(|tenure| (tenure.first, tenure.author.clone()));
+    tenures
+}
+
+fn month_start(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).unwrap()
+}
+
+/// Every month from the month of `start` up to `end`
+fn months_between(start: NaiveDate, end: NaiveDate) -> Vec<NaiveDate> {
+    let mut months = vec![];
+    let mut month = month_start(start);
+    while month <= end {
+        months.push(month);
+        month = month + Months::new(1);
+    }
+    months
+}
+
+/// Draw every contributor as a bar from their first to last commit, shaded by commits per month
+pub fn plot_tenure_timeline(commits: &[Commit], tenures: &[Tenure], n: usize) -> Plot {
+    let mut plot = Plot::new();
+
+    // only show the n most active contributors, in order of their first commit
+    let mut top: Vec<&Tenure> = tenures.iter().collect();
+    top.sort_by_key(|tenure| std::cmp::Reverse(tenure.commits));
+    top.truncate(n);
+    top.sort_by_key(|tenure| (tenure.first, tenure.author.clone()));
+    // plotly draws the first row at the bottom
+    top.reverse();
+
+    let (Some(start), Some(end)) = (
+        top.iter().map(|tenure| tenure.first).min(),
+        top.iter().map(|tenure| tenure.last).max(),
+    ) else {
+        return plot;
+    };
+
+    let months = months_between(start, end);
+
+    let mut count: HashMap<(&str, NaiveDate), u32> = HashMap::new();
+    for commit in commits {
+        *count
+            .entry((&commit.author, month_start(commit.date)))
+            .or_insert(0) += 1;
+    }
+
+    // months outside of a contributor's tenure are left empty
+    let z: Vec<Vec<Option<u32>>> = top
+        .iter()
+        .map(|tenure| {
+            months
+                .iter()
+                .map(|month| {
+                    (*month >= month_start(tenure.first) && *month <= tenure.last).then(|| {
+                        count
+                            .get(&(tenure.author.as_str(), *month))
+                            .copied()
+                            .unwrap_or(0)
+                    })
+                })
+                .collect()
+        })
+        .collect();
+    let x: Vec<String> = months
+        .iter()
+        .map(|month| month.format("%Y-%m").to_string())
+        .collect();
+    let y: Vec<String> = top.iter().map(|tenure| tenure.author.clone()).collect();
+
+    let trace = HeatMap::new(x, y, z)
+        .color_scale(ColorScale::Palette(ColorScalePalette::Blues))
+        .reverse_scale(true)
+        .hover_on_gaps(false)
+        .hover_template("%{y}<br>%{x}: %{z} commits<extra></extra>");
+    plot.add_trace(trace);
+
+    let layout = Layout::new()
+        .width(PLOT_WIDTH - 50)
+        .height(100 + 25 * top.len())
+        .title(Title::from("Contributor tenure"))
+        .margin(Margin::new().left(200));
+    plot.set_layout(layout);
+
+    plot
+}
+
+/// Authors committing per month, and the authors making their first commit that month
+pub fn plot_active_contributors(commits: &[Commit], tenures: &[Tenure]) -> Plot {
+    let mut plot = Plot::new();
+
+    let (Some(start), Some(end)) = (
+        tenures.iter().map(|tenure| tenure.first).min(),
+        tenures.iter().map(|tenure| tenure.last).max(),
+    ) else {
+        return plot;
+    };
+    let months = months_between(start, end);
+
+    let mut active: HashMap<NaiveDate, HashSet<&str>> = HashMap::new();
+    for commit in commits {
+        active
+            .entry(month_start(commit.date))
+            .or_default()
+            .insert(&commit.author);
+    }
+    let mut new: HashMap<NaiveDate, usize> = HashMap::new();
+    for tenure in tenures {
+        *new.entry(month_start(tenure.first)).or_insert(0) += 1;
+    }
+
+    let x: Vec<String> = months
+        .iter()
+        .map(|month| month.format("%Y-%m").to_string())
+        .collect();
+    let active: Vec<usize> = months
+        .iter()
+        .map(|month| active.get(month).map_or(0, HashSet::len))
+        .collect();
+    let new: Vec<usize> = months
+        .iter()
+        .map(|month| new.get(month).copied().unwrap_or(0))
+        .collect();
+    plot.add_trace(
+        Scatter::new(x.clone(), active)
+            .mode(Mode::Lines)
+            .name("Active contributors"),
+    );
+    plot.add_trace(
+        Scatter::new(x, new)
+            .mode(Mode::Lines)
+            .name("New contributors"),
+    );
+
+    let layout = Layout::new()
+        .width(PLOT_WIDTH - 50)
+        .title(Title::from("Active contributors per month"));
+    plot.set_layout(layout);
+
+    plot
+}
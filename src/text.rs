@@ -0,0 +1,27 @@
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ASCII: AtomicBool = AtomicBool::new(false);
+
+/// Make every renderer restrict its output to plain ASCII
+pub fn set_ascii(enabled: bool) {
+    ASCII.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_ascii() -> bool {
+    ASCII.load(Ordering::Relaxed)
+}
+
+/// Transliterate text to plain ASCII when running in `--ascii` mode, e.g. "Jürgen" becomes "Jurgen"
+pub fn safe(text: &str) -> Cow<'_, str> {
+    if is_ascii() && !text.is_ascii() {
+        Cow::Owned(deunicode::deunicode(text))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// Debug formatted path, safe to print in `--ascii` mode
+pub fn safe_path(path: &std::path::Path) -> String {
+    safe(&format!("{:?}", path)).into_owned()
+}
@@ -0,0 +1,70 @@
+use clap::ValueEnum;
+use plotly::layout::themes::{PLOTLY_DARK, PLOTLY_WHITE};
+use plotly::layout::Template;
+use serde::{Deserialize, Serialize};
+
+/// Built-in color schemes for the report and its plots
+#[derive(ValueEnum, Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum BuiltinTheme {
+    #[default]
+    Default,
+    Dark,
+    Minimal,
+}
+
+/// Colors of the page around the plots, exposed to the template as CSS variables
+#[derive(Serialize)]
+pub struct PageColors {
+    pub background: &'static str,
+    pub foreground: &'static str,
+    pub muted: &'static str,
+    pub border: &'static str,
+    pub item_border: &'static str,
+    pub thead_background: &'static str,
+    pub stripe_background: &'static str,
+}
+
+impl BuiltinTheme {
+    pub fn page_colors(&self) -> PageColors {
+        match self {
+            BuiltinTheme::Default => PageColors {
+                background: "white",
+                foreground: "black",
+                muted: "silver",
+                border: "rgb(190 190 190)",
+                item_border: "#d0d7de",
+                thead_background: "rgb(228 240 245)",
+                stripe_background: "rgb(237 238 242)",
+            },
+            // matches the background of the plotly_dark template
+            BuiltinTheme::Dark => PageColors {
+                background: "rgb(17 17 17)",
+                foreground: "rgb(242 245 250)",
+                muted: "rgb(120 120 120)",
+                border: "rgb(80 80 80)",
+                item_border: "rgb(60 60 60)",
+                thead_background: "rgb(40 44 52)",
+                stripe_background: "rgb(30 30 30)",
+            },
+            BuiltinTheme::Minimal => PageColors {
+                background: "white",
+                foreground: "rgb(42 63 95)",
+                muted: "rgb(160 170 185)",
+                border: "rgb(223 227 235)",
+                item_border: "rgb(223 227 235)",
+                thead_background: "white",
+                stripe_background: "rgb(248 249 251)",
+            },
+        }
+    }
+
+    /// The plotly template matching the theme, `None` keeps the plotly defaults
+    pub fn plot_template(&self) -> Option<&'static Template> {
+        match self {
+            BuiltinTheme::Default => None,
+            BuiltinTheme::Dark => Some(&PLOTLY_DARK),
+            BuiltinTheme::Minimal => Some(&PLOTLY_WHITE),
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use serde::Serialize;
+use std::time::Instant;
+
+#[derive(Serialize)]
+pub struct SectionTiming {
+    pub section: String,
+    pub seconds: f64,
+    /// Change in resident memory while running the section, only available on Linux
+    pub memory_delta_kb: Option<i64>,
+}
+
+/// Wall time and memory estimates of every analysis section
+#[derive(Serialize, Default)]
+pub struct Timings {
+    pub sections: Vec<SectionTiming>,
+}
+
+impl Timings {
+    pub fn time<T>(&mut self, section: &str, f: impl FnOnce() -> T) -> T {
+        let memory_before = resident_memory_kb();
+        let start = Instant::now();
+        let result = f();
+        let seconds = start.elapsed().as_secs_f64();
+        let memory_delta_kb = memory_before
+            .zip(resident_memory_kb())
+            .map(|(before, after)| after - before);
+
+        self.sections.push(SectionTiming {
+            section: section.to_string(),
+            seconds,
+            memory_delta_kb,
+        });
+        result
+    }
+
+    pub fn print(&self) {
+        for timing in &self.sections {
+            match timing.memory_delta_kb {
+                Some(kb) => eprintln!(
+                    "{:<20} {:>8.3}s {:>+10.1} MB",
+                    timing.section,
+                    timing.seconds,
+                    kb as f64 / 1024.0
+                ),
+                None => eprintln!("{:<20} {:>8.3}s", timing.section, timing.seconds),
+            }
+        }
+    }
+}
+
+fn resident_memory_kb() -> Option<i64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    line.split_whitespace().nth(1)?.parse().ok()
+}
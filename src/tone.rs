@@ -0,0 +1,170 @@
+use chrono::NaiveDate;
+use plotly::{
+    common::{DashType, Line, Mode, Title},
+    layout::Axis,
+    Layout, Plot, Scatter,
+};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::config::Tone;
+use crate::forecast::area;
+use crate::git::Commit;
+use crate::plots::{Granularity, PLOT_WIDTH};
+
+/// Areas charted, the ones with the most commits
+const TOP_AREAS: usize = 5;
+/// Markers listed, the most frequent ones
+const TOP_MARKERS: usize = 10;
+
+/// Commits with frustrated and upbeat words in their message
+#[derive(Default, Clone, Copy)]
+pub struct ToneCounts {
+    pub commits: usize,
+    pub frustrated: usize,
+    pub upbeat: usize,
+}
+
+impl ToneCounts {
+    fn add(&mut self, frustrated: bool, upbeat: bool) {
+        self.commits += 1;
+        self.frustrated += frustrated as usize;
+        self.upbeat += upbeat as usize;
+    }
+
+    pub fn frustrated_share(&self) -> f64 {
+        self.frustrated as f64 / self.commits.max(1) as f64
+    }
+
+    pub fn upbeat_share(&self) -> f64 {
+        self.upbeat as f64 / self.commits.max(1) as f64
+    }
+}
+
+pub struct AreaTone {
+    /// Top-level directory
+    pub area: String,
+    pub total: ToneCounts,
+    pub quarters: BTreeMap<NaiveDate, ToneCounts>,
+}
+
+pub struct ToneStats {
+    pub total: ToneCounts,
+    pub quarters: BTreeMap<NaiveDate, ToneCounts>,
+    /// Areas with the most commits, most commits first
+    pub areas: Vec<AreaTone>,
+    /// Frustrated words and how many commits use them, most used first
+    pub markers: Vec<(String, usize)>,
+}
+
+/// Lowercased words of a commit message, `wtf` and `WTF!` are the same word
+fn words(message: &str) -> HashSet<String> {
+    message
+        .split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .map(|word| word.trim_matches('\'').to_lowercase())
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+/// Commits whose message contains a word of the frustrated or upbeat lexicon, per quarter and per top-level
+/// directory. Merge commits are left out, as their messages are generated. `None` if no message matches
+pub fn get_tone(commits: &[Commit], lexicon: &Tone) -> Option<ToneStats> {
+    let frustrated: HashSet<String> = lexicon
+        .frustrated
+        .iter()
+        .map(|word| word.to_lowercase())
+        .collect();
+    let upbeat: HashSet<String> = lexicon
+        .upbeat
+        .iter()
+        .map(|word| word.to_lowercase())
+        .collect();
+
+    let mut total = ToneCounts::default();
+    let mut quarters: BTreeMap<NaiveDate, ToneCounts> = BTreeMap::new();
+    let mut areas: HashMap<&str, (ToneCounts, BTreeMap<NaiveDate, ToneCounts>)> = HashMap::new();
+    let mut markers: HashMap<String, usize> = HashMap::new();
+    for commit in commits.iter().filter(|commit| !commit.is_merge()) {
+        let words = words(&commit.message);
+        let used: Vec<&String> = words.intersection(&frustrated).collect();
+        let is_frustrated = !used.is_empty();
+        let is_upbeat = words.iter().any(|word| upbeat.contains(word));
+        for word in used {
+            *markers.entry(word.clone()).or_insert(0) += 1;
+        }
+
+        let quarter = Granularity::Quarter.bucket(commit.date);
+        total.add(is_frustrated, is_upbeat);
+        quarters
+            .entry(quarter)
+            .or_default()
+            .add(is_frustrated, is_upbeat);
+        let commit_areas: HashSet<&str> =
+            commit.files.iter().map(|file| area(&file.path)).collect();
+        for commit_area in commit_areas {
+            let (area_total, area_quarters) = areas.entry(commit_area).or_default();
+            area_total.add(is_frustrated, is_upbeat);
+            area_quarters
+                .entry(quarter)
+                .or_default()
+                .add(is_frustrated, is_upbeat);
+        }
+    }
+    if total.frustrated + total.upbeat == 0 {
+        return None;
+    }
+
+    let mut areas: Vec<AreaTone> = areas
+        .into_iter()
+        .map(|(area, (total, quarters))| AreaTone {
+            area: area.to_string(),
+            total,
+            quarters,
+        })
+        .collect();
+    areas.sort_by(|a, b| {
+        b.total
+            .commits
+            .cmp(&a.total.commits)
+            .then(a.area.cmp(&b.area))
+    });
+    areas.truncate(TOP_AREAS);
+    let mut markers: Vec<(String, usize)> = markers.into_iter().collect();
+    markers.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    markers.truncate(TOP_MARKERS);
+    Some(ToneStats {
+        total,
+        quarters,
+        areas,
+        markers,
+    })
+}
+
+/// Share of frustrated commits per quarter, for the whole repository and for the busiest areas
+pub fn plot_tone(tone: &ToneStats) -> Plot {
+    let mut plot = Plot::new();
+
+    let line = |quarters: &BTreeMap<NaiveDate, ToneCounts>| {
+        let x: Vec<String> = quarters.keys().map(|quarter| quarter.to_string()).collect();
+        let y: Vec<f64> = quarters
+            .values()
+            .map(|counts| 100.0 * counts.frustrated_share())
+            .collect();
+        Scatter::new(x, y).mode(Mode::LinesMarkers)
+    };
+    plot.add_trace(
+        line(&tone.quarters)
+            .name("All")
+            .line(Line::new().dash(DashType::Dash)),
+    );
+    for area in &tone.areas {
+        plot.add_trace(line(&area.quarters).name(&area.area));
+    }
+
+    let layout = Layout::new()
+        .width(PLOT_WIDTH - 50)
+        .title(Title::from("Frustrated commit messages per quarter"))
+        .y_axis(Axis::new().title(Title::from("% of commits")));
+    plot.set_layout(layout);
+
+    plot
+}